@@ -0,0 +1,71 @@
+//! Parse time of serde_json vs simd-json for a recipe catalog
+//!
+//! Run with `cargo bench --features simd-json --bench json`. Besides the
+//! small catalog in `testdata`, the catalog repeated to ~4 MB is measured,
+//! closer to a full `/recipe/all` response.
+use monsieurcc::schemas::Recipe;
+use std::time::{Duration, Instant};
+
+type Parser = fn(&[u8]) -> Vec<Recipe>;
+
+fn serde_json(json: &[u8]) -> Vec<Recipe> {
+    serde_json::from_slice(json).expect("Failed to deserialize")
+}
+
+fn simd_json(json: &[u8]) -> Vec<Recipe> {
+    // simd-json parses in place, copying is part of its cost in `Api` as well
+    let mut json = json.to_vec();
+    simd_json::serde::from_slice(&mut json).expect("Failed to deserialize")
+}
+
+/// Mean time of one parse, run for about a second
+fn measure(parse: Parser, json: &[u8]) -> Duration {
+    let started = Instant::now();
+    let mut runs = 0u32;
+    while started.elapsed() < Duration::from_secs(1) {
+        std::hint::black_box(parse(std::hint::black_box(json)));
+        runs += 1;
+    }
+    started.elapsed() / runs
+}
+
+fn repeated(catalog: &[u8], size: usize) -> Vec<u8> {
+    let inner = &catalog[1..catalog.len() - 1];
+    let mut json = b"[".to_vec();
+    while json.len() < size {
+        if json.len() > 1 {
+            json.push(b',');
+        }
+        json.extend_from_slice(inner);
+    }
+    json.push(b']');
+    json
+}
+
+fn main() {
+    let catalog = std::fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/recipe_all_catalog.json"
+    ))
+    .expect("Failed to read testdata");
+    let catalog = String::from_utf8(catalog).expect("Catalog is not UTF-8");
+    let catalog = catalog.trim().as_bytes();
+
+    for (name, json) in [
+        ("recipe_all_catalog.json", catalog.to_vec()),
+        ("catalog x1000", repeated(catalog, catalog.len() * 1000)),
+    ] {
+        assert_eq!(serde_json(&json), simd_json(&json));
+
+        let serde = measure(serde_json, &json);
+        let simd = measure(simd_json, &json);
+        println!(
+            "{} ({} bytes): serde_json {:?}, simd-json {:?}, speedup {:.2}x",
+            name,
+            json.len(),
+            serde,
+            simd,
+            serde.as_secs_f64() / simd.as_secs_f64()
+        );
+    }
+}
@@ -51,11 +51,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let output_path = match opts.output_file.clone() {
                 Some(fpath) => fpath,
                 None => {
-                    let filename = format!(
-                        "recipes_{}_{}.json",
-                        opts.language,
-                        opts.recipe_type.to_string()
-                    );
+                    let filename = format!("recipes_{}_{}.json", opts.language, opts.recipe_type);
                     PathBuf::from(filename)
                 }
             };
@@ -101,7 +97,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
                 None => {
                     println!("Using legacy way of fetching APK updates...");
-                    println!("! NOTE ! Provide serial number as argument to use non-legacy endpoint");
+                    println!(
+                        "! NOTE ! Provide serial number as argument to use non-legacy endpoint"
+                    );
                     api.get_apk_updates()
                         .await
                         .expect("Failed to download APK updates")
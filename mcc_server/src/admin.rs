@@ -95,18 +95,18 @@ async fn add_recipe() -> Result<Json<RecipeInternal>, &'static str> {
     Err("TODO POST /recipes")
 }
 
-#[get("/recipes/<id>")]
-async fn get_recipe(id: i32) -> Result<Json<RecipeInternal>, &'static str> {
+#[get("/recipes/<_id>")]
+async fn get_recipe(_id: i32) -> Result<Json<RecipeInternal>, &'static str> {
     Err("TODO GET /recipes/<id>")
 }
 
-#[put("/recipes/<id>")]
-async fn update_recipe(id: i32) -> Result<Json<RecipeInternal>, &'static str> {
+#[put("/recipes/<_id>")]
+async fn update_recipe(_id: i32) -> Result<Json<RecipeInternal>, &'static str> {
     Err("TODO PUT /recipes/<id>")
 }
 
-#[delete("/recipes/<id>")]
-async fn delete_recipe(id: i32) -> Result<Json<RecipeInternal>, &'static str> {
+#[delete("/recipes/<_id>")]
+async fn delete_recipe(_id: i32) -> Result<Json<RecipeInternal>, &'static str> {
     Err("TODO DELETE /recipes/<id>")
 }
 
@@ -1,3 +1,6 @@
+// Guards are not wired into any route yet
+#![allow(dead_code)]
+
 use std::str::FromStr;
 
 use monsieurcc::schemas::RecipeType;
@@ -1,3 +1,6 @@
+// diesel 1.x derive macros expand to impls inside anonymous consts
+#![allow(non_local_definitions)]
+
 #[macro_use]
 extern crate rocket;
 #[macro_use]
@@ -35,6 +38,7 @@ async fn run_migrations(rocket: Rocket<Build>) -> Rocket<Build> {
 }
 
 /// Serves as communication between the image upate worker and `/admin/recipes/update_images` endpoint
+#[allow(dead_code)]
 pub(crate) struct ImagesUpdate(pub mpsc::SyncSender<Vec<i32>>);
 
 /// Background worker/thread that awaits signal from `/admin/recipes/update_images` endpoint
@@ -42,15 +46,16 @@ pub(crate) struct ImagesUpdate(pub mpsc::SyncSender<Vec<i32>>);
 async fn spawn_image_import_worker(rocket: Rocket<Build>) -> Rocket<Build> {
     let (tx, rx) = mpsc::sync_channel(1);
 
-    let conn = db::DbConn::get_one(&rocket)
+    let _conn = db::DbConn::get_one(&rocket)
         .await
         .expect("database connection failed");
     thread::spawn(move || loop {
-        let trigger = rx.recv();
+        let _trigger = rx.recv();
     });
     rocket.manage(ImagesUpdate(tx))
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug)]
 pub(crate) struct MccConfig {
     pub default_language: String,
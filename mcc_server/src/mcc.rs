@@ -13,10 +13,11 @@ use monsieurcc::schemas::{
 };
 use rocket::{
     http::{ContentType, Status},
-    response::status::{NoContent, Unauthorized},
+    response::status::{Custom, NoContent, Unauthorized},
     serde::json::Json,
     Route,
 };
+use std::convert::TryFrom;
 
 #[post("/register", data = "<request>")]
 fn register(request: Json<RegistrationRequest<'_>>) -> (Status, (ContentType, &'static str)) {
@@ -129,15 +130,25 @@ async fn get_recipe_all(db: db::DbConn) -> Result<Json<Vec<Recipe>>, Unauthorize
 }
 
 #[get("/recipe/ids")]
-async fn get_recipe_ids(db: db::DbConn) -> Result<Json<RecipeIds>, Unauthorized<&'static str>> {
+async fn get_recipe_ids(db: db::DbConn) -> Result<Json<RecipeIds>, Custom<&'static str>> {
     let result: Result<Vec<i32>, diesel::result::Error> = db
         .run(move |conn| recipes::table.select(recipes::id).load(conn))
         .await;
 
     let recipe_ids = match result {
-        Ok(recipe_ids) => RecipeIds { ids: recipe_ids },
+        Ok(recipe_ids) => recipe_ids,
         Err(_) => {
-            return Err(Unauthorized(Some("Crap...")));
+            return Err(Custom(Status::Unauthorized, "Crap..."));
+        }
+    };
+    // Negative ids can't be served to the MCC, report them rather than hiding recipes
+    let recipe_ids = match recipe_ids.into_iter().map(u32::try_from).collect() {
+        Ok(ids) => RecipeIds { ids },
+        Err(_) => {
+            return Err(Custom(
+                Status::InternalServerError,
+                "Invalid recipe id in database",
+            ));
         }
     };
 
@@ -1,53 +1,421 @@
 use crate::{
+    clock::Clock,
+    error::{MccError, RequestContext},
     schemas::{self, RecipeType},
+    transport::HttpTransport,
     Result,
 };
+use futures::stream::{self, BoxStream, Stream, StreamExt, TryStreamExt};
 #[cfg(test)]
 use mockito;
-use reqwest::header::ACCEPT_LANGUAGE;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_ENCODING,
+    IF_MODIFIED_SINCE, LAST_MODIFIED,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    sync::{Semaphore, SemaphorePermit},
+};
+
+mod apk;
+mod backoff;
+mod batching;
+mod builder;
+mod circuit;
+mod conditional;
+mod config;
+mod consistency;
+mod cursor;
+mod deadline;
+#[cfg(feature = "dns-cache")]
+mod dns;
+mod drift;
+#[cfg(feature = "debug-dump")]
+mod dump;
+mod image_cache;
+mod partial;
+mod recipe_stream;
+mod retry;
+mod slow;
+mod sync;
+mod validation;
+
+pub use apk::{ApkUpdate, ApkVersion};
+pub use backoff::{BackoffStrategy, Exponential, Fibonacci, Fixed};
+pub use batching::BatchingApi;
+pub use builder::ApiBuilder;
+pub use config::ApiConfig;
+pub use consistency::{ConsistencyReport, CountMismatch, StructureCounts};
+pub use cursor::IdCursor;
+pub use deadline::DeadlineReport;
+pub use drift::DriftReport;
+pub use image_cache::ImageCache;
+pub use partial::{PartialRecipe, RecipeField};
+pub use retry::RetryBudget;
+pub use slow::SlowRequest;
+pub use sync::{Manifest, SyncEvent, SyncReport};
+pub use validation::ValidationReport;
 
 /// Api provides a client for fetching APK updates and recipes
+///
+/// Cloning is cheap, clones share the underlying connection pool and
+/// state like the circuit breaker.
+#[derive(Clone)]
 pub struct Api {
     session: reqwest::Client,
+    transport: Arc<dyn HttpTransport>,
+    bearer_token: Option<HeaderValue>,
+    circuit_breaker: Option<Arc<circuit::CircuitBreaker>>,
+    slow_requests: Option<Arc<slow::SlowRequestMonitor>>,
+    semaphore: Option<Arc<Semaphore>>,
+    query_params: Vec<(String, String)>,
+    default_headers: HeaderMap,
+    max_body_bytes: u64,
+    max_json_depth: usize,
+    max_apk_bytes_per_sec: Option<u64>,
+    exact_language_tags: bool,
+    base_urls: Vec<reqwest::Url>,
+    /// Index into `base_urls` of the host that answered last, shared between clones
+    preferred_host: Arc<AtomicUsize>,
+    clock: Arc<dyn Clock>,
+    conditional: Option<Arc<conditional::ConditionalCache>>,
+    #[cfg(feature = "debug-dump")]
+    dump_dir: Option<std::path::PathBuf>,
+    #[cfg(feature = "debug-dump")]
+    redaction: crate::redact::Redaction,
 }
 
 impl Api {
     /// Create new instance of Api
+    ///
+    /// Picks up a bearer token from `MCC_API_TOKEN`, see [`ApiBuilder`].
+    ///
+    /// # Panics
+    ///
+    /// If the HTTP client can't be created, e.g. the TLS backend fails to
+    /// initialize.
     pub fn new() -> Self {
-        Self {
-            session: reqwest::Client::new(),
+        Api::builder()
+            .build()
+            .expect("Failed to create Api with default settings")
+    }
+
+    /// Create new instance of Api together with the language to request
+    ///
+    /// The language is taken from the system locale, see
+    /// [`crate::locale::system_language`], falling back to
+    /// [`Api::FALLBACK_LANGUAGE`] if it's unset or not supported.
+    ///
+    /// # Panics
+    ///
+    /// Like [`Api::new`].
+    pub fn new_from_system() -> (Self, String) {
+        let language =
+            crate::locale::system_language().unwrap_or_else(|| Api::FALLBACK_LANGUAGE.to_string());
+        (Api::new(), language)
+    }
+
+    /// Create a builder to configure an Api instance
+    pub fn builder() -> ApiBuilder {
+        ApiBuilder::new()
+    }
+
+    /// Settings this instance was built with, for checking builder options took effect
+    pub fn effective_config(&self) -> ApiConfig {
+        let preferred = self.preferred_host.load(Ordering::Relaxed);
+        ApiConfig {
+            base_url: self.base_urls[preferred % self.base_urls.len()].to_string(),
+            base_urls: self.base_urls.iter().map(|url| url.to_string()).collect(),
+            bearer_token: self.bearer_token.is_some(),
+            query_params: self.query_params.clone(),
+            max_body_bytes: self.max_body_bytes,
+            max_json_depth: self.max_json_depth,
+            max_apk_bytes_per_sec: self.max_apk_bytes_per_sec,
+            exact_language_tags: self.exact_language_tags,
+            conditional_requests: self.conditional.is_some(),
+            circuit_breaker: self
+                .circuit_breaker
+                .as_ref()
+                .map(|breaker| (breaker.threshold(), breaker.cooldown())),
+            slow_request_threshold: self.slow_requests.as_ref().map(|slow| slow.threshold()),
+            shared_request_limit: self.semaphore.is_some(),
         }
     }
+
+    /// Copy of this Api with a query parameter added or replaced
+    ///
+    /// Meant for single calls, e.g. `api.with_query_param("include", "nutrition").get_recipe(..)`.
+    /// The copy shares connection pool and state with this instance.
+    pub fn with_query_param(&self, key: &str, value: &str) -> Api {
+        let mut api = self.clone();
+        set_query_param(&mut api.query_params, key, value);
+        api
+    }
+}
+
+/// Most memory reserved for a body upfront, an announced length can't be trusted
+const MAX_BODY_PREALLOCATION: u64 = 64 * 1024;
+
+/// First bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Accept a body labeled `Content-Encoding: gzip` unless it actually is compressed
+///
+/// No `Accept-Encoding` is sent and responses aren't decompressed, but
+/// misconfigured servers label plain bodies as gzip. Those are read as is.
+/// A body starting with the gzip magic bytes fails with a clear message
+/// instead of as unparseable JSON.
+fn check_gzip(body: &[u8]) -> Result<()> {
+    if body.starts_with(&GZIP_MAGIC) {
+        return Err("Response is gzip compressed, compressed responses are not supported".into());
+    }
+    Ok(())
+}
+
+/// Whether the request never reached the server, so another host may be tried
+fn is_connection_failure(err: &(dyn std::error::Error + 'static)) -> bool {
+    match err.downcast_ref::<MccError>() {
+        Some(MccError::ConnectionRefused { .. }) | Some(MccError::DnsFailure { .. }) => true,
+        Some(_) => false,
+        None => matches!(err.downcast_ref::<reqwest::Error>(), Some(e) if e.is_connect()),
+    }
+}
+
+/// Point `url` to scheme, host and port of `base`, keeping path and query
+fn rebase(url: &mut reqwest::Url, base: &reqwest::Url) -> Result<()> {
+    url.set_scheme(base.scheme())
+        .and_then(|_| url.set_host(base.host_str()).map_err(|_| ()))
+        .and_then(|_| url.set_port(base.port()))
+        .map_err(|_| format!("Failed to rebase {} onto {}", url, base).into())
+}
+
+fn single_header(name: HeaderName, value: HeaderValue) -> HeaderMap {
+    let mut headers = HeaderMap::with_capacity(1);
+    headers.insert(name, value);
+    headers
+}
+
+fn set_query_param(params: &mut Vec<(String, String)>, key: &str, value: &str) {
+    match params.iter_mut().find(|(k, _)| k == key) {
+        Some(param) => param.1 = value.to_string(),
+        None => params.push((key.to_string(), value.to_string())),
+    }
+}
+
+impl Default for Api {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Helpers
 impl Api {
-    fn create_url(path: &str) -> Result<reqwest::Url> {
+    /// Base URL used unless [`ApiBuilder::base_urls`] is set
+    pub(crate) fn default_base_url() -> String {
         #[cfg(not(test))]
-        let base = reqwest::Url::parse("https://mc20.monsieur-cuisine.com")?;
+        let base = Api::DEFAULT_BASE_URL.to_string();
 
         #[cfg(test)]
-        let base = reqwest::Url::parse(&mockito::server_url())?;
+        let base = mockito::server_url();
+
+        base
+    }
+
+    /// `path` on the base host that last answered
+    fn create_url(&self, path: &str) -> Result<reqwest::Url> {
+        let preferred = self.preferred_host.load(Ordering::Relaxed);
+        let base = &self.base_urls[preferred % self.base_urls.len()];
 
         Ok(base.join(path)?)
     }
+
+    /// Index of the base URL `url` points to, `None` for other hosts
+    fn base_index(&self, url: &reqwest::Url) -> Option<usize> {
+        self.base_urls
+            .iter()
+            .position(|base| base.origin() == url.origin())
+    }
+
+    /// Whether an error was caused by the server responding 404 Not Found
+    fn is_not_found(err: &(dyn std::error::Error + 'static)) -> bool {
+        MccError::without_context(err)
+            .downcast_ref::<reqwest::Error>()
+            .and_then(|e| e.status())
+            == Some(reqwest::StatusCode::NOT_FOUND)
+    }
+
+    /// Wait for a permit of the shared semaphore, if configured
+    async fn acquire_permit(&self) -> Result<Option<SemaphorePermit<'_>>> {
+        match &self.semaphore {
+            Some(semaphore) => Ok(Some(
+                semaphore
+                    .acquire()
+                    .await
+                    .map_err(|_| "Request semaphore was closed")?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Send request through the configured transport
+    ///
+    /// Requests to a base host that fail to connect are sent to the next
+    /// base host, see [`ApiBuilder::base_urls`]. Errors are wrapped in
+    /// [`MccError::Request`] for the request as last sent, a response keeps
+    /// that context for later errors, see [`Api::sent_context`].
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let request = request.build()?;
+        let mut context = RequestContext::of(&request);
+
+        match self.send_failover(request, &mut context).await {
+            Ok(mut response) => {
+                response.extensions_mut().insert(context);
+                Ok(response)
+            }
+            Err(err) => Err(MccError::in_context(Some(context), err)),
+        }
+    }
+
+    /// Context [`Api::send`] recorded for the request `response` answers
+    fn sent_context(response: &reqwest::Response) -> Option<RequestContext> {
+        response.extensions().get::<RequestContext>().cloned()
+    }
+
+    /// Failover part of [`Api::send`], keeping `context` at the request last sent
+    async fn send_failover(
+        &self,
+        mut request: reqwest::Request,
+        context: &mut RequestContext,
+    ) -> Result<reqwest::Response> {
+        let start = match self.base_index(request.url()) {
+            Some(start) if self.base_urls.len() > 1 => start,
+            _ => return self.send_once(request).await,
+        };
+
+        let mut attempt = 0;
+        loop {
+            let host = (start + attempt) % self.base_urls.len();
+            attempt += 1;
+            let fallback = if attempt < self.base_urls.len() {
+                request.try_clone()
+            } else {
+                None
+            };
+
+            *context = RequestContext::of(&request);
+            let result = self.send_once(request).await;
+            match (result, fallback) {
+                (Err(err), Some(mut fallback)) if is_connection_failure(err.as_ref()) => {
+                    let next = &self.base_urls[(host + 1) % self.base_urls.len()];
+                    rebase(fallback.url_mut(), next)?;
+                    request = fallback;
+                }
+                (result, _) => {
+                    if result.is_ok() {
+                        self.preferred_host.store(host, Ordering::Relaxed);
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Single attempt of [`Api::send`]
+    #[cfg(not(feature = "opentelemetry"))]
+    async fn send_once(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+        self.transport.send(request).await
+    }
+
+    /// Single attempt of [`Api::send`], inside a span following the
+    /// OpenTelemetry HTTP client conventions
+    ///
+    /// The span ends once the response headers arrived. Fields are named so
+    /// `tracing-opentelemetry` exports them as is.
+    #[cfg(feature = "opentelemetry")]
+    async fn send_once(&self, request: reqwest::Request) -> Result<reqwest::Response> {
+        use tracing::{field, Instrument};
+
+        let span = tracing::info_span!(
+            "HTTP request",
+            otel.name = %request.method(),
+            otel.kind = "client",
+            otel.status_code = field::Empty,
+            http.method = %request.method(),
+            http.url = %request.url(),
+            http.status_code = field::Empty,
+        );
+        let result = self.transport.send(request).instrument(span.clone()).await;
+
+        match &result {
+            Ok(response) => {
+                span.record("http.status_code", response.status().as_u16());
+                if response.status().is_client_error() || response.status().is_server_error() {
+                    span.record("otel.status_code", "ERROR");
+                }
+            }
+            Err(_) => {
+                span.record("otel.status_code", "ERROR");
+            }
+        }
+
+        result
+    }
+
+    /// Create request with the settings shared by all endpoints applied
+    ///
+    /// Managed headers replace default headers of the same name, see
+    /// [`ApiBuilder::default_headers`] for the order. The bearer token is only
+    /// sent to the base hosts, not to other hosts named in server data like
+    /// image CDNs.
+    fn request(&self, method: reqwest::Method, url: reqwest::Url) -> reqwest::RequestBuilder {
+        let own_host = self.base_index(&url).is_some();
+        let request = self
+            .session
+            .request(method, url)
+            .headers(self.default_headers.clone());
+
+        match &self.bearer_token {
+            Some(token) if own_host => request.headers(single_header(AUTHORIZATION, token.clone())),
+            _ => request,
+        }
+    }
 }
 
 /// APK / Android endpoints
 impl Api {
     const DOWNLOAD_PATH: &'static str = "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a";
+    const APK_PREFIX: &'static str = "MCUpdate";
+    const APK_SUFFIX: &'static str = ".apk";
 
     /// Get a list of MC2 APK files available to download
     pub async fn get_apk_updates(&self) -> Result<Vec<String>> {
-        let url = Api::create_url(&format!("{}/{}", Api::DOWNLOAD_PATH, "versions.txt"))?;
+        let url = self.create_url(&format!("{}/{}", Api::DOWNLOAD_PATH, "versions.txt"))?;
 
-        let result = self.session.get(url).send().await?.text().await?;
+        let permit = self.acquire_permit().await?;
+        let response = self.send(self.request(reqwest::Method::GET, url)).await?;
+        let context = Api::sent_context(&response);
+        let result = response
+            .text()
+            .await
+            .map_err(|err| MccError::in_context(context, err.into()))?;
+        drop(permit);
 
         let apk_urls = result
             .trim_end()
             .split('\n')
             .map(|x| {
-                let url = Api::create_url(&format!("{}/{}", Api::DOWNLOAD_PATH, x))
+                let url = self
+                    .create_url(&format!("{}/{}", Api::DOWNLOAD_PATH, x))
                     .expect("Failed to create URL");
 
                 url.to_string()
@@ -57,24 +425,143 @@ impl Api {
         Ok(apk_urls)
     }
 
+    /// Download URL of the APK of `version`, e.g. `0.123` for `MCUpdate0.123.apk`
+    ///
+    /// Only checks that the version is well-formed, see [`ApkVersion`], use
+    /// [`Api::apk_url_for_listed_version`] to make sure it's actually offered.
+    pub fn apk_url_for_version(&self, version: &str) -> Result<reqwest::Url> {
+        let version: ApkVersion = version.parse()?;
+
+        self.create_url(&format!(
+            "{}/{}{}{}",
+            Api::DOWNLOAD_PATH,
+            Api::APK_PREFIX,
+            version,
+            Api::APK_SUFFIX
+        ))
+    }
+
+    /// Like [`Api::apk_url_for_version`], failing unless the version is listed in `versions.txt`
+    pub async fn apk_url_for_listed_version(&self, version: &str) -> Result<reqwest::Url> {
+        let url = self.apk_url_for_version(version)?;
+
+        if !self.get_apk_updates().await?.contains(&url.to_string()) {
+            return Err(format!("APK version {} is not offered for download", version).into());
+        }
+
+        Ok(url)
+    }
+
+    /// Highest version listed in `versions.txt`
+    ///
+    /// Versions are compared part by part, see [`ApkVersion`]. Files not
+    /// named `MCUpdate<version>.apk` are ignored, it is an error if none is.
+    pub async fn latest_apk_version(&self) -> Result<ApkVersion> {
+        self.get_apk_updates()
+            .await?
+            .iter()
+            .filter_map(|url| ApkUpdate::from_url(url).ok()?.version())
+            .max()
+            .ok_or_else(|| "No valid APK version listed in versions.txt".into())
+    }
+
+    /// Whether `versions.txt` lists a version newer than `installed`
+    ///
+    /// A pre-release of the installed version counts as older, a release
+    /// following a pre-release as newer.
+    pub async fn is_update_available(&self, installed: &str) -> Result<bool> {
+        let installed: ApkVersion = installed.trim().parse()?;
+        Ok(self.latest_apk_version().await? > installed)
+    }
+
+    /// Poll `versions.txt` every `poll_interval` and yield APKs newer than any seen before
+    ///
+    /// The first poll happens right away and yields the newest APK currently
+    /// offered. Afterwards every newer version is yielded once, oldest first.
+    /// A failing poll yields the error and polling continues, the stream never
+    /// ends on its own. Files not named `MCUpdate<version>.apk` are ignored.
+    pub fn apk_update_stream(
+        &self,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<ApkUpdate>> + '_ {
+        struct State {
+            last_seen: Option<ApkVersion>,
+            pending: VecDeque<ApkUpdate>,
+            polled: bool,
+        }
+
+        let state = State {
+            last_seen: None,
+            pending: VecDeque::new(),
+            polled: false,
+        };
+
+        stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(update) = state.pending.pop_front() {
+                    return Some((Ok(update), state));
+                }
+
+                if state.polled {
+                    tokio::time::sleep(poll_interval).await;
+                }
+                state.polled = true;
+
+                let urls = match self.get_apk_updates().await {
+                    Ok(urls) => urls,
+                    Err(e) => return Some((Err(e), state)),
+                };
+                let mut newer: Vec<(ApkVersion, ApkUpdate)> = urls
+                    .iter()
+                    .filter_map(|url| ApkUpdate::from_url(url).ok())
+                    .filter_map(|update| Some((update.version()?, update)))
+                    .filter(|(version, _)| match &state.last_seen {
+                        Some(last_seen) => version > last_seen,
+                        None => true,
+                    })
+                    .collect();
+                newer.sort_by(|a, b| a.0.cmp(&b.0));
+                if state.last_seen.is_none() && newer.len() > 1 {
+                    newer.drain(..newer.len() - 1);
+                }
+
+                if let Some((version, _)) = newer.last() {
+                    state.last_seen = Some(version.clone());
+                }
+                state
+                    .pending
+                    .extend(newer.into_iter().map(|(_, update)| update));
+            }
+        })
+    }
+
     /// Get machine config metadata / APK updates by serial numer
     pub async fn get_apk_updates_for_machine(&self, serial: String) -> Result<Vec<String>> {
         let machineconfig = self.get_machine_config(serial).await?;
 
         // Fetch actual APK list from server
-        let update_url = Api::create_url(&format!(
+        let update_url = self.create_url(&format!(
             "{}/{}",
             machineconfig.config.updatelocation, "versions.txt"
         ))?;
-        let updates = self.session.get(update_url).send().await?.text().await?;
+        let permit = self.acquire_permit().await?;
+        let response = self
+            .send(self.request(reqwest::Method::GET, update_url))
+            .await?;
+        let context = Api::sent_context(&response);
+        let updates = response
+            .text()
+            .await
+            .map_err(|err| MccError::in_context(context, err.into()))?;
+        drop(permit);
 
         let apk_urls = updates
             .trim_end()
             .split('\n')
             .map(|x| {
-                let url =
-                    Api::create_url(&format!("{}/{}", machineconfig.config.updatelocation, x))
-                        .expect("Failed to create URL");
+                let url = self
+                    .create_url(&format!("{}/{}", machineconfig.config.updatelocation, x))
+                    .expect("Failed to create URL");
 
                 url.to_string()
             })
@@ -82,6 +569,75 @@ impl Api {
 
         Ok(apk_urls)
     }
+
+    /// Download an APK into `sink`, returns the number of bytes written
+    ///
+    /// The body is written chunk by chunk as it arrives, so the APK is never
+    /// fully held in memory. The sink is flushed but not shut down. Reads are
+    /// paced to [`ApiBuilder::max_apk_bytes_per_sec`] if set.
+    pub async fn stream_apk_to<W: AsyncWrite + Unpin>(
+        &self,
+        update: &ApkUpdate,
+        mut sink: W,
+    ) -> Result<u64> {
+        let _permit = self.acquire_permit().await?;
+        let response = self
+            .send(self.request(reqwest::Method::GET, update.url.clone()))
+            .await?;
+        let context = Api::sent_context(&response);
+        let in_context = |err| MccError::in_context(context.clone(), err);
+        let mut response = response
+            .error_for_status()
+            .map_err(|err| in_context(err.into()))?;
+
+        let started = self.clock.now();
+        let mut written = 0u64;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|err| in_context(MccError::from_transport(err)))?
+        {
+            sink.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+
+            if let Some(limit) = self.max_apk_bytes_per_sec {
+                let due = Duration::from_secs_f64(written as f64 / limit as f64);
+                let elapsed = self.clock.now().saturating_duration_since(started);
+                if due > elapsed {
+                    self.clock.sleep(due - elapsed).await;
+                }
+            }
+        }
+        sink.flush().await?;
+
+        Ok(written)
+    }
+
+    /// Download an APK into `dir`, returns the path of the written file
+    ///
+    /// The file is named after [`ApkUpdate::local_filename`], so entries of
+    /// a malicious `versions.txt` can't write outside of `dir`. The download
+    /// goes to a `.part` file first, which is renamed once complete.
+    pub async fn download_apk_to_dir(&self, update: &ApkUpdate, dir: &Path) -> Result<PathBuf> {
+        let filename = update.local_filename()?;
+        let path = dir.join(&filename);
+        let partial = dir.join(format!("{}.part", filename));
+
+        let written = async {
+            let mut file = tokio::fs::File::create(&partial).await?;
+            self.stream_apk_to(update, &mut file).await?;
+            file.sync_all().await?;
+            tokio::fs::rename(&partial, &path).await?;
+            Result::Ok(())
+        }
+        .await;
+        if written.is_err() {
+            let _ = tokio::fs::remove_file(&partial).await;
+        }
+        written?;
+
+        Ok(path)
+    }
 }
 
 // Machine
@@ -101,14 +657,14 @@ impl Api {
             .into());
         }
 
-        let url = Api::create_url(&format!("/mcc/api/v1/machineconfig/{}", serial))?;
-        let result = self
-            .session
-            .get(url)
-            .send()
-            .await?
+        let url = self.create_url(&format!("/mcc/api/v1/machineconfig/{}", serial))?;
+        let _permit = self.acquire_permit().await?;
+        let response = self.send(self.request(reqwest::Method::GET, url)).await?;
+        let context = Api::sent_context(&response);
+        let result = response
             .json::<schemas::MachineConfigResponse>()
-            .await?;
+            .await
+            .map_err(|err| MccError::in_context(context, err.into()))?;
 
         Ok(result)
     }
@@ -116,151 +672,1864 @@ impl Api {
 
 // Recipes
 impl Api {
+    /// Upper bound of in-flight requests issued by the bulk helpers
+    pub const MAX_CONCURRENT_REQUESTS: usize = 8;
+
+    /// Host of the official recipe API, used unless [`ApiBuilder::base_urls`] is set
+    pub const DEFAULT_BASE_URL: &'static str = "https://mc20.monsieur-cuisine.com";
+
+    /// Languages recipes are provided in, ISO 639-1 format
+    pub const SUPPORTED_LANGUAGES: &'static [&'static str] = &["de", "it", "fr", "pl", "en", "es"];
+
+    /// Language of [`Api::new_from_system`] if the system one isn't supported
+    pub const FALLBACK_LANGUAGE: &'static str = "de";
+
+    /// Pass as language to send no `Accept-Language` and let the server decide
+    pub const NO_LANGUAGE: &'static str = "";
+
+    /// Reduce a language tag to its lowercase ISO 639-1 base, e.g. `de-AT` or `DE` to `de`
+    ///
+    /// Both `-` and `_` are accepted as subtag separator.
+    pub fn normalize_language(language: &str) -> String {
+        language
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    }
+
+    /// Language as sent to the server, normalized unless exact tags were requested
+    fn language_tag(&self, language: &str) -> String {
+        if self.exact_language_tags {
+            language.to_string()
+        } else {
+            Api::normalize_language(language)
+        }
+    }
+
     /// Helper function to wrap calls against Recipe endpoint
     /// Language is provided in ISO 639-1 format
     //  (e.g. "de", "it", "fr", "pl", "en", "es")
-    async fn get_recipe_endpoint(
+    //  or as [`Api::NO_LANGUAGE`] to omit the header
+    //
+    //  The response is returned as is, check its status
+    async fn send_recipe_request(
         &self,
+        method: reqwest::Method,
         endpoint: &str,
         language: &str,
         recipe_type: Option<RecipeType>,
     ) -> Result<reqwest::Response> {
-        let recipe_type = recipe_type
-            .or(Some(RecipeType::Default))
-            .unwrap()
-            .to_string();
+        self.send_recipe_request_with(method, endpoint, language, recipe_type, HeaderMap::new())
+            .await
+    }
 
-        let url = Api::create_url(&format!("/mcc/api/v1/recipe/{}", endpoint))?;
-        let result = self
-            .session
-            .get(url)
-            .header(ACCEPT_LANGUAGE, language)
-            .header("X-Recipe-Type", recipe_type)
-            .send()
-            .await?;
+    /// Like [`Api::send_recipe_request`], with additional request headers
+    async fn send_recipe_request_with(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        headers: HeaderMap,
+    ) -> Result<reqwest::Response> {
+        // Errors raised before sending are reported for the request as planned
+        let planned = self.recipe_context(method.clone(), endpoint, language, recipe_type.clone());
+        let in_context = |err| MccError::in_context(planned.clone(), err);
 
-        Ok(result)
+        let language = self.language_tag(language);
+        let recipe_type = recipe_type.unwrap_or(RecipeType::Default).to_string();
+        let url = self.recipe_url(endpoint)?;
+
+        let recipe_type = single_header(
+            HeaderName::from_static("x-recipe-type"),
+            HeaderValue::from_str(&recipe_type).map_err(|err| in_context(err.into()))?,
+        );
+        let language = if language != Api::NO_LANGUAGE {
+            single_header(
+                ACCEPT_LANGUAGE,
+                HeaderValue::from_str(&language).map_err(|err| in_context(err.into()))?,
+            )
+        } else {
+            HeaderMap::new()
+        };
+
+        let attempt = match &self.circuit_breaker {
+            Some(breaker) => Some(breaker.before_request().map_err(in_context)?),
+            None => None,
+        };
+
+        let request = self
+            .request(method, url)
+            .headers(recipe_type)
+            .headers(headers)
+            .headers(language);
+
+        let result = self.send(request).await;
+
+        if let Some(attempt) = attempt {
+            match &result {
+                Ok(response) if !response.status().is_server_error() => attempt.success(),
+                _ => attempt.failure(),
+            }
+        }
+
+        result
     }
 
-    /// Get recipe ids for particular language / recipe type
-    pub async fn get_recipe_ids(
+    /// URL of a Recipe endpoint, with the configured query parameters
+    fn recipe_url(&self, endpoint: &str) -> Result<reqwest::Url> {
+        let mut url = self.create_url(&format!("/mcc/api/v1/recipe/{}", endpoint))?;
+        if !self.query_params.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.query_params);
+        }
+        Ok(url)
+    }
+
+    /// Context of a request to a Recipe endpoint before it is sent
+    fn recipe_context(
         &self,
+        method: reqwest::Method,
+        endpoint: &str,
         language: &str,
         recipe_type: Option<RecipeType>,
-    ) -> Result<Vec<i32>> {
-        let result = self
-            .get_recipe_endpoint("ids", language, recipe_type)
-            .await?
-            .json::<schemas::RecipeIds>()
-            .await?;
-
-        Ok(result.ids)
+    ) -> Option<RequestContext> {
+        Some(RequestContext {
+            method,
+            url: self.recipe_url(endpoint).ok()?,
+            language: self.language_tag(language),
+            recipe_type: recipe_type.unwrap_or(RecipeType::Default).to_string(),
+        })
     }
 
-    /// Get single recipe by id for particular language / recipe type
-    pub async fn get_recipe(
+    /// Fetch the full response body of a Recipe endpoint and `parse` it
+    ///
+    /// Errors, including those of `parse`, are wrapped in [`MccError::Request`]
+    /// with URL, language and recipe type of the request as sent.
+    async fn get_recipe_endpoint<T>(
         &self,
-        id: u32,
+        endpoint: &str,
         language: &str,
         recipe_type: Option<RecipeType>,
-    ) -> Result<schemas::Recipe> {
-        let result = self
-            .get_recipe_endpoint(&id.to_string(), language, recipe_type)
-            .await?
-            .json::<schemas::Recipe>()
-            .await?;
+        parse: impl FnOnce(Vec<u8>) -> Result<T>,
+    ) -> Result<T> {
+        let planned = self.recipe_context(
+            reqwest::Method::GET,
+            endpoint,
+            language,
+            recipe_type.clone(),
+        );
+        let mut sent = None;
 
-        Ok(result)
+        let body = self
+            .fetch_recipe_endpoint(endpoint, language, recipe_type, &mut sent)
+            .await;
+        let context = sent.or(planned);
+        match body {
+            Ok(body) => parse(body).map_err(|err| MccError::in_context(context, err)),
+            Err(err) => Err(MccError::in_context(context, err)),
+        }
     }
 
-    /// Get all recipes for particular language / recipe type
-    pub async fn get_recipes(
+    /// Key of a Recipe endpoint response in the conditional cache
+    fn cache_key(
         &self,
+        endpoint: &str,
         language: &str,
         recipe_type: Option<RecipeType>,
-    ) -> Result<Vec<schemas::Recipe>> {
-        let result = self
-            .get_recipe_endpoint("all", language, recipe_type)
-            .await?
-            .json::<Vec<schemas::Recipe>>()
+    ) -> conditional::CacheKey {
+        (
+            endpoint.to_string(),
+            self.language_tag(language),
+            recipe_type.unwrap_or(RecipeType::Default).to_string(),
+        )
+    }
+
+    /// Body of [`Api::get_recipe_endpoint`], setting `sent` once a response arrived
+    async fn fetch_recipe_endpoint(
+        &self,
+        endpoint: &str,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        sent: &mut Option<RequestContext>,
+    ) -> Result<Vec<u8>> {
+        let language = &self.language_tag(language);
+
+        // Only single recipes are revalidated, keeping whole catalogs around is too costly
+        let cache_key = match &self.conditional {
+            Some(_) if endpoint.parse::<u32>().is_ok() => {
+                Some(self.cache_key(endpoint, language, recipe_type.clone()))
+            }
+            _ => None,
+        };
+        if let (Some(cache), Some(key)) = (&self.conditional, &cache_key) {
+            if let Some(body) = cache.take_prefetched(key) {
+                return Ok(body.to_vec());
+            }
+        }
+
+        let _permit = self.acquire_permit().await?;
+        let started = self.slow_requests.as_ref().map(|_| self.clock.now());
+
+        #[cfg(feature = "debug-dump")]
+        let dump_path = self.dump_dir.as_ref().map(|dir| {
+            let recipe_type = recipe_type.clone().unwrap_or(RecipeType::Default);
+            dump::dump_path(dir, endpoint, language, &recipe_type.to_string())
+        });
+
+        let cached = match (&self.conditional, &cache_key) {
+            (Some(cache), Some(key)) => cache.get(key),
+            _ => None,
+        }
+        .filter(|cached| cached.last_modified.is_some());
+
+        let mut headers = HeaderMap::new();
+        if let Some(last_modified) = cached.as_ref().and_then(|c| c.last_modified.clone()) {
+            headers.insert(IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let response = self
+            .send_recipe_request_with(
+                reqwest::Method::GET,
+                endpoint,
+                language,
+                recipe_type,
+                headers,
+            )
             .await?;
+        *sent = Api::sent_context(&response);
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            // Without a cached body this request had no If-Modified-Since
+            let cached =
+                cached.ok_or("Server answered 304 Not Modified to an unconditional request")?;
+            if let (Some(monitor), Some(started)) = (&self.slow_requests, started) {
+                monitor.observe(endpoint, self.clock.now() - started);
+            }
+            return Ok(cached.body.to_vec());
+        }
+        let response = response.error_for_status()?;
+        let last_modified = response.headers().get(LAST_MODIFIED).cloned();
+        #[cfg(feature = "debug-dump")]
+        let head = dump_path
+            .as_ref()
+            .map(|_| dump::ResponseHead::of(&response, &self.redaction));
+        let body = self.read_body(response).await?;
 
-        Ok(result)
-    }
-}
+        if let (Some(cache), Some(key)) = (&self.conditional, cache_key) {
+            cache.store(key, last_modified, &body);
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::tests::get_testdata;
-    use mockito::mock;
-    use rstest::*;
+        if let (Some(monitor), Some(started)) = (&self.slow_requests, started) {
+            monitor.observe(endpoint, self.clock.now() - started);
+        }
 
-    #[fixture]
-    fn client() -> Api {
-        Api::new()
-    }
+        // Dumping is a diagnostic aid, failing to write must not fail the request
+        #[cfg(feature = "debug-dump")]
+        if let (Some(path), Some(head)) = (dump_path, head) {
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            if let Err(err) = dump::write(&path, &head, &body, &self.redaction) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(path = %path.display(), %err, "Failed to dump response");
+            }
+        }
 
-    #[rstest]
-    #[tokio::test]
-    async fn get_apk_updates(client: Api) {
-        let body = get_testdata("versions.txt").expect("Failed to get testdata");
+        Ok(body)
+    }
 
-        let _m = mock("GET", "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/versions.txt")
-            .with_status(200)
-            .with_header("content-type", "application/json")
-            .with_body(body)
-            .create();
+    /// Fetch a single recipe unless it wasn't modified since `since`
+    ///
+    /// Returns the body and its `Last-Modified`, `None` if the server answered
+    /// `304 Not Modified`. Bypasses [`ApiBuilder::conditional_requests`].
+    async fn revalidate_recipe(
+        &self,
+        id: u32,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        since: Option<&str>,
+    ) -> Result<Option<(Vec<u8>, Option<String>)>> {
+        let mut headers = HeaderMap::new();
+        if let Some(since) = since {
+            headers.insert(IF_MODIFIED_SINCE, HeaderValue::from_str(since)?);
+        }
 
-        let res = client
-            .get_apk_updates()
-            .await
-            .expect("Failed to fetch apk updates");
+        let _permit = self.acquire_permit().await?;
+        let response = self
+            .send_recipe_request_with(
+                reqwest::Method::GET,
+                &id.to_string(),
+                language,
+                recipe_type,
+                headers,
+            )
+            .await?;
+        let context = Api::sent_context(&response);
+        let in_context = |err| MccError::in_context(context.clone(), err);
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if since.is_none() {
+                return Err(in_context(
+                    "Server answered 304 Not Modified to an unconditional request".into(),
+                ));
+            }
+            return Ok(None);
+        }
+        let response = response
+            .error_for_status()
+            .map_err(|err| in_context(err.into()))?;
+        let last_modified = response
+            .headers()
+            .get(LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        let body = self.read_body(response).await.map_err(in_context)?;
 
-        assert!(res.len() == 3);
+        Ok(Some((body, last_modified)))
     }
 
-    #[rstest]
-    #[case::de_beta("de", Some(RecipeType::Beta))]
-    #[case::de_none("de", None)]
-    #[case::en_default("en", Some(RecipeType::Default))]
-    #[case::en_none("en", None)]
-    #[case::fr_live("fr", Some(RecipeType::Live))]
-    #[case::fr_none("fr", None)]
-    #[case::pl_default("pl", Some(RecipeType::Default))]
-    #[case::pl_none("pl", None)]
-    #[case::es_beta("es", Some(RecipeType::Beta))]
-    #[tokio::test]
-    async fn get_recipe_ids(
-        client: Api,
-        #[case] language: &str,
-        #[case] recipe_type: Option<RecipeType>,
-    ) {
-        let body = get_testdata("recipe_ids.json").expect("Failed to get testdata");
+    /// Read the full body, erroring out once it exceeds `max_body_bytes`
+    ///
+    /// See [`check_gzip`] for bodies declared as gzip compressed.
+    async fn read_body(&self, mut response: reqwest::Response) -> Result<Vec<u8>> {
+        let gzip_declared = matches!(
+            response.headers().get(CONTENT_ENCODING).and_then(|v| v.to_str().ok()),
+            Some(encoding) if encoding.trim().eq_ignore_ascii_case("gzip")
+        );
+        #[cfg(feature = "tracing")]
+        let url = response.url().clone();
+        let limit = self.max_body_bytes;
+        let too_large = || MccError::BodyTooLarge { limit };
 
-        let recipe_type_str = recipe_type
-            .as_ref()
-            .or(Some(&RecipeType::Default))
-            .unwrap()
-            .to_string();
+        // Announced length is only a hint, the counter below is what's enforced
+        let expected = response.content_length().unwrap_or(0);
+        if expected > limit {
+            return Err(too_large().into());
+        }
 
-        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
-            .match_header(&ACCEPT_LANGUAGE.to_string(), language)
-            .match_header("X-Recipe-Type", recipe_type_str.as_str())
-            .with_status(200)
-            .with_header("content-type", "application/json")
-            .with_body(body)
-            .create();
+        let mut body = Vec::with_capacity(expected.min(MAX_BODY_PREALLOCATION) as usize);
+        while let Some(chunk) = response.chunk().await.map_err(MccError::from_transport)? {
+            if (body.len() + chunk.len()) as u64 > limit {
+                return Err(too_large().into());
+            }
+            body.extend_from_slice(&chunk);
+        }
 
-        let res = client
-            .get_recipe_ids(language, recipe_type)
-            .await
-            .expect("Failed to get recipe ids");
+        if gzip_declared {
+            check_gzip(&body)?;
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                %url,
+                "Response declares gzip encoding but is not compressed, reading it as is"
+            );
+        }
 
-        assert_eq!(res.len(), 2);
+        Ok(body)
     }
 
-    #[rstest]
+    /// Iterate the recipe ids in batches, e.g. to page through them in a UI
+    ///
+    /// Nothing is fetched until the first [`IdCursor::next_batch`]. Async so
+    /// a server side cursor can be adopted without breaking callers.
+    pub async fn recipe_ids_cursor(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> IdCursor<'_> {
+        IdCursor::new(self, language, recipe_type)
+    }
+
+    /// Get recipe ids for particular language / recipe type
+    ///
+    /// Ids are returned in server order, which isn't guaranteed to be stable
+    /// between calls. Use [`Api::get_recipe_ids_sorted`] to diff or paginate.
+    pub async fn get_recipe_ids(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<u32>> {
+        let result: schemas::RecipeIds = self
+            .get_recipe_endpoint("ids", language, recipe_type, |body| {
+                crate::json::from_slice(&body, self.max_json_depth)
+            })
+            .await?;
+
+        Ok(result.ids)
+    }
+
+    /// Like [`Api::get_recipe_ids`], sorted ascending
+    pub async fn get_recipe_ids_sorted(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<u32>> {
+        let mut ids = self.get_recipe_ids(language, recipe_type).await?;
+        ids.sort_unstable();
+
+        Ok(ids)
+    }
+
+    /// Detect which recipe types are populated for a language
+    ///
+    /// Queries the ids endpoint for every type in [`RecipeType::ALL`]
+    /// concurrently. A type counts as available when the request succeeds and
+    /// returns at least one id. Failing requests just mark the type as
+    /// unavailable, an error is only returned if every probe failed.
+    pub async fn probe_recipe_types(&self, language: &str) -> Result<Vec<RecipeType>> {
+        let results = futures::future::join_all(
+            RecipeType::ALL
+                .iter()
+                .map(|t| self.get_recipe_ids(language, Some(t.clone()))),
+        )
+        .await;
+
+        if results.iter().all(|r| r.is_err()) {
+            if let Some(Err(e)) = results.first() {
+                return Err(format!("Failed to probe recipe types, err: {}", e).into());
+            }
+        }
+
+        let available = RecipeType::ALL
+            .iter()
+            .zip(results)
+            .filter(|(_, res)| matches!(res, Ok(ids) if !ids.is_empty()))
+            .map(|(t, _)| t.clone())
+            .collect();
+
+        Ok(available)
+    }
+
+    /// Number of recipes for every combination of `languages` and `types`
+    ///
+    /// Fetches the id lists concurrently, at most
+    /// [`Api::MAX_CONCURRENT_REQUESTS`] at a time. Combinations failing to
+    /// fetch are left out, an error is only returned if every one failed.
+    pub async fn coverage_matrix(
+        &self,
+        languages: &[&str],
+        types: &[RecipeType],
+    ) -> Result<HashMap<(String, RecipeType), usize>> {
+        let cells: Vec<(String, RecipeType)> = languages
+            .iter()
+            .flat_map(|language| types.iter().map(move |t| (language.to_string(), t.clone())))
+            .collect();
+        let total = cells.len();
+
+        let results: Vec<_> = stream::iter(cells)
+            .map(|(language, recipe_type)| async move {
+                let ids = self
+                    .get_recipe_ids(&language, Some(recipe_type.clone()))
+                    .await;
+                ((language, recipe_type), ids)
+            })
+            .buffer_unordered(Api::MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+
+        let mut matrix = HashMap::with_capacity(total);
+        let mut last_error = None;
+        for (cell, ids) in results {
+            match ids {
+                Ok(ids) => {
+                    matrix.insert(cell, ids.len());
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        match last_error {
+            Some(e) if matrix.is_empty() => {
+                Err(format!("Failed to get coverage matrix, err: {}", e).into())
+            }
+            _ => Ok(matrix),
+        }
+    }
+
+    /// Get single recipe by id for particular language / recipe type
+    pub async fn get_recipe(
+        &self,
+        id: u32,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<schemas::Recipe> {
+        self.get_recipe_endpoint(&id.to_string(), language, recipe_type, |body| {
+            crate::json::from_slice(&body, self.max_json_depth)
+        })
+        .await
+    }
+
+    /// Get single recipe by id, including response metadata if the server sent any
+    pub async fn get_recipe_envelope(
+        &self,
+        id: u32,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<schemas::RecipeEnvelope> {
+        self.get_recipe_endpoint(&id.to_string(), language, recipe_type, |body| {
+            crate::json::from_slice(&body, self.max_json_depth)
+        })
+        .await
+    }
+
+    /// Get single recipe by id with only `fields` deserialized
+    ///
+    /// The server has no field selection, so the full recipe is downloaded,
+    /// but all other fields are skipped while parsing. This also means they
+    /// don't need to match [`schemas::RecipeData`].
+    pub async fn get_recipe_fields(
+        &self,
+        id: u32,
+        fields: &[RecipeField],
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<PartialRecipe> {
+        self.get_recipe_endpoint(&id.to_string(), language, recipe_type, |body| {
+            crate::json::check_depth(&body, self.max_json_depth)?;
+            PartialRecipe::from_slice(&body, fields)
+        })
+        .await
+    }
+
+    /// Check whether a recipe exists without downloading it
+    ///
+    /// Sends a HEAD request, falling back to GET (body discarded) if the
+    /// server doesn't support HEAD. Any status besides success and 404 is
+    /// returned as error.
+    pub async fn recipe_exists(
+        &self,
+        id: u32,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<bool> {
+        let endpoint = id.to_string();
+        let _permit = self.acquire_permit().await?;
+
+        let mut response = self
+            .send_recipe_request(
+                reqwest::Method::HEAD,
+                &endpoint,
+                language,
+                recipe_type.clone(),
+            )
+            .await?;
+        if matches!(
+            response.status(),
+            reqwest::StatusCode::METHOD_NOT_ALLOWED | reqwest::StatusCode::NOT_IMPLEMENTED
+        ) {
+            response = self
+                .send_recipe_request(reqwest::Method::GET, &endpoint, language, recipe_type)
+                .await?;
+        }
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        let context = Api::sent_context(&response);
+        response
+            .error_for_status()
+            .map_err(|err| MccError::in_context(context, err.into()))?;
+        Ok(true)
+    }
+
+    /// Get multiple recipes by id concurrently, keeping individual failures
+    ///
+    /// Results are returned in the order of `ids`.
+    pub async fn get_recipes_by_ids_settled(
+        &self,
+        ids: &[u32],
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Vec<Result<schemas::Recipe>> {
+        stream::iter(ids.iter().copied())
+            .map(|id| self.get_recipe(id, language, recipe_type.clone()))
+            .buffered(Api::MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await
+    }
+
+    /// Get a recipe together with the recipes it links as related
+    ///
+    /// Related recipes are fetched concurrently and returned in the order
+    /// they are linked. Only direct links are followed, links of related
+    /// recipes are not, so cycles end there. Duplicate links and links to
+    /// the recipe itself are dropped, linked ids the server doesn't know
+    /// are skipped. Other failures fail the call.
+    pub async fn get_recipe_with_related(
+        &self,
+        id: u32,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<(schemas::Recipe, Vec<schemas::Recipe>)> {
+        let recipe = self.get_recipe(id, language, recipe_type.clone()).await?;
+
+        let mut seen = HashSet::new();
+        seen.insert(id);
+        let related_ids: Vec<u32> = recipe
+            .data
+            .related_recipes
+            .iter()
+            .copied()
+            .filter(|related| seen.insert(*related))
+            .collect();
+
+        let mut related = Vec::with_capacity(related_ids.len());
+        for result in self
+            .get_recipes_by_ids_settled(&related_ids, language, recipe_type)
+            .await
+        {
+            match result {
+                Ok(recipe) => related.push(recipe),
+                Err(e) if Api::is_not_found(e.as_ref()) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok((recipe, related))
+    }
+
+    /// Get multiple recipes by id concurrently, failing on the first error
+    ///
+    /// Recipes are returned in the order of `ids`.
+    pub async fn get_recipes_by_ids(
+        &self,
+        ids: &[u32],
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<schemas::Recipe>> {
+        self.get_recipes_by_ids_settled(ids, language, recipe_type)
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Like [`Api::get_recipes_by_ids`], returning whatever completed by `deadline`
+    ///
+    /// Never fails: recipes that couldn't be fetched end up in
+    /// [`DeadlineReport::failed`], ones still pending at the deadline in
+    /// [`DeadlineReport::unfinished`]. Pending requests are dropped. Duplicate
+    /// ids are fetched once. The deadline is measured on the clock of the Api,
+    /// see [`ApiBuilder::clock`].
+    pub async fn get_recipes_by_ids_until(
+        &self,
+        ids: &[u32],
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        deadline: Instant,
+    ) -> DeadlineReport {
+        self.fetch_until(ids, deadline, |id| {
+            self.get_recipe(id, language, recipe_type.clone())
+        })
+        .await
+    }
+
+    /// Fetch `ids` concurrently, collecting the results available at `deadline`
+    async fn fetch_until<'a, F, Fut>(
+        &self,
+        ids: &[u32],
+        deadline: Instant,
+        fetch: F,
+    ) -> DeadlineReport
+    where
+        F: Fn(u32) -> Fut,
+        Fut: Future<Output = Result<schemas::Recipe>> + 'a,
+    {
+        let expired = self
+            .clock
+            .sleep(deadline.saturating_duration_since(self.clock.now()));
+        let mut seen = HashSet::new();
+        let unique: Vec<u32> = ids.iter().copied().filter(|id| seen.insert(*id)).collect();
+        let results = stream::iter(unique.iter().copied())
+            .map(|id| {
+                let fetched = fetch(id);
+                async move { (id, fetched.await) }
+            })
+            .buffer_unordered(Api::MAX_CONCURRENT_REQUESTS)
+            .take_until(expired)
+            .collect()
+            .await;
+
+        DeadlineReport::new(&unique, results)
+    }
+
+    /// Stream recipes by id concurrently, each as soon as its request completes
+    ///
+    /// Unlike [`Api::get_recipes_by_ids`], recipes are yielded in no particular
+    /// order, a slow request doesn't hold back the ones behind it. Match them
+    /// up by [`schemas::RecipeData::id`]. A failing request yields an error
+    /// and the stream continues with the remaining ids.
+    pub fn get_recipes_unordered<'a>(
+        &'a self,
+        ids: &'a [u32],
+        language: &'a str,
+        recipe_type: Option<RecipeType>,
+    ) -> impl Stream<Item = Result<schemas::Recipe>> + 'a {
+        stream::iter(ids.iter().copied())
+            .map(move |id| self.get_recipe(id, language, recipe_type.clone()))
+            .buffer_unordered(Api::MAX_CONCURRENT_REQUESTS)
+    }
+
+    /// Fetch recipes in a background task to warm up the response cache
+    ///
+    /// Requires [`ApiBuilder::conditional_requests`], as that is where recipe
+    /// responses are kept. The next [`Api::get_recipe`] call for a prefetched
+    /// id is answered from the cache without any request, later ones
+    /// revalidate as usual. Recipes are kept even if their response has no
+    /// `Last-Modified`, until that first call. Ids already cached are
+    /// revalidated by the prefetch.
+    ///
+    /// Dropping the handle leaves the task running. Awaiting it reports the
+    /// ids that failed to fetch. Must be called within a tokio runtime.
+    pub fn prefetch(
+        &self,
+        ids: Vec<u32>,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<tokio::task::JoinHandle<Result<()>>> {
+        if self.conditional.is_none() {
+            return Err("Prefetching requires conditional requests to be enabled".into());
+        }
+
+        let client = self.clone();
+        let language = language.to_string();
+        Ok(tokio::spawn(async move {
+            let mut failed: Vec<u32> = stream::iter(ids)
+                .map(|id| {
+                    let client = &client;
+                    let language = &language;
+                    let recipe_type = recipe_type.clone();
+                    async move {
+                        let fetched = client.prefetch_recipe(id, language, recipe_type).await;
+                        (id, fetched)
+                    }
+                })
+                .buffer_unordered(Api::MAX_CONCURRENT_REQUESTS)
+                .filter_map(|(id, fetched)| async move { fetched.err().map(|_| id) })
+                .collect()
+                .await;
+
+            if failed.is_empty() {
+                return Ok(());
+            }
+            failed.sort_unstable();
+            Err(format!("Failed to prefetch recipes {:?}", failed).into())
+        }))
+    }
+
+    /// Fetch a single recipe into the conditional cache, see [`Api::prefetch`]
+    async fn prefetch_recipe(
+        &self,
+        id: u32,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<()> {
+        let cache = self
+            .conditional
+            .as_ref()
+            .ok_or("Prefetching requires conditional requests to be enabled")?;
+        let key = self.cache_key(&id.to_string(), language, recipe_type.clone());
+        let cached = cache.get(&key);
+        let since = cached
+            .as_ref()
+            .and_then(|cached| cached.last_modified.as_ref())
+            .and_then(|value| value.to_str().ok());
+
+        let fetched = self
+            .revalidate_recipe(id, language, recipe_type, since)
+            .await?;
+        let (body, last_modified) = match (fetched, cached) {
+            (Some((body, last_modified)), _) => (
+                body.into(),
+                last_modified.and_then(|value| HeaderValue::from_str(&value).ok()),
+            ),
+            (None, Some(cached)) => (cached.body, cached.last_modified),
+            (None, None) => {
+                return Err("Server answered 304 Not Modified to an unconditional request".into())
+            }
+        };
+        cache.store_prefetched(key, last_modified, body);
+
+        Ok(())
+    }
+
+    /// Get multiple recipes concurrently, each in its own language, keeping individual failures
+    ///
+    /// Results are returned in the order of `items`.
+    pub async fn get_recipes_by_id_lang_settled(
+        &self,
+        items: &[(u32, &str)],
+        recipe_type: Option<RecipeType>,
+    ) -> Vec<Result<schemas::Recipe>> {
+        stream::iter(items.iter().copied())
+            .map(|(id, language)| self.get_recipe(id, language, recipe_type.clone()))
+            .buffered(Api::MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await
+    }
+
+    /// Get multiple recipes concurrently, each in its own language
+    ///
+    /// Recipes are returned in the order of `items`. Fails on the first error,
+    /// its [`MccError::context`] names the recipe and language that failed.
+    pub async fn get_recipes_by_id_lang(
+        &self,
+        items: &[(u32, &str)],
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<schemas::Recipe>> {
+        self.get_recipes_by_id_lang_settled(items, recipe_type)
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Get single recipe by id, retrying transient failures as far as `budget` allows
+    async fn get_recipe_with_retries(
+        &self,
+        id: u32,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        budget: &RetryBudget,
+    ) -> Result<schemas::Recipe> {
+        let mut retries = 0;
+        loop {
+            let err = match self.get_recipe(id, language, recipe_type.clone()).await {
+                Ok(recipe) => return Ok(recipe),
+                Err(err) => err,
+            };
+
+            if !retry::is_retryable(err.as_ref()) || retries >= budget.retries_per_item_limit() {
+                return Err(err);
+            }
+            let delay = match budget.retry_delay(retries + 1) {
+                Some(delay) => delay,
+                None => return Err(err),
+            };
+            if !budget.take() {
+                return Err(MccError::RetryBudgetExhausted { source: err }.into());
+            }
+
+            retries += 1;
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                endpoint = %id,
+                attempt = retries,
+                delay_ms = delay.as_millis() as u64,
+                error = %err,
+                "Retrying recipe request"
+            );
+            self.clock.sleep(delay).await;
+        }
+    }
+
+    /// Get every recipe of the catalog by id, retrying transient failures
+    ///
+    /// Unlike [`Api::get_recipes`] each recipe is fetched on its own, so a
+    /// single broken recipe can be retried instead of the whole catalog.
+    /// Retries of all recipes draw from `budget`, once it is used up the next
+    /// failure fails the call with [`MccError::RetryBudgetExhausted`].
+    /// Recipes are returned in server order.
+    pub async fn get_full_catalog(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        budget: &RetryBudget,
+    ) -> Result<Vec<schemas::Recipe>> {
+        let ids = self.get_recipe_ids(language, recipe_type.clone()).await?;
+
+        stream::iter(ids)
+            .map(|id| self.get_recipe_with_retries(id, language, recipe_type.clone(), budget))
+            .buffered(Api::MAX_CONCURRENT_REQUESTS)
+            .try_collect()
+            .await
+    }
+
+    /// Like [`Api::get_full_catalog`], returning whatever completed by `deadline`
+    ///
+    /// Only fails if the id list can't be fetched or doesn't arrive in time,
+    /// see [`Api::get_recipes_by_ids_until`] for the handling of recipes.
+    /// Retries stop at the deadline as well.
+    pub async fn get_full_catalog_until(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        budget: &RetryBudget,
+        deadline: Instant,
+    ) -> Result<DeadlineReport> {
+        let expired = self
+            .clock
+            .sleep(deadline.saturating_duration_since(self.clock.now()));
+        let listing = self.get_recipe_ids(language, recipe_type.clone());
+        futures::pin_mut!(listing);
+        let ids = match futures::future::select(listing, expired).await {
+            futures::future::Either::Left((ids, _)) => ids?,
+            futures::future::Either::Right(_) => {
+                return Err("Deadline passed before the recipe id list arrived".into())
+            }
+        };
+
+        Ok(self
+            .fetch_until(&ids, deadline, |id| {
+                self.get_recipe_with_retries(id, language, recipe_type.clone(), budget)
+            })
+            .await)
+    }
+
+    /// Get the first `n` recipes in server order, without downloading all recipes
+    ///
+    /// Fetches the id list and then only the first `n` recipes concurrently.
+    /// Returns fewer recipes if less than `n` are available.
+    pub async fn get_first_recipes(
+        &self,
+        n: usize,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<schemas::Recipe>> {
+        let mut ids = self.get_recipe_ids(language, recipe_type.clone()).await?;
+        ids.truncate(n);
+
+        self.get_recipes_by_ids(&ids, language, recipe_type).await
+    }
+
+    /// Get a single recipe picked uniformly at random
+    ///
+    /// Fetches the id list and then only the chosen recipe. Pass a `seed` to
+    /// get a reproducible pick for the same id list. Returns
+    /// [`MccError::EmptyCatalog`] if the server has no recipes.
+    pub async fn get_random_recipe(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        seed: Option<u64>,
+    ) -> Result<schemas::Recipe> {
+        let ids = self.get_recipe_ids(language, recipe_type.clone()).await?;
+        if ids.is_empty() {
+            return Err(MccError::EmptyCatalog.into());
+        }
+
+        let mut rng = match seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        let id = ids[rng.usize(..ids.len())];
+
+        self.get_recipe(id, language, recipe_type).await
+    }
+
+    /// Get the recipes whose ids are not in `have`, e.g. to update a local store
+    ///
+    /// Fetches the id list and then only the missing recipes concurrently,
+    /// in server order.
+    pub async fn fetch_missing(
+        &self,
+        have: &HashSet<u32>,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<schemas::Recipe>> {
+        let ids = self.get_recipe_ids(language, recipe_type.clone()).await?;
+        let missing = ids
+            .into_iter()
+            .filter(|id| !have.contains(id))
+            .collect::<Vec<u32>>();
+
+        self.get_recipes_by_ids(&missing, language, recipe_type)
+            .await
+    }
+
+    /// Get the recipes added since `known_ids` was taken, to surface new content
+    ///
+    /// Like [`Api::fetch_missing`], but recipes the server already flags for
+    /// removal are left out, they are no content worth presenting.
+    pub async fn whats_new(
+        &self,
+        known_ids: &HashSet<u32>,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<schemas::Recipe>> {
+        let mut recipes = self.fetch_missing(known_ids, language, recipe_type).await?;
+        recipes.retain(|recipe| !recipe.data.remove);
+
+        Ok(recipes)
+    }
+
+    /// Compare locally stored recipes against the current catalog
+    ///
+    /// Fetches all recipes and compares them by [`schemas::Recipe::content_hash`],
+    /// reporting stale, vanished and newly available recipes. Use
+    /// [`Api::fetch_missing`] to then download the new ones.
+    pub async fn detect_drift(
+        &self,
+        local: &[schemas::Recipe],
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<DriftReport> {
+        let remote = self.get_recipes(language, recipe_type).await?;
+
+        Ok(DriftReport::new(local, &remote))
+    }
+
+    /// Get single recipe by id in multiple languages concurrently
+    ///
+    /// Languages the recipe is not available in (404) map to `None`,
+    /// any other failure fails the whole call.
+    pub async fn get_recipe_in_languages(
+        &self,
+        id: u32,
+        languages: &[&str],
+        recipe_type: Option<RecipeType>,
+    ) -> Result<HashMap<String, Option<schemas::Recipe>>> {
+        let results = futures::future::join_all(
+            languages
+                .iter()
+                .map(|lang| self.get_recipe(id, lang, recipe_type.clone())),
+        )
+        .await;
+
+        let mut recipes = HashMap::new();
+        for (lang, res) in languages.iter().zip(results) {
+            let recipe = match res {
+                Ok(recipe) => Some(recipe),
+                Err(e) if Api::is_not_found(e.as_ref()) => None,
+                Err(e) => return Err(e),
+            };
+            recipes.insert(lang.to_string(), recipe);
+        }
+
+        Ok(recipes)
+    }
+
+    /// Get single recipe by id in every language of [`Api::SUPPORTED_LANGUAGES`]
+    pub async fn get_recipe_all_languages(
+        &self,
+        id: u32,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<HashMap<String, Option<schemas::Recipe>>> {
+        self.get_recipe_in_languages(id, Api::SUPPORTED_LANGUAGES, recipe_type)
+            .await
+    }
+
+    /// Compare ingredient and step counts of a recipe across languages
+    ///
+    /// Languages the recipe is not available in are listed as missing
+    /// and don't count as mismatch.
+    pub async fn check_translation_consistency(
+        &self,
+        id: u32,
+        languages: &[&str],
+        recipe_type: Option<RecipeType>,
+    ) -> Result<ConsistencyReport> {
+        let translations = self
+            .get_recipe_in_languages(id, languages, recipe_type)
+            .await?;
+
+        Ok(ConsistencyReport::new(id, translations))
+    }
+
+    /// Bring a local copy of the catalog up to date, reporting progress as it goes
+    ///
+    /// Fetches the id list, skips the ids in `have` and fetches all others
+    /// concurrently. Yields [`SyncEvent::Started`] first and
+    /// [`SyncEvent::Finished`] last, a failing recipe doesn't stop the sync.
+    /// Only failing to fetch the id list yields an error and ends the stream.
+    pub fn sync_catalog_events<'a>(
+        &'a self,
+        have: &'a HashSet<u32>,
+        language: &'a str,
+        recipe_type: Option<RecipeType>,
+    ) -> impl Stream<Item = Result<SyncEvent>> + 'a {
+        enum Phase<'a> {
+            Listing,
+            Fetching(BoxStream<'a, (u32, Result<schemas::Recipe>)>),
+            Done,
+        }
+
+        struct State<'a> {
+            phase: Phase<'a>,
+            queued: VecDeque<SyncEvent>,
+            report: SyncReport,
+        }
+
+        let state = State {
+            phase: Phase::Listing,
+            queued: VecDeque::new(),
+            report: SyncReport::default(),
+        };
+
+        stream::unfold(state, move |mut state| {
+            let recipe_type = recipe_type.clone();
+            async move {
+                loop {
+                    if let Some(event) = state.queued.pop_front() {
+                        return Some((Ok(event), state));
+                    }
+
+                    let fetched = match &mut state.phase {
+                        Phase::Done => return None,
+                        Phase::Fetching(fetches) => fetches.next().await,
+                        Phase::Listing => {
+                            let ids = match self.get_recipe_ids(language, recipe_type.clone()).await
+                            {
+                                Ok(ids) => ids,
+                                Err(e) => {
+                                    state.phase = Phase::Done;
+                                    return Some((Err(e), state));
+                                }
+                            };
+
+                            let on_server: HashSet<u32> = ids.iter().copied().collect();
+                            state.report.removed = have.difference(&on_server).copied().collect();
+                            state.report.removed.sort_unstable();
+                            state
+                                .queued
+                                .push_back(SyncEvent::Started { total: ids.len() });
+
+                            let (skipped, missing): (Vec<u32>, Vec<u32>) =
+                                ids.into_iter().partition(|id| have.contains(id));
+                            state.queued.extend(
+                                skipped
+                                    .iter()
+                                    .map(|id| SyncEvent::RecipeSkipped { id: *id }),
+                            );
+                            state.report.skipped = skipped;
+
+                            let fetch_type = recipe_type.clone();
+                            let fetches =
+                                stream::iter(missing)
+                                    .map(move |id| {
+                                        let recipe_type = fetch_type.clone();
+                                        async move {
+                                            (id, self.get_recipe(id, language, recipe_type).await)
+                                        }
+                                    })
+                                    .buffered(Api::MAX_CONCURRENT_REQUESTS);
+                            state.phase = Phase::Fetching(fetches.boxed());
+                            continue;
+                        }
+                    };
+
+                    match fetched {
+                        Some((id, Ok(recipe))) => {
+                            state.report.synced.push(recipe);
+                            state.queued.push_back(SyncEvent::RecipeSynced { id });
+                        }
+                        Some((id, Err(e))) => {
+                            let error = e.to_string();
+                            state.report.failed.insert(id, error.clone());
+                            state
+                                .queued
+                                .push_back(SyncEvent::RecipeFailed { id, error });
+                        }
+                        None => {
+                            state.phase = Phase::Done;
+                            let report = std::mem::take(&mut state.report);
+                            state.queued.push_back(SyncEvent::Finished { report });
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Bring the copy of the catalog in `dir` up to date
+    ///
+    /// Recipes are stored as `<id>.json`, next to a [`Manifest`] of their
+    /// content hashes. The backend offers no lightweight hash or change
+    /// listing, so after fetching the id list every recipe in the manifest is
+    /// revalidated with the `Last-Modified` the server sent for it last time:
+    /// a `304 Not Modified` skips it without transferring the recipe. A
+    /// recipe sent anyway is compared by hash and only rewritten if it
+    /// changed. Recipes not in the manifest are fetched, ones no longer on
+    /// the server deleted.
+    ///
+    /// Recipes and manifest are replaced atomically, readers never see a
+    /// partially written file. The manifest is saved before returning, also
+    /// if some recipes failed to sync. Only failing to fetch the id list or
+    /// to write the manifest fails the call.
+    pub async fn sync_catalog(
+        &self,
+        dir: &Path,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<SyncReport> {
+        /// Result of a recipe on the server, with the `Last-Modified` it was sent with
+        enum Outcome {
+            NotModified,
+            Unchanged(Option<String>),
+            Changed(Box<schemas::Recipe>, u64, Option<String>),
+        }
+
+        tokio::fs::create_dir_all(dir).await?;
+        let manifest_path = dir.join(Manifest::FILENAME);
+        let mut manifest = Manifest::load_async(&manifest_path).await?;
+        let ids = self.get_recipe_ids(language, recipe_type.clone()).await?;
+
+        let results: Vec<(u32, Result<Outcome>)> = stream::iter(ids.clone())
+            .map(|id| {
+                let path = dir.join(format!("{}.json", id));
+                let known = manifest.get(id);
+                let since = manifest.last_modified(id).map(String::from);
+                let recipe_type = recipe_type.clone();
+                async move {
+                    let res = async {
+                        // A deleted file has to be fetched again, regardless of the manifest
+                        let present = tokio::fs::metadata(&path).await.is_ok();
+                        let since = since.filter(|_| present);
+                        let (body, last_modified) = match self
+                            .revalidate_recipe(id, language, recipe_type, since.as_deref())
+                            .await?
+                        {
+                            Some(fetched) => fetched,
+                            None => return Ok(Outcome::NotModified),
+                        };
+                        let recipe: schemas::Recipe =
+                            crate::json::from_slice(&body, self.max_json_depth)?;
+                        let hash = recipe.content_hash();
+                        if present && known == Some(hash) {
+                            return Ok(Outcome::Unchanged(last_modified));
+                        }
+                        crate::files::write_atomic_async(path, serde_json::to_vec(&recipe)?)
+                            .await?;
+                        Ok(Outcome::Changed(Box::new(recipe), hash, last_modified))
+                    }
+                    .await;
+                    (id, res)
+                }
+            })
+            .buffered(Api::MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+
+        let mut report = SyncReport::default();
+        for (id, res) in results {
+            match res {
+                Ok(Outcome::NotModified) => report.skipped.push(id),
+                Ok(Outcome::Unchanged(last_modified)) => {
+                    manifest.set_last_modified(id, last_modified);
+                    report.skipped.push(id);
+                }
+                Ok(Outcome::Changed(recipe, hash, last_modified)) => {
+                    manifest.insert(id, hash);
+                    manifest.set_last_modified(id, last_modified);
+                    report.synced.push(*recipe);
+                }
+                Err(e) => {
+                    report.failed.insert(id, e.to_string());
+                }
+            }
+        }
+
+        let on_server: HashSet<u32> = ids.into_iter().collect();
+        report.removed = manifest
+            .ids()
+            .filter(|id| !on_server.contains(id))
+            .collect();
+        for id in &report.removed {
+            manifest.remove(*id);
+            match tokio::fs::remove_file(dir.join(format!("{}.json", id))).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    report.failed.insert(*id, e.to_string());
+                }
+                _ => {}
+            }
+        }
+
+        crate::files::write_atomic_async(manifest_path, serde_json::to_vec(&manifest)?).await?;
+        Ok(report)
+    }
+
+    /// Fetch every recipe of the catalog one by one and report the ones failing
+    ///
+    /// Smoke test against production data: a recipe the crate can't
+    /// deserialize ends up in [`ValidationReport::parse_errors`], failing
+    /// requests in [`ValidationReport::fetch_errors`]. Only fetching the id
+    /// list fails the call itself.
+    pub async fn validate_catalog(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<ValidationReport> {
+        let ids = self.get_recipe_ids(language, recipe_type.clone()).await?;
+        let results = self
+            .get_recipes_by_ids_settled(&ids, language, recipe_type)
+            .await;
+
+        Ok(ValidationReport::new(&ids, results))
+    }
+
+    /// Get all recipes for particular language / recipe type
+    ///
+    /// Accepts both a bare array and a `{"recipes": [...]}` response body.
+    pub async fn get_recipes(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<schemas::Recipe>> {
+        let list: schemas::RecipeList = self
+            .get_recipe_endpoint("all", language, recipe_type, |body| {
+                crate::json::from_bytes(body, self.max_json_depth)
+            })
+            .await?;
+
+        Ok(list.into_recipes())
+    }
+
+    /// Get the `base` catalog with the recipes of `overlay` applied, e.g. beta over default
+    ///
+    /// Both catalogs are fetched concurrently and merged by id, a recipe of
+    /// `overlay` replaces the `base` recipe with the same id in place. Recipes
+    /// only in `overlay` are appended in overlay order. Also returns the ids
+    /// taken from `overlay`, ascending.
+    pub async fn get_merged_recipes(
+        &self,
+        language: &str,
+        base: RecipeType,
+        overlay: RecipeType,
+    ) -> Result<(Vec<schemas::Recipe>, Vec<u32>)> {
+        let (base, overlay) = futures::future::try_join(
+            self.get_recipes(language, Some(base)),
+            self.get_recipes(language, Some(overlay)),
+        )
+        .await?;
+
+        let mut merged = base;
+        let mut positions: HashMap<u32, usize> = merged
+            .iter()
+            .enumerate()
+            .map(|(pos, recipe)| (recipe.data.id, pos))
+            .collect();
+        let mut from_overlay = Vec::with_capacity(overlay.len());
+        for recipe in overlay {
+            from_overlay.push(recipe.data.id);
+            match positions.get(&recipe.data.id) {
+                Some(pos) => merged[*pos] = recipe,
+                None => {
+                    positions.insert(recipe.data.id, merged.len());
+                    merged.push(recipe);
+                }
+            }
+        }
+        from_overlay.sort_unstable();
+        from_overlay.dedup();
+
+        Ok((merged, from_overlay))
+    }
+
+    /// Field level differences of the recipes present in both `a` and `b`, e.g. default and beta
+    ///
+    /// Both catalogs are fetched concurrently and matched by id, see
+    /// [`schemas::Recipe::diff`] for how recipes are compared, `a` being the
+    /// before side. Only recipes with differences are returned, ascending by
+    /// id. Recipes present in just one of the catalogs are not reported.
+    pub async fn content_diff_between_types(
+        &self,
+        language: &str,
+        a: RecipeType,
+        b: RecipeType,
+    ) -> Result<Vec<(u32, crate::diff::RecipeDiff)>> {
+        let (a, b) = futures::future::try_join(
+            self.get_recipes(language, Some(a)),
+            self.get_recipes(language, Some(b)),
+        )
+        .await?;
+
+        let b: HashMap<u32, schemas::Recipe> = b.into_iter().map(|r| (r.data.id, r)).collect();
+        let mut diffs = vec![];
+        for recipe in &a {
+            if let Some(other) = b.get(&recipe.data.id) {
+                let diff = recipe.diff(other);
+                if !diff.is_empty() {
+                    diffs.push((recipe.data.id, diff));
+                }
+            }
+        }
+        diffs.sort_by_key(|(id, _)| *id);
+
+        Ok(diffs)
+    }
+
+    /// Mirror the catalog into `dest`, one `<id>.json` file per recipe
+    ///
+    /// The MCC backend offers no archive endpoint, so the mirror is always
+    /// built client side. It comes from the "all" endpoint though, which
+    /// already delivers the whole catalog in a single request. Existing
+    /// files of the same name are replaced atomically, so readers never see
+    /// a partially written recipe. Other files are left alone.
+    pub async fn download_catalog_archive(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        dest: &Path,
+    ) -> Result<()> {
+        let recipes = self.get_recipes(language, recipe_type).await?;
+
+        tokio::fs::create_dir_all(dest).await?;
+        for recipe in recipes {
+            let path = dest.join(format!("{}.json", recipe.data.id));
+            crate::files::write_atomic_async(path, serde_json::to_vec(&recipe)?).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get all recipes for particular language / recipe type matching `predicate`
+    ///
+    /// Filtering happens client side after fetching the full catalog.
+    pub async fn get_recipes_where<F>(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+        predicate: F,
+    ) -> Result<Vec<schemas::Recipe>>
+    where
+        F: Fn(&schemas::Recipe) -> bool,
+    {
+        let mut recipes = self.get_recipes(language, recipe_type).await?;
+        recipes.retain(|r| predicate(r));
+
+        Ok(recipes)
+    }
+
+    /// Get all recipes using the appliance program `program` in a guided cooking step
+    ///
+    /// Recipes without guided cooking never match, see [`schemas::Recipe::programs`].
+    pub async fn get_recipes_by_program(
+        &self,
+        program: schemas::StepMode,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<schemas::Recipe>> {
+        self.get_recipes_where(language, recipe_type, |r| r.programs().contains(&program))
+            .await
+    }
+
+    /// Get all recipes suitable for `diet`, see [`schemas::Recipe::is_suitable_for`]
+    ///
+    /// Recipes without dietary tags never match.
+    pub async fn get_recipes_by_diet(
+        &self,
+        diet: &crate::diet::DietaryTag,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<schemas::Recipe>> {
+        self.get_recipes_where(language, recipe_type, |r| r.is_suitable_for(diet))
+            .await
+    }
+
+    /// Stream all recipes for particular language / recipe type
+    ///
+    /// Recipes are parsed and yielded as the response arrives, only a single
+    /// recipe is buffered at a time. Order is the order the server sent. The
+    /// stream ends after the first error.
+    ///
+    /// [`ApiBuilder::max_body_bytes`] applies to each recipe rather than the
+    /// whole response.
+    pub fn stream_recipes<'a>(
+        &'a self,
+        language: &'a str,
+        recipe_type: Option<RecipeType>,
+    ) -> impl Stream<Item = Result<schemas::Recipe>> + 'a {
+        stream::unfold(Some(None), move |state| {
+            let recipe_type = recipe_type.clone();
+            async move {
+                let mut receiving = match state? {
+                    Some(receiving) => receiving,
+                    None => match self.start_recipe_stream(language, recipe_type).await {
+                        Ok(receiving) => receiving,
+                        Err(e) => return Some((Err(e), None)),
+                    },
+                };
+
+                match receiving.next().await? {
+                    Ok(recipe) => Some((Ok(recipe), Some(Some(receiving)))),
+                    Err(e) => Some((Err(e), None)),
+                }
+            }
+        })
+    }
+
+    /// Stream the recipes for particular language / recipe type matching `predicate`
+    ///
+    /// Like [`Api::stream_recipes`], recipes are filtered as they are parsed,
+    /// so memory stays bounded regardless of the catalog size. Errors are
+    /// passed through.
+    pub fn stream_recipes_where<'a, F>(
+        &'a self,
+        language: &'a str,
+        recipe_type: Option<RecipeType>,
+        predicate: F,
+    ) -> impl Stream<Item = Result<schemas::Recipe>> + 'a
+    where
+        F: Fn(&schemas::Recipe) -> bool + 'a,
+    {
+        self.stream_recipes(language, recipe_type)
+            .filter(move |r| futures::future::ready(r.as_ref().map_or(true, &predicate)))
+    }
+
+    async fn start_recipe_stream(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<recipe_stream::RecipeStream<'_>> {
+        let permit = self.acquire_permit().await?;
+        let response = self
+            .send_recipe_request(reqwest::Method::GET, "all", language, recipe_type)
+            .await?;
+        let context = Api::sent_context(&response);
+        let response = response
+            .error_for_status()
+            .map_err(|err| MccError::in_context(context, err.into()))?;
+
+        Ok(recipe_stream::RecipeStream::new(
+            response,
+            self.max_json_depth,
+            self.max_body_bytes,
+            permit,
+        ))
+    }
+
+    /// Get the distinct ingredient names of all recipes, sorted
+    ///
+    /// See [`crate::stats::distinct_ingredients`] for matching and order.
+    pub async fn list_all_ingredients(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<String>> {
+        let recipes = self.get_recipes(language, recipe_type).await?;
+
+        Ok(crate::stats::distinct_ingredients(
+            &recipes,
+            &Api::normalize_language(language),
+        ))
+    }
+
+    /// Get id / title pairs of all recipes, sorted by id
+    ///
+    /// Minimal data to render a navigable list, fetched via the "all" endpoint.
+    pub async fn get_recipe_id_titles(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Vec<(u32, String)>> {
+        let recipes = self.get_recipes(language, recipe_type).await?;
+
+        let mut titles = recipes
+            .into_iter()
+            .map(|r| (r.data.id, r.data.name))
+            .collect::<Vec<(u32, String)>>();
+        titles.sort_by_key(|(id, _)| *id);
+
+        Ok(titles)
+    }
+
+    /// Get all recipes keyed by id
+    ///
+    /// If the server sends an id more than once, the recipe listed last wins.
+    pub async fn get_recipe_map(
+        &self,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<BTreeMap<u32, schemas::Recipe>> {
+        let recipes = self.get_recipes(language, recipe_type).await?;
+
+        Ok(recipes.into_iter().map(|r| (r.data.id, r)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use mockito::{mock, Matcher};
+    use rstest::*;
+
+    #[fixture]
+    fn client() -> Api {
+        Api::new()
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_apk_updates(client: Api) {
+        let body = get_testdata("versions.txt").expect("Failed to get testdata");
+
+        let _m = mock("GET", "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/versions.txt")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+
+        let res = client
+            .get_apk_updates()
+            .await
+            .expect("Failed to fetch apk updates");
+
+        assert!(res.len() == 3);
+    }
+
+    #[rstest]
+    #[case::listed(get_testdata("versions.txt").expect("Failed to get testdata"), Some("0.125"))]
+    #[case::numeric_order(b"MCUpdate1.9.apk\nMCUpdate1.10.apk\nMCUpdate1.2.apk\n".to_vec(), Some("1.10"))]
+    #[case::skips_invalid(b"notes.txt\nMCUpdate1.x.apk\nMCUpdate0.9.apk\n".to_vec(), Some("0.9"))]
+    #[case::empty(b"".to_vec(), None)]
+    #[case::only_invalid(b"MCUpdate.apk\nreadme\n".to_vec(), None)]
+    #[tokio::test]
+    async fn latest_apk_version(
+        client: Api,
+        #[case] body: Vec<u8>,
+        #[case] expected: Option<&str>,
+    ) {
+        let _m = mock("GET", "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/versions.txt")
+            .with_status(200)
+            .with_body(body)
+            .create();
+
+        let latest = client.latest_apk_version().await;
+
+        match expected {
+            Some(version) => assert_eq!(
+                latest.expect("Failed to get latest version").to_string(),
+                version
+            ),
+            None => assert!(latest
+                .expect_err("No version should be found")
+                .to_string()
+                .contains("No valid APK version")),
+        }
+    }
+
+    #[rstest]
+    #[case::equal("1.10", Some(false))]
+    #[case::older("1.9", Some(true))]
+    #[case::newer("1.11", Some(false))]
+    #[case::longer_newer("1.10.1", Some(false))]
+    #[case::trailing_zero("1.10.0", Some(false))]
+    #[case::prerelease_installed("1.10-beta.3", Some(true))]
+    #[case::prerelease_of_next("1.11-alpha", Some(false))]
+    #[case::invalid("1.x", None)]
+    #[tokio::test]
+    async fn is_update_available(
+        client: Api,
+        #[case] installed: &str,
+        #[case] expected: Option<bool>,
+    ) {
+        let _m = mock("GET", "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/versions.txt")
+            .with_status(200)
+            .with_body("MCUpdate1.9.apk\nMCUpdate1.10-beta.1.apk\nMCUpdate1.10.apk\n")
+            .create();
+
+        let available = client.is_update_available(installed).await;
+
+        match expected {
+            Some(expected) => assert_eq!(available.expect("Failed to check"), expected),
+            None => assert!(available
+                .expect_err("Installed version should be rejected")
+                .to_string()
+                .contains("Invalid APK version")),
+        }
+    }
+
+    #[rstest]
+    #[case::plain(
+        "0.123",
+        Some("/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate0.123.apk")
+    )]
+    #[case::three_parts(
+        "1.2.3",
+        Some("/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate1.2.3.apk")
+    )]
+    #[case::leading_zero(
+        "0.050",
+        Some("/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate0.050.apk")
+    )]
+    #[case::prerelease(
+        "1.10-beta.2",
+        Some("/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate1.10-beta.2.apk")
+    )]
+    #[case::empty("", None)]
+    #[case::empty_part("0..1", None)]
+    #[case::letters("0.12a", None)]
+    #[case::path("../0.123", None)]
+    #[case::whole_name("MCUpdate0.123.apk", None)]
+    fn apk_url_for_version(client: Api, #[case] version: &str, #[case] path: Option<&str>) {
+        let res = client.apk_url_for_version(version);
+
+        assert_eq!(
+            res.ok().map(|url| url.path().to_string()),
+            path.map(String::from)
+        );
+    }
+
+    #[rstest]
+    #[case::listed("0.124", true)]
+    #[case::not_listed("0.126", false)]
+    #[tokio::test]
+    async fn apk_url_for_listed_version(client: Api, #[case] version: &str, #[case] listed: bool) {
+        let _m = mock("GET", "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/versions.txt")
+            .with_status(200)
+            .with_body(get_testdata("versions.txt").expect("Failed to get testdata"))
+            .create();
+
+        let res = client.apk_url_for_listed_version(version).await;
+
+        assert_eq!(res.is_ok(), listed);
+    }
+
+    #[rstest]
+    #[case::de_beta("de", Some(RecipeType::Beta))]
+    #[case::de_none("de", None)]
+    #[case::en_default("en", Some(RecipeType::Default))]
+    #[case::en_none("en", None)]
+    #[case::fr_live("fr", Some(RecipeType::Live))]
+    #[case::fr_none("fr", None)]
+    #[case::pl_default("pl", Some(RecipeType::Default))]
+    #[case::pl_none("pl", None)]
+    #[case::es_beta("es", Some(RecipeType::Beta))]
+    #[tokio::test]
+    async fn get_recipe_ids(
+        client: Api,
+        #[case] language: &str,
+        #[case] recipe_type: Option<RecipeType>,
+    ) {
+        let body = get_testdata("recipe_ids.json").expect("Failed to get testdata");
+
+        let recipe_type_str = recipe_type
+            .as_ref()
+            .unwrap_or(&RecipeType::Default)
+            .to_string();
+
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header(ACCEPT_LANGUAGE.as_str(), language)
+            .match_header("X-Recipe-Type", recipe_type_str.as_str())
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+
+        let res = client
+            .get_recipe_ids(language, recipe_type)
+            .await
+            .expect("Failed to get recipe ids");
+
+        assert_eq!(res.len(), 2);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn coverage_matrix(client: Api) {
+        let _de_default = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "de")
+            .match_header("X-Recipe-Type", "default")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids_unsorted.json").expect("Failed to get testdata"))
+            .create();
+        let _de_beta = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "de")
+            .match_header("X-Recipe-Type", "beta")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[]}"#)
+            .create();
+        let _en_default = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "en")
+            .match_header("X-Recipe-Type", "default")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+        let _en_beta = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "en")
+            .match_header("X-Recipe-Type", "beta")
+            .with_status(500)
+            .create();
+
+        let matrix = client
+            .coverage_matrix(&["de", "en"], &[RecipeType::Default, RecipeType::Beta])
+            .await
+            .expect("Failed to get coverage matrix");
+
+        assert_eq!(matrix.len(), 3);
+        assert_eq!(matrix[&("de".to_string(), RecipeType::Default)], 5);
+        assert_eq!(matrix[&("de".to_string(), RecipeType::Beta)], 0);
+        assert_eq!(matrix[&("en".to_string(), RecipeType::Default)], 2);
+        assert!(!matrix.contains_key(&("en".to_string(), RecipeType::Beta)));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn coverage_matrix_all_failed(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(500)
+            .create();
+
+        let res = client
+            .coverage_matrix(&["de"], &[RecipeType::Default, RecipeType::Live])
+            .await;
+
+        assert!(res.is_err());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipe_ids_sorted(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids_unsorted.json").expect("Failed to get testdata"))
+            .expect(2)
+            .create();
+
+        let unsorted = client
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+        let sorted = client
+            .get_recipe_ids_sorted("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        assert_eq!(unsorted, vec![25012, 311, 25011, 4711, 1002]);
+        assert_eq!(sorted.len(), unsorted.len());
+        assert!(sorted.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[rstest]
+    #[case::de_beta("de", Some(RecipeType::Beta))]
+    #[case::de_none("de", None)]
+    #[case::en_default("en", Some(RecipeType::Default))]
+    #[case::en_none("en", None)]
+    #[case::fr_live("fr", Some(RecipeType::Live))]
+    #[case::fr_none("fr", None)]
+    #[case::pl_default("pl", Some(RecipeType::Default))]
+    #[case::pl_none("pl", None)]
+    #[case::es_beta("es", Some(RecipeType::Beta))]
+    #[tokio::test]
+    async fn get_recipe(
+        client: Api,
+        #[case] language: &str,
+        #[case] recipe_type: Option<RecipeType>,
+    ) {
+        let body = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+
+        let recipe_type_str = recipe_type
+            .as_ref()
+            .unwrap_or(&RecipeType::Default)
+            .to_string();
+
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), language)
+            .match_header("X-Recipe-Type", recipe_type_str.as_str())
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+
+        let res = client
+            .get_recipe(25011, language, recipe_type)
+            .await
+            .expect("Failed to get recipe");
+
+        assert_eq!(res.data.id, 25011);
+    }
+
+    #[rstest]
     #[case::de_beta("de", Some(RecipeType::Beta))]
     #[case::de_none("de", None)]
     #[case::en_default("en", Some(RecipeType::Default))]
@@ -271,73 +2540,2616 @@ mod tests {
     #[case::pl_none("pl", None)]
     #[case::es_beta("es", Some(RecipeType::Beta))]
     #[tokio::test]
-    async fn get_recipe(
+    async fn get_recipes_all(
+        client: Api,
+        #[case] language: &str,
+        #[case] recipe_type: Option<RecipeType>,
+    ) {
+        let recipe_type_str = recipe_type
+            .as_ref()
+            .unwrap_or(&RecipeType::Default)
+            .to_string();
+
+        let filename = format!("recipe_all_{}.json", recipe_type_str);
+        let body = get_testdata(&filename).expect("Failed to get testdata");
+
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .match_header(ACCEPT_LANGUAGE.as_str(), language)
+            .match_header("X-Recipe-Type", recipe_type_str.as_str())
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+
+        let res = client
+            .get_recipes(language, recipe_type)
+            .await
+            .expect("Failed to get recipe");
+
+        assert!(!res.is_empty());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipes_by_ids(client: Api) {
+        let _m1 = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+        let _m2 = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .create();
+
+        let res = client
+            .get_recipes_by_ids(&[25012, 25011, 25012], "de", None)
+            .await
+            .expect("Failed to get recipes");
+
+        let ids: Vec<u32> = res.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![25012, 25011, 25012]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipe_with_related(client: Api) {
+        let primary = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                get_testdata("recipe_single_25011_related.json").expect("Failed to get testdata"),
+            )
+            .expect(1)
+            .create();
+        let related = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+        let _missing = mock("GET", "/mcc/api/v1/recipe/4711")
+            .with_status(404)
+            .create();
+
+        let (recipe, related_recipes) = client
+            .get_recipe_with_related(25011, "de", None)
+            .await
+            .expect("Failed to get related recipes");
+
+        assert_eq!(recipe.data.related_recipes, vec![25012, 25011, 4711, 25012]);
+        let ids: Vec<u32> = related_recipes.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![25012]);
+        primary.assert();
+        related.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipes_by_ids_settled(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+
+        let res = client
+            .get_recipes_by_ids_settled(&[25011, 99999], "de", None)
+            .await;
+
+        assert_eq!(res.len(), 2);
+        assert!(res[0].is_ok());
+        assert!(res[1].is_err());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn probe_recipe_types(client: Api) {
+        let body = get_testdata("recipe_ids.json").expect("Failed to get testdata");
+
+        let _m1 = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header("X-Recipe-Type", "default")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+        let _m2 = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header("X-Recipe-Type", "live")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[]}"#)
+            .create();
+        let _m3 = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header("X-Recipe-Type", "beta")
+            .with_status(500)
+            .create();
+
+        let res = client
+            .probe_recipe_types("de")
+            .await
+            .expect("Failed to probe recipe types");
+
+        assert_eq!(res, vec![RecipeType::Default]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn probe_recipe_types_all_failing(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(500)
+            .create();
+
+        let res = client.probe_recipe_types("de").await;
+
+        assert!(res.is_err());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipe_all_languages(client: Api) {
+        let body = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+
+        let _m_missing = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(
+                ACCEPT_LANGUAGE.as_str(),
+                Matcher::Regex("^(it|fr|pl|es)$".into()),
+            )
+            .with_status(404)
+            .create();
+        let _m_de = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "de")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(&body)
+            .create();
+        let _m_en = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "en")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(&body)
+            .create();
+
+        let res = client
+            .get_recipe_all_languages(25011, None)
+            .await
+            .expect("Failed to get recipe in all languages");
+
+        assert_eq!(res.len(), Api::SUPPORTED_LANGUAGES.len());
+        assert!(res["de"].is_some());
+        assert!(res["en"].is_some());
+        for lang in &["it", "fr", "pl", "es"] {
+            assert!(res[*lang].is_none(), "Expected no recipe for {}", lang);
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipe_in_languages_server_error(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(500)
+            .create();
+
+        let res = client.get_recipe_in_languages(25011, &["de"], None).await;
+
+        assert!(res.is_err());
+    }
+
+    #[rstest]
+    #[case::fewer(1, vec![25011])]
+    #[case::exact(2, vec![25011, 25012])]
+    #[case::more_than_available(10, vec![25011, 25012])]
+    #[case::none(0, vec![])]
+    #[tokio::test]
+    async fn get_first_recipes(client: Api, #[case] n: usize, #[case] expected: Vec<u32>) {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+        let _m1 = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+        let _m2 = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .create();
+        let m_all = mock("GET", "/mcc/api/v1/recipe/all").expect(0).create();
+
+        let res = client
+            .get_first_recipes(n, "de", None)
+            .await
+            .expect("Failed to get first recipes");
+
+        let ids: Vec<u32> = res.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, expected);
+        m_all.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_random_recipe_seeded(client: Api) {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+        let _m1 = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+        let _m2 = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .create();
+
+        let mut picks = vec![];
+        for _ in 0..3 {
+            let recipe = client
+                .get_random_recipe("de", None, Some(42))
+                .await
+                .expect("Failed to get random recipe");
+            picks.push(recipe.data.id);
+        }
+
+        assert!([25011, 25012].contains(&picks[0]));
+        assert!(picks.iter().all(|id| *id == picks[0]));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_random_recipe_empty(client: Api) {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[]}"#)
+            .create();
+
+        let err = client
+            .get_random_recipe("de", None, Some(42))
+            .await
+            .expect_err("Empty catalog should fail");
+
+        assert!(matches!(
+            err.downcast_ref::<MccError>(),
+            Some(MccError::EmptyCatalog)
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn content_diff_between_types(client: Api) {
+        let _m_default = mock("GET", "/mcc/api/v1/recipe/all")
+            .match_header("X-Recipe-Type", "default")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
+            .create();
+        let _m_beta = mock("GET", "/mcc/api/v1/recipe/all")
+            .match_header("X-Recipe-Type", "beta")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                get_testdata("recipe_all_catalog_beta.json").expect("Failed to get testdata"),
+            )
+            .create();
+
+        let diffs = client
+            .content_diff_between_types("de", RecipeType::Default, RecipeType::Beta)
+            .await
+            .expect("Failed to diff catalogs");
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].0, 25011);
+        assert_eq!(
+            diffs[0].1.paths(),
+            vec![
+                "data.ingredientsBases[0].ingredients[1].amount",
+                "data.name"
+            ]
+        );
+        assert_eq!(
+            diffs[0].1.changes[1].after,
+            Some(serde_json::json!("Moscow Mule (Beta)"))
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_merged_recipes(client: Api) {
+        let _m_default = mock("GET", "/mcc/api/v1/recipe/all")
+            .match_header("X-Recipe-Type", "default")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
+            .create();
+        let mut overlay: Vec<schemas::Recipe> = serde_json::from_slice(
+            &get_testdata("recipe_all_diet.json").expect("Failed to get testdata"),
+        )
+        .expect("Failed to parse testdata");
+        overlay[0].data.name = "Moscow Mule (Beta)".to_string();
+        let _m_beta = mock("GET", "/mcc/api/v1/recipe/all")
+            .match_header("X-Recipe-Type", "beta")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_vec(&overlay).expect("Failed to serialize"))
+            .create();
+
+        let (merged, from_overlay) = client
+            .get_merged_recipes("de", RecipeType::Default, RecipeType::Beta)
+            .await
+            .expect("Failed to get merged recipes");
+
+        let names: Vec<(u32, &str)> = merged
+            .iter()
+            .map(|r| (r.data.id, r.data.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (25012, "Kartoffelsuppe"),
+                (25011, "Moscow Mule (Beta)"),
+                (25013, "Linsensuppe"),
+            ]
+        );
+        assert_eq!(from_overlay, vec![25011, 25013]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipe_id_titles(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
+            .create();
+
+        let res = client
+            .get_recipe_id_titles("de", None)
+            .await
+            .expect("Failed to get recipe titles");
+
+        assert_eq!(
+            res,
+            vec![
+                (25011, "Moscow Mule".to_string()),
+                (25012, "Kartoffelsuppe".to_string())
+            ]
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn clones_in_spawned_tasks(client: Api) {
+        let m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .expect(4)
+            .create();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.get_recipe_ids("de", None).await })
+            })
+            .collect();
+
+        for handle in handles {
+            let ids = handle
+                .await
+                .expect("Task panicked")
+                .expect("Failed to get recipe ids");
+            assert_eq!(ids, vec![25011, 25012]);
+        }
+        m.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn stream_apk_to(client: Api) {
+        let apk: Vec<u8> = (0..=255u8).cycle().take(100_000).collect();
+        let _m = mock(
+            "GET",
+            "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate0.125.apk",
+        )
+        .with_status(200)
+        .with_header("content-type", "application/vnd.android.package-archive")
+        .with_body(&apk)
+        .create();
+
+        let url = client
+            .create_url("/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate0.125.apk")
+            .expect("Failed to create URL");
+        let update = ApkUpdate::from_url(url.as_str()).expect("Failed to parse update");
+
+        let mut sink = Vec::new();
+        let written = client
+            .stream_apk_to(&update, &mut sink)
+            .await
+            .expect("Failed to stream apk");
+
+        assert_eq!(written, apk.len() as u64);
+        assert_eq!(sink, apk);
+    }
+
+    #[rstest]
+    #[case::capped(10_000, Duration::from_secs(10))]
+    #[case::above_throughput(u64::MAX, Duration::from_secs(0))]
+    #[tokio::test]
+    async fn stream_apk_to_throttled(#[case] limit: u64, #[case] minimum: Duration) {
+        let apk = vec![0u8; 100_000];
+        let _m = mock(
+            "GET",
+            "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate0.125.apk",
+        )
+        .with_status(200)
+        .with_body(&apk)
+        .create();
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let client = ApiBuilder::new()
+            .max_apk_bytes_per_sec(limit)
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to build client");
+
+        let url = client
+            .create_url("/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate0.125.apk")
+            .expect("Failed to create URL");
+        let update = ApkUpdate::from_url(url.as_str()).expect("Failed to parse update");
+        let written = client
+            .stream_apk_to(&update, &mut Vec::new())
+            .await
+            .expect("Failed to stream apk");
+
+        assert_eq!(written, apk.len() as u64);
+        assert!(clock.elapsed() >= minimum);
+        assert!(clock.elapsed() < minimum + Duration::from_millis(1));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn download_apk_to_dir_neutralizes_traversal(client: Api) {
+        let _versions = mock("GET", "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/versions.txt")
+            .with_status(200)
+            .with_body("../../etc/passwd\n")
+            .create();
+        let _m = mock("GET", "/etc/passwd")
+            .with_status(200)
+            .with_body("apk")
+            .create();
+        let root = tempfile::tempdir().expect("Failed to create tempdir");
+        let dir = root.path().join("a").join("b");
+        std::fs::create_dir_all(&dir).expect("Failed to create dir");
+
+        let urls = client
+            .get_apk_updates()
+            .await
+            .expect("Failed to get apk updates");
+        let update = ApkUpdate::from_url(&urls[0]).expect("Failed to parse update");
+        let path = client
+            .download_apk_to_dir(&update, &dir)
+            .await
+            .expect("Failed to download apk");
+        assert_eq!(path, dir.join("passwd"));
+        assert_eq!(std::fs::read(&path).expect("Failed to read apk"), b"apk");
+
+        // Name not derived from the URL, e.g. constructed by hand
+        let update = ApkUpdate {
+            filename: "../../etc/passwd".to_string(),
+            ..update
+        };
+        let path = client
+            .download_apk_to_dir(&update, &dir)
+            .await
+            .expect("Failed to download apk");
+        assert_eq!(path, dir.join("passwd"));
+        assert!(!root.path().join("etc").exists());
+        assert_eq!(
+            std::fs::read_dir(&dir).expect("Failed to list dir").count(),
+            1
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn stream_apk_to_not_found(client: Api) {
+        let _m = mock(
+            "GET",
+            "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate0.999.apk",
+        )
+        .with_status(404)
+        .create();
+
+        let url = client
+            .create_url("/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/MCUpdate0.999.apk")
+            .expect("Failed to create URL");
+        let update = ApkUpdate::from_url(url.as_str()).expect("Failed to parse update");
+
+        let mut sink = Vec::new();
+        let res = client.stream_apk_to(&update, &mut sink).await;
+
+        let err = res.expect_err("Missing APK was streamed");
+        let context = MccError::context(err.as_ref()).expect("Missing request context");
+        assert_eq!(context.url, update.url);
+        assert!(Api::is_not_found(err.as_ref()));
+        assert!(sink.is_empty());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn check_translation_consistency(client: Api) {
+        let _m_de = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "de")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+        let _m_en = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "en")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011_en.json").expect("Failed to get testdata"))
+            .create();
+        let _m_fr = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "fr")
+            .with_status(404)
+            .create();
+
+        let report = client
+            .check_translation_consistency(25011, &["de", "en", "fr"], None)
+            .await
+            .expect("Failed to check translations");
+
+        assert!(!report.is_consistent());
+        assert_eq!(report.mismatches, vec![CountMismatch::Ingredients]);
+        assert_eq!(report.counts["de"].ingredients, 3);
+        assert_eq!(report.counts["en"].ingredients, 2);
+        assert_eq!(report.counts["de"].steps, report.counts["en"].steps);
+        assert_eq!(report.missing, vec!["fr".to_string()]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn no_language_omits_header(client: Api) {
+        let m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header(ACCEPT_LANGUAGE.as_str(), Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+
+        client
+            .get_recipe_ids(Api::NO_LANGUAGE, None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn shared_semaphore_bounds_clones() {
+        let m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .expect(3)
+            .create();
+
+        let semaphore = Arc::new(Semaphore::new(2));
+        let client = Api::builder()
+            .semaphore(semaphore.clone())
+            .build()
+            .expect("Failed to build Api");
+
+        // Occupy both permits, no clone may send a request meanwhile
+        let held = semaphore.acquire_many(2).await.expect("Failed to acquire");
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.get_recipe_ids("de", None).await })
+            })
+            .collect();
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!m.matched());
+        assert!(handles.iter().all(|h| !h.is_finished()));
+
+        drop(held);
+        for handle in handles {
+            handle
+                .await
+                .expect("Task panicked")
+                .expect("Failed to get recipe ids");
+        }
+        assert_eq!(semaphore.available_permits(), 2);
+        m.assert();
+    }
+
+    #[rstest]
+    #[case::found(200, true)]
+    #[case::not_found(404, false)]
+    #[tokio::test]
+    async fn recipe_exists(client: Api, #[case] status: usize, #[case] expected: bool) {
+        let head = mock("HEAD", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "de")
+            .with_status(status)
+            .create();
+        let get = mock("GET", "/mcc/api/v1/recipe/25011").expect(0).create();
+
+        let exists = client
+            .recipe_exists(25011, "de", None)
+            .await
+            .expect("Failed to check recipe");
+
+        assert_eq!(exists, expected);
+        head.assert();
+        get.assert();
+    }
+
+    #[rstest]
+    #[case::found(200, true)]
+    #[case::not_found(404, false)]
+    #[tokio::test]
+    async fn recipe_exists_get_fallback(
+        client: Api,
+        #[case] status: usize,
+        #[case] expected: bool,
+    ) {
+        let _head = mock("HEAD", "/mcc/api/v1/recipe/25011")
+            .with_status(405)
+            .create();
+        let get = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(status)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+
+        let exists = client
+            .recipe_exists(25011, "de", None)
+            .await
+            .expect("Failed to check recipe");
+
+        assert_eq!(exists, expected);
+        get.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn recipe_exists_server_error(client: Api) {
+        let _m = mock("HEAD", "/mcc/api/v1/recipe/25011")
+            .with_status(500)
+            .create();
+
+        assert!(client.recipe_exists(25011, "de", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn default_query_params() {
+        let m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("include".into(), "nutrition".into()),
+                Matcher::UrlEncoded("filter".into(), "a b&c=d".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+
+        Api::builder()
+            .query_param("include", "nutrition")
+            .query_param("filter", "a b&c=d")
+            .build()
+            .expect("Failed to build Api")
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn per_call_query_param_override() {
+        let overridden = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("include".into(), "steps".into()),
+                Matcher::UrlEncoded("page".into(), "2".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+
+        let client = Api::builder()
+            .query_param("include", "nutrition")
+            .build()
+            .expect("Failed to build Api");
+
+        client
+            .with_query_param("include", "steps")
+            .with_query_param("page", "2")
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        overridden.assert();
+        assert_eq!(
+            client.query_params,
+            vec![("include".to_string(), "nutrition".to_string())]
+        );
+    }
+
+    #[rstest]
+    #[case::bare("recipe_all_catalog.json")]
+    #[case::wrapped("recipe_all_catalog_wrapped.json")]
+    #[tokio::test]
+    async fn get_recipes_response_shapes(client: Api, #[case] filename: &str) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata(filename).expect("Failed to get testdata"))
+            .create();
+
+        let res = client
+            .get_recipes("de", None)
+            .await
+            .expect("Failed to get recipes");
+
+        assert_eq!(res.len(), 2);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipe_fields(client: Api) {
+        let json = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let mut body: serde_json::Value =
+            serde_json::from_slice(&json).expect("Failed to deserialize");
+        // Fields not requested are skipped, even if they don't match the schema
+        body["data"]["nutrients"] = serde_json::json!("not a list");
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_body(serde_json::to_vec(&body).expect("Failed to serialize"))
+            .create();
+
+        let partial = client
+            .get_recipe_fields(
+                25011,
+                &[RecipeField::Name, RecipeField::ImageName],
+                "de",
+                None,
+            )
+            .await
+            .expect("Failed to get recipe fields");
+
+        assert_eq!(
+            partial,
+            PartialRecipe {
+                name: Some("Moscow Mule".into()),
+                image_name: Some("Platzhalter_Madame.jpg".into()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipe_envelope(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                get_testdata("recipe_single_25011_meta.json").expect("Failed to get testdata"),
+            )
+            .create();
+
+        let envelope = client
+            .get_recipe_envelope(25011, "de", None)
+            .await
+            .expect("Failed to get recipe");
+
+        assert_eq!(envelope.data.id, 25011);
+        assert_eq!(
+            envelope.meta.and_then(|m| m.version),
+            Some("1.4.2".to_string())
+        );
+    }
+
+    #[rstest]
+    #[case::bare("recipe_all_catalog.json")]
+    #[case::wrapped("recipe_all_catalog_wrapped.json")]
+    #[tokio::test]
+    async fn stream_recipes(client: Api, #[case] filename: &str) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata(filename).expect("Failed to get testdata"))
+            .create();
+
+        let ids: Vec<u32> = client
+            .stream_recipes("de", None)
+            .map(|r| r.expect("Failed to get recipe").data.id)
+            .collect()
+            .await;
+
+        assert_eq!(ids, vec![25012, 25011]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn stream_recipes_where(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
+            .create();
+
+        let ids: Vec<u32> = client
+            .stream_recipes_where("de", None, |r| r.data.id == 25011)
+            .map(|r| r.expect("Failed to get recipe").data.id)
+            .collect()
+            .await;
+
+        assert_eq!(ids, vec![25011]);
+    }
+
+    #[rstest]
+    #[case::per_recipe(3000, true)]
+    #[case::recipe_too_large(1000, false)]
+    #[tokio::test]
+    async fn stream_recipes_body_limit(#[case] limit: u64, #[case] ok: bool) {
+        let body = get_testdata("recipe_all_catalog.json").expect("Failed to get testdata");
+        assert!(body.len() as u64 > limit);
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_body(body)
+            .create();
+        let client = Api::builder()
+            .max_body_bytes(limit)
+            .build()
+            .expect("Failed to build Api");
+
+        let results: Vec<_> = client.stream_recipes("de", None).collect().await;
+
+        if ok {
+            assert_eq!(results.len(), 2);
+            assert!(results.iter().all(|r| r.is_ok()));
+        } else {
+            assert_eq!(results.len(), 1);
+            let err = results[0].as_ref().expect_err("Recipe should be too large");
+            assert!(matches!(
+                MccError::without_context(err.as_ref()).downcast_ref::<MccError>(),
+                Some(MccError::BodyTooLarge { limit: 1000 })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_recipes_unterminated_recipe() {
+        let body = format!(r#"[{{"data":{{"name":"{}"#, "x".repeat(100_000));
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_body(body)
+            .create();
+        let client = Api::builder()
+            .max_body_bytes(1000)
+            .build()
+            .expect("Failed to build Api");
+
+        let results: Vec<_> = client.stream_recipes("de", None).collect().await;
+
+        assert_eq!(results.len(), 1);
+        let err = results[0].as_ref().expect_err("Recipe should be too large");
+        assert!(matches!(
+            MccError::without_context(err.as_ref()).downcast_ref::<MccError>(),
+            Some(MccError::BodyTooLarge { limit: 1000 })
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn stream_recipes_truncated(client: Api) {
+        let body = get_testdata("recipe_all_catalog.json").expect("Failed to get testdata");
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(&body[..body.len() - 10])
+            .create();
+
+        let results: Vec<_> = client.stream_recipes("de", None).collect().await;
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn fetch_missing(client: Api) {
+        let _ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+        let present = mock("GET", "/mcc/api/v1/recipe/25011").expect(0).create();
+        let missing = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+
+        let have: HashSet<u32> = [25011, 4711].iter().copied().collect();
+        let res = client
+            .fetch_missing(&have, "de", None)
+            .await
+            .expect("Failed to fetch missing recipes");
+
+        let ids: Vec<u32> = res.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![25012]);
+        present.assert();
+        missing.assert();
+    }
+
+    #[tokio::test]
+    async fn get_recipes_by_ids_until() {
+        /// Answers recipe 25011 only after a second, 4711 with 404
+        #[derive(Debug)]
+        struct Slow;
+
+        impl crate::transport::HttpTransport for Slow {
+            fn send(&self, request: reqwest::Request) -> crate::transport::Sending {
+                let path = request.url().path().to_string();
+                Box::pin(async move {
+                    let (status, body) = match path.rsplit('/').next() {
+                        Some("25011") => {
+                            tokio::time::sleep(Duration::from_secs(1)).await;
+                            (200, get_testdata("recipe_single_25011.json")?)
+                        }
+                        Some("25012") => (200, get_testdata("recipe_single_25012.json")?),
+                        _ => (404, vec![]),
+                    };
+                    let response = http::Response::builder()
+                        .status(status)
+                        .header("content-type", "application/json")
+                        .body(body)?;
+                    Ok(response.into())
+                })
+            }
+        }
+
+        let client = Api::builder()
+            .transport(Arc::new(Slow))
+            .build()
+            .expect("Failed to build Api");
+
+        let started = Instant::now();
+        let report = client
+            .get_recipes_by_ids_until(
+                &[25011, 4711, 25012],
+                "de",
+                None,
+                started + Duration::from_millis(300),
+            )
+            .await;
+
+        assert!(started.elapsed() < Duration::from_millis(900));
+        let ids: Vec<u32> = report.recipes.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![25012]);
+        assert_eq!(report.failed.keys().collect::<Vec<_>>(), vec![&4711]);
+        assert_eq!(report.unfinished, vec![25011]);
+        assert!(!report.is_complete());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipes_by_ids_until_duplicates(client: Api) {
+        let m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+
+        let report = client
+            .get_recipes_by_ids_until(
+                &[25011, 25011],
+                "de",
+                None,
+                Instant::now() + Duration::from_secs(10),
+            )
+            .await;
+
+        let ids: Vec<u32> = report.recipes.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![25011]);
+        assert!(report.unfinished.is_empty());
+        assert!(report.is_complete());
+        m.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_full_catalog_until(client: Api) {
+        let _ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25011,25012]}"#)
+            .create();
+        let _first = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+        let _second = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .create();
+
+        let report = client
+            .get_full_catalog_until(
+                "de",
+                None,
+                &RetryBudget::new(0),
+                Instant::now() + Duration::from_secs(10),
+            )
+            .await
+            .expect("Failed to get catalog");
+
+        let ids: Vec<u32> = report.recipes.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![25011, 25012]);
+        assert!(report.is_complete());
+    }
+
+    #[tokio::test]
+    async fn get_full_catalog_until_deadline() {
+        /// Lists 25011 and 25012, answers 25011 only after a second
+        #[derive(Debug)]
+        struct Slow;
+
+        impl crate::transport::HttpTransport for Slow {
+            fn send(&self, request: reqwest::Request) -> crate::transport::Sending {
+                let path = request.url().path().to_string();
+                Box::pin(async move {
+                    let body = match path.rsplit('/').next() {
+                        Some("ids") => br#"{"ids":[25011,25012]}"#.to_vec(),
+                        Some("25011") => {
+                            tokio::time::sleep(Duration::from_secs(1)).await;
+                            get_testdata("recipe_single_25011.json")?
+                        }
+                        _ => get_testdata("recipe_single_25012.json")?,
+                    };
+                    let response = http::Response::builder()
+                        .status(200)
+                        .header("content-type", "application/json")
+                        .body(body)?;
+                    Ok(response.into())
+                })
+            }
+        }
+
+        let client = Api::builder()
+            .transport(Arc::new(Slow))
+            .build()
+            .expect("Failed to build Api");
+
+        let started = Instant::now();
+        let report = client
+            .get_full_catalog_until(
+                "de",
+                None,
+                &RetryBudget::new(0),
+                started + Duration::from_millis(300),
+            )
+            .await
+            .expect("Failed to get catalog");
+
+        assert!(started.elapsed() < Duration::from_millis(900));
+        let ids: Vec<u32> = report.recipes.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![25012]);
+        assert!(report.failed.is_empty());
+        assert_eq!(report.unfinished, vec![25011]);
+        assert!(!report.is_complete());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipes_unordered(client: Api) {
+        let _slow = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_fn(|w| {
+                std::thread::sleep(Duration::from_millis(100));
+                w.write_all(
+                    &get_testdata("recipe_single_25011.json").expect("Failed to get testdata"),
+                )
+            })
+            .create();
+        let _fast = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .create();
+        let _missing = mock("GET", "/mcc/api/v1/recipe/4711")
+            .with_status(404)
+            .create();
+
+        let results: Vec<Result<schemas::Recipe>> = client
+            .get_recipes_unordered(&[25011, 4711, 25012], "de", None)
+            .collect()
+            .await;
+
+        assert_eq!(results.len(), 3);
+        let mut ids: Vec<u32> = results
+            .iter()
+            .filter_map(|r| r.as_ref().ok())
+            .map(|r| r.data.id)
+            .collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![25011, 25012]);
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn whats_new(client: Api) {
+        let _ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25011,25012,25013]}"#)
+            .create();
+        let known = mock("GET", "/mcc/api/v1/recipe/25011").expect(0).create();
+        let added = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+        let mut withdrawn: schemas::Recipe = serde_json::from_slice(
+            &get_testdata("recipe_single_25013_diet.json").expect("Failed to get testdata"),
+        )
+        .expect("Failed to parse testdata");
+        withdrawn.data.remove = true;
+        let _withdrawn = mock("GET", "/mcc/api/v1/recipe/25013")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_vec(&withdrawn).expect("Failed to serialize"))
+            .create();
+
+        let known_ids: HashSet<u32> = [25011].iter().copied().collect();
+        let res = client
+            .whats_new(&known_ids, "de", None)
+            .await
+            .expect("Failed to get new recipes");
+
+        let ids: Vec<u32> = res.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![25012]);
+        known.assert();
+        added.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipes_by_id_lang(client: Api) {
+        let _de = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "de")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+        let _en = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "en")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011_en.json").expect("Failed to get testdata"))
+            .create();
+        let _missing = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "fr")
+            .with_status(404)
+            .create();
+
+        let res = client
+            .get_recipes_by_id_lang(&[(25011, "en"), (25011, "de")], None)
+            .await
+            .expect("Failed to get recipes");
+        let languages: Vec<&str> = res.iter().map(|r| r.data.language.as_str()).collect();
+        assert_eq!(languages, vec!["en", "de"]);
+
+        let settled = client
+            .get_recipes_by_id_lang_settled(&[(25011, "fr"), (25011, "de")], None)
+            .await;
+        assert!(settled[0].is_err());
+        assert!(settled[1].is_ok());
+
+        let err = client
+            .get_recipes_by_id_lang(&[(25011, "de"), (25011, "fr")], None)
+            .await
+            .expect_err("Missing translation not reported");
+        let context = MccError::context(err.as_ref()).expect("Missing request context");
+        assert_eq!(context.url.path(), "/mcc/api/v1/recipe/25011");
+        assert_eq!(context.language, "fr");
+        assert!(Api::is_not_found(err.as_ref()));
+    }
+
+    #[tokio::test]
+    async fn base_urls_failover() {
+        /// Remembers the port of every request before passing it on
+        #[derive(Debug, Default)]
+        struct Ports {
+            inner: crate::transport::ReqwestTransport,
+            seen: std::sync::Mutex<Vec<Option<u16>>>,
+        }
+
+        impl HttpTransport for Ports {
+            fn send(&self, request: reqwest::Request) -> crate::transport::Sending {
+                self.seen.lock().unwrap().push(request.url().port());
+                self.inner.send(request)
+            }
+        }
+
+        // Nothing listens on the port once the listener is dropped
+        let down = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("Failed to bind")
+            .port();
+        let up = mockito::server_address().port();
+        let m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25011]}"#)
+            .expect(2)
+            .create();
+        let transport = Arc::new(Ports::default());
+        let client = Api::builder()
+            .base_urls(vec![
+                format!("http://127.0.0.1:{}", down),
+                mockito::server_url(),
+            ])
+            .transport(transport.clone())
+            .build()
+            .expect("Failed to build Api");
+
+        for _ in 0..2 {
+            let ids = client
+                .get_recipe_ids("de", None)
+                .await
+                .expect("Failover to second host failed");
+            assert_eq!(ids, vec![25011]);
+        }
+
+        assert_eq!(
+            *transport.seen.lock().unwrap(),
+            vec![Some(down), Some(up), Some(up)]
+        );
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn failover_context() {
+        let down = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("Failed to bind")
+            .port();
+        let _m = mock("GET", "/mcc/api/v1/recipe/4711")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data":1}"#)
+            .create();
+        let client = Api::builder()
+            .base_urls(vec![
+                format!("http://127.0.0.1:{}", down),
+                mockito::server_url(),
+            ])
+            .build()
+            .expect("Failed to build Api");
+
+        let err = client
+            .get_recipe(4711, "de", Some(RecipeType::Live))
+            .await
+            .expect_err("Invalid recipe was accepted");
+
+        let context = MccError::context(err.as_ref()).expect("Missing request context");
+        assert_eq!(context.url.port(), mockito::server_address().port().into());
+        assert_eq!(context.language, "de");
+        assert_eq!(context.recipe_type, "live");
+        assert!(MccError::without_context(err.as_ref())
+            .downcast_ref::<serde_json::Error>()
+            .is_some());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn context_beyond_recipe_endpoint(client: Api) {
+        let _exists = mock("HEAD", "/mcc/api/v1/recipe/4712")
+            .with_status(500)
+            .create();
+        let _all = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"[{"data":1}]"#)
+            .create();
+        let _config = mock("GET", "/mcc/api/v1/machineconfig/4C5BAB5600000012-4711")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("{}")
+            .create();
+
+        let exists = client.recipe_exists(4712, "de", None).await;
+        let streamed = Box::pin(client.stream_recipes("de", None))
+            .next()
+            .await
+            .expect("Empty recipe stream");
+        let config = client
+            .get_machine_config("4C5BAB5600000012-4711".to_string())
+            .await;
+
+        let path = |err: Box<dyn std::error::Error + Send + Sync>| {
+            MccError::context(err.as_ref())
+                .expect("Missing request context")
+                .url
+                .path()
+                .to_string()
+        };
+        assert_eq!(path(exists.unwrap_err()), "/mcc/api/v1/recipe/4712");
+        assert_eq!(path(streamed.unwrap_err()), "/mcc/api/v1/recipe/all");
+        assert_eq!(
+            path(config.unwrap_err()),
+            "/mcc/api/v1/machineconfig/4C5BAB5600000012-4711"
+        );
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::no_host("mailto:mcc@example.com")]
+    fn invalid_base_url(#[case] url: &str) {
+        assert!(Api::builder().base_urls(vec![url]).build().is_err());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn gzip_declared_plain_body(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("content-encoding", "gzip")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+
+        let recipe = client
+            .get_recipe(25011, "de", None)
+            .await
+            .expect("Mislabeled plain body was rejected");
+
+        assert_eq!(recipe.data.id, 25011);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn gzip_compressed_body(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-encoding", "gzip")
+            .with_body([0x1f, 0x8b, 0x08, 0x00, 0x00])
+            .create();
+
+        let err = client
+            .get_recipe(25011, "de", None)
+            .await
+            .expect_err("Compressed body was accepted");
+
+        assert!(err.to_string().contains("gzip compressed"), "{}", err);
+    }
+
+    #[test]
+    fn effective_config() {
+        let client = Api::builder()
+            .base_urls(vec!["https://mirror.invalid", "https://backup.invalid/"])
+            .bearer_token("secret")
+            .query_param("include", "nutrition")
+            .max_body_bytes(1024)
+            .conditional_requests()
+            .circuit_breaker(3, Duration::from_secs(30))
+            .build()
+            .expect("Failed to build Api");
+
+        assert_eq!(
+            client.effective_config(),
+            ApiConfig {
+                base_url: "https://mirror.invalid/".to_string(),
+                base_urls: vec![
+                    "https://mirror.invalid/".to_string(),
+                    "https://backup.invalid/".to_string()
+                ],
+                bearer_token: true,
+                query_params: vec![("include".to_string(), "nutrition".to_string())],
+                max_body_bytes: 1024,
+                max_json_depth: ApiBuilder::DEFAULT_MAX_JSON_DEPTH,
+                max_apk_bytes_per_sec: None,
+                exact_language_tags: false,
+                conditional_requests: true,
+                circuit_breaker: Some((3, Duration::from_secs(30))),
+                slow_request_threshold: None,
+                shared_request_limit: false,
+            }
+        );
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[test]
+    fn opentelemetry_spans() {
+        let _ok = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25011]}"#)
+            .create();
+        let _missing = mock("GET", "/mcc/api/v1/recipe/4711")
+            .with_status(404)
+            .create();
+
+        let spans = crate::tests::capture_spans(|| {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build runtime");
+            runtime.block_on(async {
+                let client = Api::new();
+                client
+                    .get_recipe_ids("de", None)
+                    .await
+                    .expect("Failed to get ids");
+                client
+                    .get_recipe(4711, "de", None)
+                    .await
+                    .expect_err("404 should fail");
+            });
+        });
+
+        let spans: Vec<_> = spans
+            .into_iter()
+            .filter(|s| s["name"] == "HTTP request")
+            .collect();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0]["http.method"], "GET");
+        assert_eq!(
+            spans[0]["http.url"],
+            format!("{}/mcc/api/v1/recipe/ids", mockito::server_url())
+        );
+        assert_eq!(spans[0]["http.status_code"], "200");
+        assert_eq!(spans[0]["otel.kind"], "client");
+        assert!(!spans[0].contains_key("otel.status_code"));
+        assert_eq!(spans[1]["http.status_code"], "404");
+        assert_eq!(spans[1]["otel.status_code"], "ERROR");
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn error_context(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header("x-recipe-type", "beta")
+            .with_status(500)
+            .create();
+
+        let err = client
+            .get_recipe(25011, "de-AT", Some(RecipeType::Beta))
+            .await
+            .expect_err("500 should fail");
+
+        let expected = format!(
+            "failed GET {}/mcc/api/v1/recipe/25011 [lang=de, type=beta]: HTTP status server error (500",
+            mockito::server_url()
+        );
+        assert!(err.to_string().starts_with(&expected), "{}", err);
+        let context = MccError::context(err.as_ref()).expect("No request context");
+        assert_eq!(context.url.path(), "/mcc/api/v1/recipe/25011");
+        assert_eq!(context.language, "de");
+        assert_eq!(
+            MccError::without_context(err.as_ref())
+                .downcast_ref::<reqwest::Error>()
+                .and_then(|e| e.status()),
+            Some(reqwest::StatusCode::INTERNAL_SERVER_ERROR)
+        );
+    }
+
+    #[rstest]
+    #[case::content_length(false)]
+    #[case::chunked(true)]
+    #[tokio::test]
+    async fn max_body_bytes(#[case] chunked: bool) {
+        let body = get_testdata("recipe_all_catalog.json").expect("Failed to get testdata");
+        let mock = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json");
+        let _m = if chunked {
+            mock.with_body_from_fn(move |w| w.write_all(&body))
+        } else {
+            mock.with_body(&body)
+        }
+        .create();
+
+        let res = Api::builder()
+            .max_body_bytes(1024)
+            .build()
+            .expect("Failed to build Api")
+            .get_recipes("de", None)
+            .await;
+
+        let err = res.expect_err("Oversized body was accepted");
+        assert!(matches!(
+            MccError::without_context(err.as_ref()).downcast_ref::<MccError>(),
+            Some(MccError::BodyTooLarge { limit: 1024 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn if_modified_since() {
+        let last_modified = "Sat, 06 Nov 2021 12:00:00 GMT";
+        let m_full = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header("if-modified-since", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("last-modified", last_modified)
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+        let m_not_modified = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header("if-modified-since", last_modified)
+            .with_status(304)
+            .expect(2)
+            .create();
+
+        let client = Api::builder()
+            .conditional_requests()
+            .build()
+            .expect("Failed to build Api");
+
+        let first = client
+            .get_recipe(25011, "de", None)
+            .await
+            .expect("Failed to get recipe");
+        for _ in 0..2 {
+            let cached = client
+                .get_recipe(25011, "de", None)
+                .await
+                .expect("Failed to get cached recipe");
+            assert_eq!(cached, first);
+        }
+
+        m_full.assert();
+        m_not_modified.assert();
+    }
+
+    #[tokio::test]
+    async fn unexpected_not_modified() {
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header("if-modified-since", Matcher::Missing)
+            .with_status(304)
+            .create();
+
+        let client = Api::builder()
+            .conditional_requests()
+            .build()
+            .expect("Failed to build Api");
+
+        let err = client
+            .get_recipe(25011, "de", None)
+            .await
+            .expect_err("304 without cached recipe should fail");
+        assert!(err.to_string().contains("304 Not Modified"));
+    }
+
+    #[tokio::test]
+    async fn prefetch() {
+        let last_modified = "Wed, 21 Oct 2020 07:28:00 GMT";
+        let m_full = mock("GET", "/mcc/api/v1/recipe/25012")
+            .match_header("if-modified-since", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("last-modified", last_modified)
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+        let m_not_modified = mock("GET", "/mcc/api/v1/recipe/25012")
+            .match_header("if-modified-since", last_modified)
+            .with_status(304)
+            .expect(0)
+            .create();
+        let m_undated = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .expect(2)
+            .create();
+        let _missing = mock("GET", "/mcc/api/v1/recipe/4711")
+            .with_status(404)
+            .create();
+
+        let client = Api::builder()
+            .conditional_requests()
+            .build()
+            .expect("Failed to build Api");
+        let prefetched = client
+            .prefetch(vec![25012, 25011, 4711], "de", None)
+            .expect("Failed to start prefetch")
+            .await
+            .expect("Prefetch task panicked");
+        assert!(prefetched.unwrap_err().to_string().contains("[4711]"));
+
+        for id in [25012, 25011] {
+            let recipe = client
+                .get_recipe(id, "de", None)
+                .await
+                .expect("Failed to get recipe");
+            assert_eq!(recipe.data.id, id);
+        }
+        m_full.assert();
+        m_not_modified.assert();
+
+        // Only the first call is answered from the cache
+        client
+            .get_recipe(25011, "de", None)
+            .await
+            .expect("Failed to get recipe");
+        m_undated.assert();
+    }
+
+    #[test]
+    fn prefetch_requires_conditional_requests() {
+        assert!(Api::new().prefetch(vec![25011], "de", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn if_modified_since_without_last_modified() {
+        let m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header("if-modified-since", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .expect(2)
+            .create();
+
+        let client = Api::builder()
+            .conditional_requests()
+            .build()
+            .expect("Failed to build Api");
+        for _ in 0..2 {
+            client
+                .get_recipe(25011, "de", None)
+                .await
+                .expect("Failed to get recipe");
+        }
+
+        m.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipe_map(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
+            .create();
+
+        let map = client
+            .get_recipe_map("de", None)
+            .await
+            .expect("Failed to get recipe map");
+
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![25011, 25012]);
+        for (id, recipe) in &map {
+            assert_eq!(*id, recipe.data.id);
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipe_map_duplicate_keeps_last(client: Api) {
+        let recipe = |name| {
+            let json = get_testdata(name).expect("Failed to get testdata");
+            serde_json::from_slice::<serde_json::Value>(&json).expect("Failed to deserialize")
+        };
+        let body = serde_json::to_vec(&vec![
+            recipe("recipe_single_25011.json"),
+            recipe("recipe_single_25012.json"),
+            recipe("recipe_single_25011_en.json"),
+        ])
+        .expect("Failed to serialize");
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+
+        let map = client
+            .get_recipe_map("de", None)
+            .await
+            .expect("Failed to get recipe map");
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[&25011].data.language, "en");
+    }
+
+    #[rstest]
+    #[case::upper("DE", "de")]
+    #[case::mixed("De", "de")]
+    #[case::region("de-AT", "de")]
+    #[case::underscore("fr_FR", "fr")]
+    #[case::padded(" en-gb ", "en")]
+    #[case::no_language("", "")]
+    fn normalize_language(#[case] language: &str, #[case] expected: &str) {
+        assert_eq!(Api::normalize_language(language), expected);
+    }
+
+    #[rstest]
+    #[case::upper("DE", "de", false)]
+    #[case::region("de-AT", "de", false)]
+    #[case::underscore("fr_FR", "fr", false)]
+    #[case::exact("de-AT", "de-AT", true)]
+    #[tokio::test]
+    async fn language_is_normalized(
+        #[case] language: &str,
+        #[case] sent: &str,
+        #[case] exact: bool,
+    ) {
+        let m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header("accept-language", sent)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+
+        let mut builder = Api::builder();
+        if exact {
+            builder = builder.exact_language_tags();
+        }
+        builder
+            .build()
+            .expect("Failed to build Api")
+            .get_recipe_ids(language, None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        m.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_recipes_where(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
+            .expect(2)
+            .create();
+
+        let soups = client
+            .get_recipes_where("de", None, |r| r.data.name.contains("suppe"))
+            .await
+            .expect("Failed to get recipes");
+        assert_eq!(soups.len(), 1);
+        assert_eq!(soups[0].data.id, 25012);
+
+        let none = client
+            .get_recipes_where("de", None, |_| false)
+            .await
+            .expect("Failed to get recipes");
+        assert!(none.is_empty());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn validate_catalog(client: Api) {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25011,25012,25013]}"#)
+            .create();
+        let _m_ok = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+        let _m_broken = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data":{"id":"not a number"}}"#)
+            .create();
+        let _m_missing = mock("GET", "/mcc/api/v1/recipe/25013")
+            .with_status(404)
+            .create();
+
+        let report = client
+            .validate_catalog("de", None)
+            .await
+            .expect("Failed to validate catalog");
+
+        assert!(!report.is_valid());
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.parse_errors.keys().collect::<Vec<_>>(), vec![&25012]);
+        assert!(report.parse_errors[&25012].contains("invalid type"));
+        assert_eq!(report.fetch_errors.keys().collect::<Vec<_>>(), vec![&25013]);
+    }
+
+    #[tokio::test]
+    async fn max_json_depth() {
+        let nested = format!(r#"{{"data":{}{}}}"#, "[".repeat(20), "]".repeat(20));
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(nested)
+            .create();
+
+        let res = Api::builder()
+            .max_json_depth(16)
+            .build()
+            .expect("Failed to build Api")
+            .get_recipe(25011, "de", None)
+            .await;
+
+        let err = res.expect_err("Nested body was accepted");
+        assert!(MccError::context(err.as_ref()).is_some());
+        assert!(matches!(
+            MccError::without_context(err.as_ref()).downcast_ref::<MccError>(),
+            Some(MccError::JsonTooDeep { limit: 16 })
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn apk_update_stream(client: Api) {
+        let polls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = polls.clone();
+        let _m = mock("GET", "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/versions.txt")
+            .with_status(200)
+            .with_body_from_fn(move |w| {
+                let body = match counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                    0 => "MCUpdate0.123.apk\nMCUpdate0.124.apk\n",
+                    1 => "MCUpdate0.123.apk\nMCUpdate0.124.apk\n",
+                    2 => return Err(std::io::Error::other("flaky")),
+                    _ => "MCUpdate0.124.apk\nMCUpdate0.126.apk\nMCUpdate0.125.apk\n",
+                };
+                w.write_all(body.as_bytes())
+            })
+            .create();
+
+        let items: Vec<Result<ApkUpdate>> = client
+            .apk_update_stream(Duration::from_millis(10))
+            .take(4)
+            .collect()
+            .await;
+
+        let versions: Vec<Option<String>> = items
+            .iter()
+            .map(|item| {
+                item.as_ref()
+                    .ok()
+                    .map(|u| u.version().expect("No version").to_string())
+            })
+            .collect();
+        assert_eq!(
+            versions,
+            vec![
+                Some("0.124".to_string()),
+                None,
+                Some("0.125".to_string()),
+                Some("0.126".to_string())
+            ]
+        );
+        assert_eq!(polls.load(std::sync::atomic::Ordering::SeqCst), 4);
+    }
+
+    #[rstest]
+    #[case::turbo(schemas::StepMode::Turbo, vec![25012])]
+    #[case::scale(schemas::StepMode::Scale, vec![25012, 25011])]
+    #[case::steaming(schemas::StepMode::Steaming, vec![])]
+    #[case::instruction_is_no_program(schemas::StepMode::Instruction, vec![])]
+    #[tokio::test]
+    async fn get_recipes_by_program(
         client: Api,
-        #[case] language: &str,
-        #[case] recipe_type: Option<RecipeType>,
+        #[case] program: schemas::StepMode,
+        #[case] expected: Vec<u32>,
     ) {
-        let body = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
+            .create();
 
-        let recipe_type_str = recipe_type
-            .as_ref()
-            .or(Some(&RecipeType::Default))
-            .unwrap()
-            .to_string();
+        let res = client
+            .get_recipes_by_program(program, "de", None)
+            .await
+            .expect("Failed to get recipes");
 
-        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
-            .match_header(&ACCEPT_LANGUAGE.to_string(), language)
-            .match_header("X-Recipe-Type", recipe_type_str.as_str())
+        assert_eq!(res.iter().map(|r| r.data.id).collect::<Vec<_>>(), expected);
+    }
+
+    fn sync_mocks() -> Vec<mockito::Mock> {
+        vec![
+            mock("GET", "/mcc/api/v1/recipe/ids")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(r#"{"ids":[25011,25012,25013]}"#)
+                .create(),
+            mock("GET", "/mcc/api/v1/recipe/25011")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(
+                    get_testdata("recipe_single_25011.json").expect("Failed to get testdata"),
+                )
+                .create(),
+            mock("GET", "/mcc/api/v1/recipe/25012")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(
+                    get_testdata("recipe_single_25012.json").expect("Failed to get testdata"),
+                )
+                .create(),
+            mock("GET", "/mcc/api/v1/recipe/25013")
+                .with_status(404)
+                .create(),
+        ]
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn sync_catalog_events(client: Api) {
+        let _m = sync_mocks();
+        let have: HashSet<u32> = vec![25011, 1].into_iter().collect();
+
+        let events: Vec<SyncEvent> = client
+            .sync_catalog_events(&have, "de", None)
+            .map(|event| event.expect("Failed to sync"))
+            .collect()
+            .await;
+
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[0], SyncEvent::Started { total: 3 });
+        assert_eq!(events[1], SyncEvent::RecipeSkipped { id: 25011 });
+        assert_eq!(events[2], SyncEvent::RecipeSynced { id: 25012 });
+        assert!(matches!(
+            events[3],
+            SyncEvent::RecipeFailed { id: 25013, .. }
+        ));
+        match &events[4] {
+            SyncEvent::Finished { report } => {
+                assert_eq!(report.synced.len(), 1);
+                assert_eq!(report.synced[0].data.id, 25012);
+                assert_eq!(report.skipped, vec![25011]);
+                assert_eq!(report.failed.keys().collect::<Vec<_>>(), vec![&25013]);
+                assert_eq!(report.removed, vec![1]);
+            }
+            other => panic!("Expected Finished, got {:?}", other),
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn sync_catalog(client: Api) {
+        let _m = sync_mocks();
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+
+        let report = client
+            .sync_catalog(dir.path(), "de", None)
+            .await
+            .expect("Failed to sync");
+
+        assert_eq!(report.synced.len(), 2);
+        assert!(report.skipped.is_empty());
+        assert_eq!(report.failed.keys().collect::<Vec<_>>(), vec![&25013]);
+        let stored: schemas::Recipe = serde_json::from_slice(
+            &std::fs::read(dir.path().join("25012.json")).expect("Recipe not written"),
+        )
+        .expect("Failed to parse stored recipe");
+        assert_eq!(stored, report.synced[1]);
+        let manifest =
+            Manifest::load(&dir.path().join(Manifest::FILENAME)).expect("Failed to load manifest");
+        assert_eq!(manifest.ids().collect::<Vec<_>>(), vec![25011, 25012]);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn sync_catalog_keeps_files_on_failure(client: Api) {
+        let _ids = mock("GET", "/mcc/api/v1/recipe/ids")
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(body)
+            .with_body(r#"{"ids":[25011]}"#)
+            .create();
+        // Connection dropped mid-body, the recipe can't be parsed
+        let _truncated = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data":{"id":25011,"#)
             .create();
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let mut manifest = Manifest::new();
+        manifest.insert(25011, 0);
+        manifest
+            .save(&dir.path().join(Manifest::FILENAME))
+            .expect("Failed to save");
+        std::fs::write(dir.path().join("25011.json"), b"old").expect("Failed to write");
 
-        let res = client
-            .get_recipe(25011, language, recipe_type)
+        let report = client
+            .sync_catalog(dir.path(), "de", None)
             .await
-            .expect("Failed to get recipe");
+            .expect("Failed to sync");
 
-        assert_eq!(res.data.id, 25011);
+        assert_eq!(report.failed.keys().collect::<Vec<_>>(), vec![&25011]);
+        assert_eq!(
+            std::fs::read(dir.path().join("25011.json")).expect("Failed to read"),
+            b"old"
+        );
+        let mut files: Vec<String> = std::fs::read_dir(dir.path())
+            .expect("Failed to list dir")
+            .map(|e| {
+                e.expect("Failed to read entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["25011.json", Manifest::FILENAME]);
     }
 
     #[rstest]
-    #[case::de_beta("de", Some(RecipeType::Beta))]
-    #[case::de_none("de", None)]
-    #[case::en_default("en", Some(RecipeType::Default))]
-    #[case::en_none("en", None)]
-    #[case::fr_live("fr", Some(RecipeType::Live))]
-    #[case::fr_none("fr", None)]
-    #[case::pl_default("pl", Some(RecipeType::Default))]
-    #[case::pl_none("pl", None)]
-    #[case::es_beta("es", Some(RecipeType::Beta))]
     #[tokio::test]
-    async fn get_recipes_all(
+    async fn sync_catalog_id_list_fails(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(500)
+            .create();
+
+        let events: Vec<Result<SyncEvent>> = client
+            .sync_catalog_events(&HashSet::new(), "de", None)
+            .collect()
+            .await;
+
+        assert_eq!(events.len(), 1);
+        assert!(events[0].is_err());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn sync_catalog_with_manifest(client: Api) {
+        const LAST_MODIFIED: &str = "Wed, 21 Oct 2015 07:28:00 GMT";
+        let ids = |body: &str| {
+            mock("GET", "/mcc/api/v1/recipe/ids")
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(body)
+                .create()
+        };
+        let recipe = |id: u32, body: Vec<u8>| {
+            mock("GET", format!("/mcc/api/v1/recipe/{}", id).as_str())
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(body)
+        };
+        let single_25011 =
+            get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let single_25012 =
+            get_testdata("recipe_single_25012.json").expect("Failed to get testdata");
+        let mut changed: schemas::Recipe =
+            serde_json::from_slice(&single_25012).expect("Failed to parse testdata");
+        changed.data.name = "Renamed".to_string();
+
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let mut stale = Manifest::new();
+        stale.insert(1, 0);
+        stale
+            .save(&dir.path().join(Manifest::FILENAME))
+            .expect("Failed to save");
+        std::fs::write(dir.path().join("1.json"), b"{}").expect("Failed to write");
+
+        // First sync fetches everything, 25011 comes with Last-Modified
+        let mocks = vec![
+            ids(r#"{"ids":[25011,25012]}"#),
+            recipe(25011, single_25011.clone())
+                .with_header("last-modified", LAST_MODIFIED)
+                .expect(1)
+                .create(),
+            recipe(25012, single_25012.clone()).expect(1).create(),
+        ];
+        let first = client
+            .sync_catalog(dir.path(), "de", None)
+            .await
+            .expect("Failed to sync");
+        assert_eq!(first.synced.len(), 2);
+        assert_eq!(first.removed, vec![1]);
+        assert!(!dir.path().join("1.json").exists());
+        for m in mocks {
+            m.assert();
+        }
+
+        // Only 25012 changed, 25011 is revalidated without transferring it
+        let mocks = vec![
+            ids(r#"{"ids":[25011,25012]}"#),
+            mock("GET", "/mcc/api/v1/recipe/25011")
+                .match_header("if-modified-since", LAST_MODIFIED)
+                .with_status(304)
+                .expect(1)
+                .create(),
+            recipe(
+                25012,
+                serde_json::to_vec(&changed).expect("Failed to serialize"),
+            )
+            .match_header("if-modified-since", Matcher::Missing)
+            .expect(1)
+            .create(),
+        ];
+        let second = client
+            .sync_catalog(dir.path(), "de", None)
+            .await
+            .expect("Failed to sync");
+        let synced: Vec<u32> = second.synced.iter().map(|r| r.data.id).collect();
+        assert_eq!(synced, vec![25012]);
+        assert_eq!(second.skipped, vec![25011]);
+        assert!(second.removed.is_empty());
+        let stored: schemas::Recipe = serde_json::from_slice(
+            &std::fs::read(dir.path().join("25012.json")).expect("Recipe not written"),
+        )
+        .expect("Failed to parse stored recipe");
+        assert_eq!(stored.data.name, "Renamed");
+        for m in mocks {
+            m.assert();
+        }
+
+        // A recipe sent again unchanged is skipped by its hash, a removed one deleted
+        let mocks = vec![
+            ids(r#"{"ids":[25011]}"#),
+            recipe(25011, single_25011).expect(1).create(),
+        ];
+        let third = client
+            .sync_catalog(dir.path(), "de", None)
+            .await
+            .expect("Failed to sync");
+        assert!(third.synced.is_empty());
+        assert_eq!(third.skipped, vec![25011]);
+        assert_eq!(third.removed, vec![25012]);
+        assert!(!dir.path().join("25012.json").exists());
+        let manifest =
+            Manifest::load(&dir.path().join(Manifest::FILENAME)).expect("Failed to load manifest");
+        assert_eq!(manifest.ids().collect::<Vec<_>>(), vec![25011]);
+        assert_eq!(manifest.last_modified(25011), None);
+        for m in mocks {
+            m.assert();
+        }
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn detect_drift(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
+            .expect(2)
+            .create();
+        let recipe = |name| {
+            let json = get_testdata(name).expect("Failed to get testdata");
+            serde_json::from_slice::<schemas::Recipe>(&json).expect("Failed to deserialize")
+        };
+
+        let in_sync = vec![
+            recipe("recipe_single_25011.json"),
+            recipe("recipe_single_25012.json"),
+        ];
+        let report = client
+            .detect_drift(&in_sync, "de", None)
+            .await
+            .expect("Failed to detect drift");
+        assert!(report.is_empty(), "{:?}", report);
+
+        let mut edited = recipe("recipe_single_25011.json");
+        edited.data.name.push_str(" (alt)");
+        let mut vanished = recipe("recipe_single_25012.json");
+        vanished.data.id = 1;
+        let report = client
+            .detect_drift(&[edited, vanished], "de", None)
+            .await
+            .expect("Failed to detect drift");
+        assert_eq!(
+            report,
+            DriftReport {
+                stale: vec![25011],
+                missing_from_server: vec![1],
+                new: vec![25012],
+            }
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn download_catalog_archive(client: Api) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
+            .create();
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let dest = dir.path().join("mirror");
+
+        client
+            .download_catalog_archive("de", None, &dest)
+            .await
+            .expect("Failed to download catalog");
+
+        let mut files: Vec<String> = std::fs::read_dir(&dest)
+            .expect("Failed to list mirror")
+            .map(|e| {
+                e.expect("Failed to read entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["25011.json", "25012.json"]);
+
+        let json = std::fs::read(dest.join("25011.json")).expect("Failed to read recipe");
+        let recipe: schemas::Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+        assert_eq!(recipe.data.id, 25011);
+    }
+
+    #[rstest]
+    #[case::vegan(crate::diet::DietaryTag::Vegan, vec![25013])]
+    #[case::vegetarian(crate::diet::DietaryTag::Vegetarian, vec![25013])]
+    #[case::lactose_free(crate::diet::DietaryTag::LactoseFree, vec![])]
+    #[tokio::test]
+    async fn get_recipes_by_diet(
         client: Api,
-        #[case] language: &str,
-        #[case] recipe_type: Option<RecipeType>,
+        #[case] diet: crate::diet::DietaryTag,
+        #[case] expected: Vec<u32>,
     ) {
-        let recipe_type_str = recipe_type
-            .as_ref()
-            .or(Some(&RecipeType::Default))
-            .unwrap()
-            .to_string();
+        let _m = mock("GET", "/mcc/api/v1/recipe/all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_all_diet.json").expect("Failed to get testdata"))
+            .create();
 
-        let filename = format!("recipe_all_{}.json", recipe_type_str);
-        let body = get_testdata(&filename).expect("Failed to get testdata");
+        let res = client
+            .get_recipes_by_diet(&diet, "de", None)
+            .await
+            .expect("Failed to get recipes");
+
+        assert_eq!(res.iter().map(|r| r.data.id).collect::<Vec<_>>(), expected);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_full_catalog_retries(client: Api) {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25011,25012]}"#)
+            .create();
+        let _m_25011 = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+        // Mockito answers with the first mock still missing hits, so 25012 fails once
+        let m_25012_fail = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let m_25012 = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+        let budget = RetryBudget::new(5).delay(Duration::from_millis(1));
+
+        let res = client
+            .get_full_catalog("de", None, &budget)
+            .await
+            .expect("Failed to get catalog");
+
+        assert_eq!(
+            res.iter().map(|r| r.data.id).collect::<Vec<_>>(),
+            vec![25011, 25012]
+        );
+        assert_eq!(budget.remaining(), 4);
+        m_25012_fail.assert();
+        m_25012.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn get_full_catalog_does_not_retry_client_errors(client: Api) {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25013]}"#)
+            .create();
+        let m = mock("GET", "/mcc/api/v1/recipe/25013")
+            .with_status(404)
+            .expect(1)
+            .create();
+        let budget = RetryBudget::new(5).delay(Duration::from_millis(1));
+
+        assert!(client.get_full_catalog("de", None, &budget).await.is_err());
+        assert_eq!(budget.remaining(), 5);
+        m.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn retry_budget_caps_attempts(client: Api) {
+        let ids: Vec<u32> = (1..=50).collect();
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::to_vec(&schemas::RecipeIds { ids }).expect("Failed to serialize"),
+            )
+            .create();
+        let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let _m_outage = mock(
+            "GET",
+            Matcher::Regex(r"^/mcc/api/v1/recipe/\d+$".to_string()),
+        )
+        .with_status(503)
+        .with_body_from_fn(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        })
+        .create();
+        let budget = RetryBudget::new(10).delay(Duration::from_millis(1));
+
+        let res = client.get_full_catalog("de", None, &budget).await;
+
+        assert!(res.is_err());
+        assert_eq!(budget.remaining(), 0);
+        // Gives up after the shared retries instead of trying 50 ids 4 times each
+        let attempts = attempts.load(std::sync::atomic::Ordering::SeqCst);
+        assert!(
+            attempts <= 10 + Api::MAX_CONCURRENT_REQUESTS + 1,
+            "{} attempts",
+            attempts
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn retry_budget_exhausted_keeps_last_error(client: Api) {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25013]}"#)
+            .create();
+        let m = mock("GET", "/mcc/api/v1/recipe/25013")
+            .with_status(503)
+            .expect(2)
+            .create();
+        let budget = RetryBudget::new(1).delay(Duration::from_millis(1));
+
+        let err = client
+            .get_full_catalog("de", None, &budget)
+            .await
+            .expect_err("Outage should fail the catalog");
+
+        let last = match err.downcast_ref::<MccError>() {
+            Some(MccError::RetryBudgetExhausted { source }) => source,
+            other => panic!("Expected RetryBudgetExhausted, got {:?}", other),
+        };
+        assert!(MccError::context(last.as_ref()).is_some());
+        assert_eq!(
+            MccError::without_context(last.as_ref())
+                .downcast_ref::<reqwest::Error>()
+                .and_then(|e| e.status()),
+            Some(reqwest::StatusCode::SERVICE_UNAVAILABLE)
+        );
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn retry_delay_uses_injected_clock() {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25013]}"#)
+            .create();
+        let m = mock("GET", "/mcc/api/v1/recipe/25013")
+            .with_status(503)
+            .expect(3)
+            .create();
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let client = Api::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to build Api");
+        let budget = RetryBudget::new(10)
+            .retries_per_item(2)
+            .delay(Duration::from_secs(30));
+
+        assert!(client.get_full_catalog("de", None, &budget).await.is_err());
+
+        // Two retries slept on the mock clock, without actually waiting
+        assert_eq!(clock.elapsed(), Duration::from_secs(60));
+        m.assert();
+    }
 
+    #[tokio::test]
+    async fn retry_follows_backoff_strategy() {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25013]}"#)
+            .create();
+        let m = mock("GET", "/mcc/api/v1/recipe/25013")
+            .with_status(503)
+            .expect(4)
+            .create();
+        let clock = Arc::new(crate::clock::MockClock::new());
+        let client = Api::builder()
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to build Api");
+        let budget = RetryBudget::new(10)
+            .retries_per_item(5)
+            .backoff(Exponential::new(Duration::from_secs(1)).max_retries(3));
+
+        assert!(client.get_full_catalog("de", None, &budget).await.is_err());
+
+        // Strategy gives up after 1 + 2 + 4 seconds, before the per item limit
+        assert_eq!(clock.elapsed(), Duration::from_secs(7));
+        assert_eq!(budget.remaining(), 7);
+        m.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn list_all_ingredients(client: Api) {
         let _m = mock("GET", "/mcc/api/v1/recipe/all")
-            .match_header(&ACCEPT_LANGUAGE.to_string(), language)
-            .match_header("X-Recipe-Type", recipe_type_str.as_str())
             .with_status(200)
             .with_header("content-type", "application/json")
-            .with_body(body)
+            .with_body(get_testdata("recipe_all_catalog.json").expect("Failed to get testdata"))
             .create();
 
         let res = client
-            .get_recipes(language, recipe_type)
+            .list_all_ingredients("de", None)
             .await
-            .expect("Failed to get recipe");
+            .expect("Failed to list ingredients");
 
-        assert!(!res.is_empty());
+        // Kartoffeln, Limette and Vodka appear more than once
+        assert_eq!(
+            res,
+            vec![
+                "Gemüsebrühe",
+                "Ginger beer",
+                "Kartoffeln",
+                "Limette",
+                "Salz",
+                "Vodka",
+                "Zwiebel"
+            ]
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn retry_is_traced() {
+        let _m_ids = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25012]}"#)
+            .create();
+        let _m_fail = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(503)
+            .expect(1)
+            .create();
+        let _m_ok = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .create();
+        let client = Api::builder()
+            .clock(Arc::new(crate::clock::MockClock::new()))
+            .build()
+            .expect("Failed to build Api");
+        let budget = RetryBudget::new(5).delay(Duration::from_millis(250));
+
+        let events = crate::tests::capture_events(|| {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create runtime")
+                .block_on(client.get_full_catalog("de", None, &budget))
+                .expect("Failed to get catalog");
+        });
+
+        let retries: Vec<_> = events
+            .iter()
+            .filter(|e| e["message"] == "Retrying recipe request")
+            .collect();
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0]["endpoint"], "25012");
+        assert_eq!(retries[0]["attempt"], "1");
+        assert_eq!(retries[0]["delay_ms"], "250");
+        assert!(retries[0]["error"].contains("503"));
+    }
+
+    #[tokio::test]
+    async fn default_headers_present() {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header("x-gateway", "mcc")
+            .match_header("user-agent", "mcc-rs")
+            .match_header(ACCEPT_LANGUAGE.as_str(), "de")
+            .match_header("X-Recipe-Type", "default")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-gateway", HeaderValue::from_static("mcc"));
+        headers.insert("user-agent", HeaderValue::from_static("mcc-rs"));
+        let client = Api::builder()
+            .default_headers(headers)
+            .build()
+            .expect("Failed to build Api");
+
+        let ids = client
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        assert_eq!(ids, vec![25011, 25012]);
+    }
+
+    #[tokio::test]
+    async fn default_headers_order() {
+        #[derive(Debug, Default)]
+        struct Recorder(std::sync::Mutex<Vec<(String, String)>>);
+
+        impl crate::transport::HttpTransport for Recorder {
+            fn send(&self, request: reqwest::Request) -> crate::transport::Sending {
+                *self.0.lock().unwrap() = request
+                    .headers()
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_str().unwrap().to_string()))
+                    .collect();
+                let response = http::Response::builder()
+                    .status(200)
+                    .body(r#"{"ids":[]}"#)
+                    .expect("Failed to build response");
+                Box::pin(futures::future::ready(Ok(response.into())))
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-b", HeaderValue::from_static("1"));
+        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("fr"));
+        headers.insert("x-a", HeaderValue::from_static("2"));
+        let client = Api::builder()
+            .transport(recorder.clone())
+            .bearer_token("secret")
+            .default_headers(headers)
+            .build()
+            .expect("Failed to build Api");
+
+        client
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        let recorded = recorder.0.lock().unwrap().clone();
+        let expected: Vec<(String, String)> = vec![
+            ("x-b", "1"),
+            ("accept-language", "de"),
+            ("x-a", "2"),
+            ("authorization", "Bearer secret"),
+            ("x-recipe-type", "default"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        assert_eq!(recorded, expected);
     }
 }
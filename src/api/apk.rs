@@ -0,0 +1,353 @@
+use crate::Result;
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+/// Numeric APK version like `0.123`, compared part by part
+///
+/// `1.10` is newer than `1.9`. A pre-release suffix as in `1.10-beta.2` is
+/// ordered before the release it precedes, following semantic versioning.
+/// Parts are compared by value and trailing zero parts ignored, so `1.2`,
+/// `1.2.0` and `1.02` are equal. The version is displayed as parsed.
+#[derive(Debug, Clone)]
+pub struct ApkVersion {
+    raw: String,
+    parts: Vec<u32>,
+    pre: Vec<String>,
+}
+
+impl ApkVersion {
+    /// Whether the version has a pre-release suffix
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Version as parsed, e.g. `0.050` for `MCUpdate0.050.apk`
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// Release parts without trailing zeros
+    fn release(&self) -> &[u32] {
+        let len = self
+            .parts
+            .iter()
+            .rposition(|p| *p != 0)
+            .map_or(0, |i| i + 1);
+        &self.parts[..len]
+    }
+}
+
+/// Compare pre-release identifiers, numeric ones by value and before alphanumeric ones
+fn compare_identifier(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+impl Ord for ApkVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.release().cmp(other.release()).then_with(|| {
+            match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self
+                    .pre
+                    .iter()
+                    .zip(&other.pre)
+                    .map(|(a, b)| compare_identifier(a, b))
+                    .find(|o| o.is_ne())
+                    .unwrap_or_else(|| self.pre.len().cmp(&other.pre.len())),
+            }
+        })
+    }
+}
+
+impl PartialEq for ApkVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ApkVersion {}
+
+impl Hash for ApkVersion {
+    /// Consistent with [`Ord`], numeric identifiers are hashed by value
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.release().hash(state);
+        for id in &self.pre {
+            match id.parse::<u64>() {
+                Ok(n) => n.hash(state),
+                Err(_) => id.hash(state),
+            }
+        }
+    }
+}
+
+impl PartialOrd for ApkVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for ApkVersion {
+    type Err = Box<dyn std::error::Error + Send + Sync>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || format!("Invalid APK version: {:?}", s);
+        let (release, pre) = match s.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (s, None),
+        };
+
+        let parts = release
+            .split('.')
+            .map(|part| {
+                // u32::from_str alone would accept a leading `+`
+                if part.chars().all(|c| c.is_ascii_digit()) {
+                    part.parse::<u32>().ok()
+                } else {
+                    None
+                }
+            })
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(invalid)?;
+
+        let pre = match pre {
+            Some(pre) => pre
+                .split('.')
+                .map(|id| {
+                    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                        Some(id.to_string())
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Option<Vec<String>>>()
+                .ok_or_else(invalid)?,
+            None => vec![],
+        };
+
+        Ok(Self {
+            raw: s.to_string(),
+            parts,
+            pre,
+        })
+    }
+}
+
+impl fmt::Display for ApkVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// Longest file name written to disk, the limit of common file systems
+const MAX_FILENAME_BYTES: usize = 255;
+
+/// Names with special meaning on Windows, regardless of extension
+const RESERVED_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Turn an untrusted name into a file name safe to create inside a directory
+///
+/// Directory parts and traversal sequences are dropped, reserved and
+/// control characters replaced by `_`, leading dots removed and overlong
+/// names truncated while keeping the extension. Fails if nothing usable
+/// remains.
+pub(crate) fn sanitize_filename(name: &str) -> Result<String> {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let replaced: String = base
+        .chars()
+        .map(|c| {
+            if c.is_control() || "<>:\"|?*".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    // Windows silently drops trailing dots and spaces
+    let mut sanitized = replaced
+        .trim_start_matches(|c: char| c == '.' || c.is_whitespace())
+        .trim_end_matches(|c: char| c == '.' || c.is_whitespace())
+        .to_string();
+    if sanitized.is_empty() {
+        return Err(format!("No usable file name in {:?}", name).into());
+    }
+
+    let stem = sanitized.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES.contains(&stem.to_lowercase().as_str()) {
+        sanitized.insert(0, '_');
+    }
+
+    if sanitized.len() > MAX_FILENAME_BYTES {
+        let extension = match sanitized.rfind('.') {
+            Some(idx) if sanitized.len() - idx <= 16 => sanitized[idx..].to_string(),
+            _ => String::new(),
+        };
+        let mut end = MAX_FILENAME_BYTES - extension.len();
+        while !sanitized.is_char_boundary(end) {
+            end -= 1;
+        }
+        sanitized = format!("{}{}", &sanitized[..end], extension);
+    }
+
+    Ok(sanitized)
+}
+
+/// Single APK file offered for download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApkUpdate {
+    /// File name as listed in `versions.txt`, e.g. `MCUpdate0.123.apk`
+    pub filename: String,
+    /// Full download URL
+    pub url: reqwest::Url,
+}
+
+impl ApkUpdate {
+    /// Create from a download URL as returned by [`super::Api::get_apk_updates`]
+    pub fn from_url(url: &str) -> Result<Self> {
+        let url = reqwest::Url::parse(url)?;
+        let filename = url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| format!("No APK file name in URL: {}", url))?
+            .to_string();
+
+        Ok(Self { filename, url })
+    }
+
+    /// [`ApkUpdate::filename`] made safe to write below a local directory
+    ///
+    /// The name comes from the server's `versions.txt`, so it's untrusted:
+    /// traversal like `../../etc/passwd` ends up as `passwd`.
+    pub fn local_filename(&self) -> Result<String> {
+        sanitize_filename(&self.filename)
+    }
+
+    /// Version encoded in the file name, `None` if it doesn't follow `MCUpdate<version>.apk`
+    pub fn version(&self) -> Option<ApkVersion> {
+        self.filename
+            .strip_prefix(super::Api::APK_PREFIX)?
+            .strip_suffix(super::Api::APK_SUFFIX)?
+            .parse()
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::plain("https://host/666a60bc/MCUpdate0.123.apk", Some("MCUpdate0.123.apk"))]
+    #[case::no_file("https://host/", None)]
+    #[case::not_an_url("MCUpdate0.123.apk", None)]
+    fn from_url(#[case] url: &str, #[case] expected: Option<&str>) {
+        let res = ApkUpdate::from_url(url);
+
+        assert_eq!(res.ok().map(|u| u.filename), expected.map(String::from));
+    }
+
+    #[rstest]
+    #[case::plain("https://host/666a60bc/MCUpdate0.123.apk", Some("0.123"))]
+    #[case::leading_zero("https://host/666a60bc/MCUpdate0.050.apk", Some("0.050"))]
+    #[case::other_name("https://host/666a60bc/firmware.bin", None)]
+    #[case::no_version("https://host/666a60bc/MCUpdate.apk", None)]
+    fn version(#[case] url: &str, #[case] expected: Option<&str>) {
+        let update = ApkUpdate::from_url(url).expect("Failed to parse URL");
+
+        assert_eq!(
+            update.version().map(|v| v.to_string()),
+            expected.map(String::from)
+        );
+    }
+
+    #[rstest]
+    #[case::plain("MCUpdate0.123.apk", Some("MCUpdate0.123.apk"))]
+    #[case::traversal("../../etc/passwd", Some("passwd"))]
+    #[case::traversal_only("../..", None)]
+    #[case::backslash("..\\..\\windows\\evil.apk", Some("evil.apk"))]
+    #[case::hidden("..evil.apk", Some("evil.apk"))]
+    #[case::reserved_chars("MC<1>:2|?.apk", Some("MC_1__2__.apk"))]
+    #[case::control("MC\nUpdate.apk", Some("MC_Update.apk"))]
+    #[case::windows_device("NUL.apk", Some("_NUL.apk"))]
+    #[case::trailing_dots("MCUpdate.apk. .", Some("MCUpdate.apk"))]
+    #[case::empty("", None)]
+    fn sanitize_filename(#[case] name: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            super::sanitize_filename(name).ok(),
+            expected.map(String::from)
+        );
+    }
+
+    #[test]
+    fn sanitize_filename_truncates() {
+        let long = format!("{}.apk", "ä".repeat(300));
+
+        let sanitized = super::sanitize_filename(&long).expect("Failed to sanitize");
+
+        assert!(sanitized.len() <= MAX_FILENAME_BYTES);
+        assert!(sanitized.ends_with("ää.apk"));
+    }
+
+    #[rstest]
+    #[case::minor("0.123", "0.124")]
+    #[case::numeric_not_lexical("1.9", "1.10")]
+    #[case::major("0.999", "1.0")]
+    #[case::longer("1.2", "1.2.1")]
+    #[case::prerelease("1.10-beta", "1.10")]
+    #[case::prerelease_of_next("1.9", "1.10-alpha")]
+    #[case::prerelease_numeric("1.10-beta.2", "1.10-beta.10")]
+    #[case::prerelease_alpha("1.10-alpha.2", "1.10-beta.1")]
+    #[case::prerelease_longer("1.10-beta", "1.10-beta.1")]
+    fn version_order(#[case] older: &str, #[case] newer: &str) {
+        let older: ApkVersion = older.parse().expect("Invalid version");
+        let newer: ApkVersion = newer.parse().expect("Invalid version");
+
+        assert!(older < newer);
+    }
+
+    #[rstest]
+    #[case::trailing_zero("1.2", "1.2.0")]
+    #[case::leading_zero("0.50", "0.050")]
+    #[case::prerelease_leading_zero("1.2-beta.1", "1.2.0-beta.01")]
+    fn version_equal(#[case] a: &str, #[case] b: &str) {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |v: &ApkVersion| {
+            let mut hasher = DefaultHasher::new();
+            v.hash(&mut hasher);
+            hasher.finish()
+        };
+        let a: ApkVersion = a.parse().expect("Invalid version");
+        let b: ApkVersion = b.parse().expect("Invalid version");
+
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(a, b);
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::empty_part("1..2")]
+    #[case::sign("+1.2")]
+    #[case::letters("1.2a")]
+    #[case::empty_prerelease("1.2-")]
+    #[case::empty_prerelease_part("1.2-beta..1")]
+    fn invalid_version(#[case] version: &str) {
+        assert!(version.parse::<ApkVersion>().is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use crate::Result;
+use std::{convert::TryFrom, fmt, time::Duration};
+
+/// Delay before each retry of a failed request
+///
+/// Passed to [`super::RetryBudget::backoff`]. `attempt` counts retries of a
+/// single item, starting at 1 for the first retry.
+pub trait BackoffStrategy: fmt::Debug + Send + Sync {
+    /// Pause before retry number `attempt`, `None` to give up
+    fn next_delay(&self, attempt: u32) -> Option<Duration>;
+}
+
+fn within(attempt: u32, max_retries: Option<u32>) -> bool {
+    attempt > 0 && !matches!(max_retries, Some(max) if attempt > max)
+}
+
+/// Same delay before every retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixed {
+    delay: Duration,
+    max_retries: Option<u32>,
+}
+
+impl Fixed {
+    /// Wait `delay` before every retry, without a retry limit
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            max_retries: None,
+        }
+    }
+
+    /// Give up after `retries` retries
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = Some(retries);
+        self
+    }
+}
+
+impl BackoffStrategy for Fixed {
+    fn next_delay(&self, attempt: u32) -> Option<Duration> {
+        if !within(attempt, self.max_retries) {
+            return None;
+        }
+        Some(self.delay)
+    }
+}
+
+/// Delay multiplied by `factor` with every retry, starting at `initial`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exponential {
+    initial: Duration,
+    factor: f64,
+    max_delay: Option<Duration>,
+    max_retries: Option<u32>,
+}
+
+impl Exponential {
+    /// Growth of the delay per retry used by [`Exponential::new`]
+    pub const DEFAULT_FACTOR: f64 = 2.0;
+
+    /// Wait `initial` before the first retry, doubling it every retry
+    pub fn new(initial: Duration) -> Self {
+        Self {
+            initial,
+            factor: Exponential::DEFAULT_FACTOR,
+            max_delay: None,
+            max_retries: None,
+        }
+    }
+
+    /// Like [`Exponential::new`], multiplying the delay by `factor` every retry
+    ///
+    /// Fails if `factor` is negative, NaN or infinite.
+    pub fn with_factor(initial: Duration, factor: f64) -> Result<Self> {
+        if !(factor.is_finite() && factor >= 0.0) {
+            return Err(format!("Invalid backoff factor {}", factor).into());
+        }
+        Ok(Self {
+            factor,
+            ..Self::new(initial)
+        })
+    }
+
+    /// Upper bound of a single delay
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = Some(delay);
+        self
+    }
+
+    /// Give up after `retries` retries
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = Some(retries);
+        self
+    }
+}
+
+impl BackoffStrategy for Exponential {
+    fn next_delay(&self, attempt: u32) -> Option<Duration> {
+        if !within(attempt, self.max_retries) {
+            return None;
+        }
+
+        let exponent = i32::try_from(attempt - 1).unwrap_or(i32::MAX);
+        let secs = self.initial.as_secs_f64() * self.factor.powi(exponent);
+        let delay = Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX);
+        Some(self.max_delay.map_or(delay, |max| delay.min(max)))
+    }
+}
+
+/// Delays following the Fibonacci sequence in multiples of `unit`: 1, 1, 2, 3, 5, ..
+///
+/// Grows slower than [`Exponential`] with factor 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fibonacci {
+    unit: Duration,
+    max_delay: Option<Duration>,
+    max_retries: Option<u32>,
+}
+
+impl Fibonacci {
+    /// Wait multiples of `unit` before each retry, without a retry limit
+    pub fn new(unit: Duration) -> Self {
+        Self {
+            unit,
+            max_delay: None,
+            max_retries: None,
+        }
+    }
+
+    /// Upper bound of a single delay
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = Some(delay);
+        self
+    }
+
+    /// Give up after `retries` retries
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = Some(retries);
+        self
+    }
+}
+
+impl BackoffStrategy for Fibonacci {
+    fn next_delay(&self, attempt: u32) -> Option<Duration> {
+        if !within(attempt, self.max_retries) {
+            return None;
+        }
+
+        let (mut current, mut next) = (1u32, 1u32);
+        for _ in 1..attempt {
+            let sum = current.saturating_add(next);
+            current = next;
+            next = sum;
+        }
+        let delay = self.unit.checked_mul(current).unwrap_or(Duration::MAX);
+        Some(self.max_delay.map_or(delay, |max| delay.min(max)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delays(strategy: &dyn BackoffStrategy, attempts: u32) -> Vec<Option<u64>> {
+        (1..=attempts)
+            .map(|attempt| {
+                strategy
+                    .next_delay(attempt)
+                    .map(|d| u64::try_from(d.as_millis()).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fixed() {
+        let strategy = Fixed::new(Duration::from_millis(100)).max_retries(3);
+
+        assert_eq!(
+            delays(&strategy, 4),
+            vec![Some(100), Some(100), Some(100), None]
+        );
+        assert_eq!(strategy.next_delay(0), None);
+    }
+
+    #[test]
+    fn exponential() {
+        let strategy = Exponential::new(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(1000))
+            .max_retries(6);
+
+        assert_eq!(
+            delays(&strategy, 7),
+            vec![
+                Some(100),
+                Some(200),
+                Some(400),
+                Some(800),
+                Some(1000),
+                Some(1000),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn exponential_factor() {
+        let strategy =
+            Exponential::with_factor(Duration::from_millis(10), 3.0).expect("Valid factor");
+
+        assert_eq!(delays(&strategy, 3), vec![Some(10), Some(30), Some(90)]);
+        assert_eq!(strategy.next_delay(u32::MAX), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn exponential_invalid_factor() {
+        for factor in [-2.0, f64::NAN, f64::INFINITY] {
+            assert!(Exponential::with_factor(Duration::from_millis(10), factor).is_err());
+        }
+    }
+
+    #[test]
+    fn fibonacci() {
+        let strategy = Fibonacci::new(Duration::from_millis(10)).max_retries(7);
+
+        assert_eq!(
+            delays(&strategy, 8),
+            vec![
+                Some(10),
+                Some(10),
+                Some(20),
+                Some(30),
+                Some(50),
+                Some(80),
+                Some(130),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn fibonacci_capped() {
+        let strategy = Fibonacci::new(Duration::from_secs(1)).max_delay(Duration::from_secs(4));
+
+        assert_eq!(strategy.next_delay(5), Some(Duration::from_secs(4)));
+        assert_eq!(strategy.next_delay(1000), Some(Duration::from_secs(4)));
+    }
+}
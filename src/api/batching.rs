@@ -0,0 +1,279 @@
+use super::Api;
+use crate::{
+    schemas::{Recipe, RecipeType},
+    Result,
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+/// Language and recipe type a batch is issued for
+type BatchKey = (String, String);
+
+type Waiter = (u32, oneshot::Sender<Result<Recipe>>);
+
+/// Failure of a recipe requested by several pending calls, the original is its source
+#[derive(Debug, Clone)]
+struct SharedError(Arc<dyn Error + Send + Sync>);
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for SharedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
+
+/// Coalesces individual `get_recipe` calls into concurrent bulk fetches
+///
+/// Calls arriving within `window` of the first pending call for the same
+/// language / recipe type are buffered and then fetched together via
+/// [`Api::get_recipes_by_ids_settled`]. Languages are grouped as sent to the
+/// server, so "de" and "de-DE" share a batch unless
+/// [`super::ApiBuilder::exact_language_tags`] is set.
+///
+/// Duplicate ids are only requested once. A failure is returned as is, unless
+/// several pending calls asked for the same id. Those get an error with the
+/// original failure as [`Error::source`].
+///
+/// Requires a running tokio runtime, the batch is flushed by a spawned task.
+#[derive(Clone)]
+pub struct BatchingApi {
+    api: Api,
+    window: Duration,
+    pending: Arc<Mutex<HashMap<BatchKey, Vec<Waiter>>>>,
+}
+
+impl BatchingApi {
+    /// Default time to wait for further calls before issuing a batch
+    pub const DEFAULT_WINDOW: Duration = Duration::from_millis(10);
+
+    /// Wrap an Api, using the default batching window
+    pub fn new(api: Api) -> Self {
+        Self::with_window(api, BatchingApi::DEFAULT_WINDOW)
+    }
+
+    /// Wrap an Api, using a custom batching window
+    pub fn with_window(api: Api, window: Duration) -> Self {
+        Self {
+            api,
+            window,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get single recipe by id, fetched together with other pending calls
+    pub async fn get_recipe(
+        &self,
+        id: u32,
+        language: &str,
+        recipe_type: Option<RecipeType>,
+    ) -> Result<Recipe> {
+        let recipe_type = recipe_type.unwrap_or(RecipeType::Default);
+        let key = (self.api.language_tag(language), recipe_type.to_string());
+        let (tx, rx) = oneshot::channel();
+
+        let is_first = {
+            let mut pending = self.pending.lock().expect("Batch lock poisoned");
+            let waiters = pending.entry(key.clone()).or_default();
+            waiters.push((id, tx));
+            waiters.len() == 1
+        };
+
+        if is_first {
+            tokio::spawn(self.clone().flush(key, recipe_type));
+        }
+
+        match rx.await {
+            Ok(res) => res,
+            Err(_) => Err("Batch was dropped before completing".into()),
+        }
+    }
+
+    async fn flush(self, key: BatchKey, recipe_type: RecipeType) {
+        tokio::time::sleep(self.window).await;
+
+        let waiters = self
+            .pending
+            .lock()
+            .expect("Batch lock poisoned")
+            .remove(&key)
+            .unwrap_or_default();
+
+        let mut callers: HashMap<u32, usize> = HashMap::new();
+        for (id, _) in &waiters {
+            *callers.entry(*id).or_default() += 1;
+        }
+        let mut ids: Vec<u32> = callers.keys().copied().collect();
+        ids.sort_unstable();
+
+        let results = self
+            .api
+            .get_recipes_by_ids_settled(&ids, &key.0, Some(recipe_type))
+            .await;
+
+        // Errors can't be cloned, ids with several callers share theirs
+        let mut single: HashMap<u32, Result<Recipe>> = HashMap::new();
+        let mut shared: HashMap<u32, std::result::Result<Recipe, SharedError>> = HashMap::new();
+        for (id, res) in ids.into_iter().zip(results) {
+            if callers[&id] == 1 {
+                single.insert(id, res);
+            } else {
+                shared.insert(id, res.map_err(|e| SharedError(e.into())));
+            }
+        }
+
+        for (id, tx) in waiters {
+            let res = match single.remove(&id) {
+                Some(res) => res,
+                None => shared[&id].clone().map_err(|e| e.into()),
+            };
+            // Receiver might have been dropped in the meantime, nothing to do then
+            let _ = tx.send(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::MccError, tests::get_testdata};
+    use mockito::mock;
+    use rstest::*;
+
+    #[fixture]
+    fn client() -> BatchingApi {
+        BatchingApi::with_window(Api::new(), Duration::from_millis(50))
+    }
+
+    async fn delayed_get(
+        client: &BatchingApi,
+        delay_ms: u64,
+        id: u32,
+        language: &str,
+    ) -> Result<Recipe> {
+        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        client.get_recipe(id, language, None).await
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn coalesces_staggered_calls(client: BatchingApi) {
+        let m1 = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+        let m2 = mock("GET", "/mcc/api/v1/recipe/25012")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25012.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+
+        let (a, b, c) = futures::join!(
+            delayed_get(&client, 0, 25011, "de"),
+            delayed_get(&client, 5, 25012, "de"),
+            delayed_get(&client, 10, 25011, "de"),
+        );
+
+        assert_eq!(a.expect("Failed to get recipe").data.id, 25011);
+        assert_eq!(b.expect("Failed to get recipe").data.id, 25012);
+        assert_eq!(c.expect("Failed to get recipe").data.id, 25011);
+        m1.assert();
+        m2.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn separate_batches_after_window(client: BatchingApi) {
+        let m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .expect(2)
+            .create();
+
+        let (a, b) = futures::join!(
+            delayed_get(&client, 0, 25011, "de"),
+            delayed_get(&client, 200, 25011, "de"),
+        );
+
+        assert!(a.is_ok());
+        assert!(b.is_ok());
+        m.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn routes_failures_to_their_caller(client: BatchingApi) {
+        let _m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .create();
+
+        let (ok, err) = futures::join!(
+            delayed_get(&client, 0, 25011, "de"),
+            delayed_get(&client, 5, 99999, "de"),
+        );
+
+        assert_eq!(ok.expect("Failed to get recipe").data.id, 25011);
+        let err = err.expect_err("Unknown recipe succeeded");
+        let context = MccError::context(err.as_ref()).expect("Missing request context");
+        assert!(context.url.path().ends_with("/99999"));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn shares_failures_of_duplicate_ids(client: BatchingApi) {
+        let m = mock("GET", "/mcc/api/v1/recipe/99999")
+            .with_status(404)
+            .expect(1)
+            .create();
+
+        let (a, b) = futures::join!(
+            delayed_get(&client, 0, 99999, "de"),
+            delayed_get(&client, 5, 99999, "de"),
+        );
+
+        for err in [a, b] {
+            let err = err.expect_err("Unknown recipe succeeded");
+            let source = err.source().expect("Missing shared failure");
+            assert!(MccError::context(source).is_some());
+            assert_eq!(err.to_string(), source.to_string());
+        }
+        m.assert();
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn groups_language_variants(client: BatchingApi) {
+        let m = mock("GET", "/mcc/api/v1/recipe/25011")
+            .match_header("accept-language", "de")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+
+        let (a, b) = futures::join!(
+            delayed_get(&client, 0, 25011, "de"),
+            delayed_get(&client, 5, 25011, "de-DE"),
+        );
+
+        assert!(a.is_ok());
+        assert!(b.is_ok());
+        m.assert();
+    }
+}
@@ -0,0 +1,632 @@
+use super::{
+    circuit::CircuitBreaker,
+    conditional::ConditionalCache,
+    slow::{SlowRequestCallback, SlowRequestMonitor},
+    Api, SlowRequest,
+};
+use crate::{
+    clock::{Clock, SystemClock},
+    transport::{HttpTransport, ReqwestTransport},
+    Result,
+};
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::sync::Semaphore;
+
+/// Builder for [`Api`] instances with non-default settings
+#[derive(Default)]
+pub struct ApiBuilder {
+    bearer_token: Option<String>,
+    circuit_breaker: Option<(u32, Duration)>,
+    slow_request_threshold: Option<Duration>,
+    on_slow_request: Option<SlowRequestCallback>,
+    semaphore: Option<Arc<Semaphore>>,
+    query_params: Vec<(String, String)>,
+    default_headers: HeaderMap,
+    max_body_bytes: Option<u64>,
+    max_json_depth: Option<usize>,
+    max_apk_bytes_per_sec: Option<u64>,
+    conditional_requests: bool,
+    exact_language_tags: bool,
+    resolve_overrides: Vec<(String, SocketAddr)>,
+    base_urls: Vec<String>,
+    proxy: Option<String>,
+    proxy_credentials: Option<(String, String)>,
+    clock: Option<Arc<dyn Clock>>,
+    transport: Option<Arc<dyn HttpTransport>>,
+    #[cfg(feature = "dns-cache")]
+    dns_cache_ttl: Option<Duration>,
+    #[cfg(feature = "uds")]
+    unix_socket: Option<std::path::PathBuf>,
+    #[cfg(feature = "debug-dump")]
+    dump_dir: Option<std::path::PathBuf>,
+    #[cfg(feature = "debug-dump")]
+    redaction: Option<crate::redact::Redaction>,
+}
+
+impl ApiBuilder {
+    /// Environment variable a bearer token is read from, if not set explicitly
+    pub const TOKEN_ENV_VAR: &'static str = "MCC_API_TOKEN";
+
+    /// Default for [`ApiBuilder::max_body_bytes`], the full catalog is well below
+    pub const DEFAULT_MAX_BODY_BYTES: u64 = 256 * 1024 * 1024;
+
+    /// Memory kept for [`ApiBuilder::conditional_requests`], a few thousand recipes
+    pub const CONDITIONAL_CACHE_BYTES: u64 = 16 * 1024 * 1024;
+
+    /// Default for [`ApiBuilder::max_json_depth`], recipes nest less than 10 levels
+    pub const DEFAULT_MAX_JSON_DEPTH: usize = 64;
+
+    /// Create builder with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send `Authorization: Bearer <token>` with every request
+    ///
+    /// Takes precedence over a token provided via `MCC_API_TOKEN`.
+    /// The header is marked sensitive, so reqwest won't print it in debug output.
+    pub fn bearer_token(mut self, token: &str) -> Self {
+        self.bearer_token = Some(token.to_string());
+        self
+    }
+
+    /// Fast-fail recipe requests after `threshold` consecutive failures
+    ///
+    /// Transport errors and 5xx responses count as failures. While the circuit
+    /// is open, requests return [`crate::MccError::CircuitOpen`] without
+    /// contacting the server. After `cooldown` one trial request is allowed,
+    /// its outcome decides whether the circuit closes again. A trial cancelled
+    /// before it completes counts as failed.
+    pub fn circuit_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.circuit_breaker = Some((threshold, cooldown));
+        self
+    }
+
+    /// Report recipe requests taking longer than `threshold`, e.g. 2s
+    ///
+    /// Time is measured until the full body was received. With the `tracing`
+    /// feature enabled a `warn` event with endpoint and elapsed time is
+    /// emitted. Requests are not timed unless a threshold is set.
+    pub fn slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// Call `callback` for every request exceeding the slow request threshold
+    ///
+    /// Has no effect without [`ApiBuilder::slow_request_threshold`].
+    pub fn on_slow_request<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&SlowRequest) + Send + Sync + 'static,
+    {
+        self.on_slow_request = Some(Box::new(callback));
+        self
+    }
+
+    /// Acquire a permit of `semaphore` for every request
+    ///
+    /// Share one semaphore between Api instances, or just clone the built Api,
+    /// to bound the total number of requests in flight. The permit is held
+    /// until the response body was read.
+    pub fn semaphore(mut self, semaphore: Arc<Semaphore>) -> Self {
+        self.semaphore = Some(semaphore);
+        self
+    }
+
+    /// Append `key=value` to the query of every recipe request
+    ///
+    /// Can be called multiple times, setting a key again replaces its value.
+    /// Use [`Api::with_query_param`] to override a value for single calls.
+    pub fn query_param(mut self, key: &str, value: &str) -> Self {
+        super::set_query_param(&mut self.query_params, key, value);
+        self
+    }
+
+    /// Send `headers` with every request, in the order of the map
+    ///
+    /// They are written first, followed by the headers managed by this
+    /// crate in a fixed order: `Authorization`, `X-Recipe-Type`, per call
+    /// headers like `If-Modified-Since`, then `Accept-Language`. A managed
+    /// header replaces a default header of the same name in its position.
+    /// Replaces headers set by an earlier call.
+    ///
+    /// The order is kept on the wire for HTTP/1.1 only, HTTP/2 header
+    /// compression may reorder them.
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// Abort reading recipe responses larger than `limit` bytes
+    ///
+    /// Exceeding it fails the call with [`crate::MccError::BodyTooLarge`].
+    /// Streamed responses ([`Api::stream_recipes`]) are limited per recipe.
+    /// Defaults to [`ApiBuilder::DEFAULT_MAX_BODY_BYTES`].
+    pub fn max_body_bytes(mut self, limit: u64) -> Self {
+        self.max_body_bytes = Some(limit);
+        self
+    }
+
+    /// Reject recipe responses with arrays / objects nested deeper than `limit`
+    ///
+    /// Checked before deserializing, exceeding it fails the call with
+    /// [`crate::MccError::JsonTooDeep`]. Defaults to
+    /// [`ApiBuilder::DEFAULT_MAX_JSON_DEPTH`]. serde_json on its own stops
+    /// at 128 levels, so larger values have no effect.
+    pub fn max_json_depth(mut self, limit: usize) -> Self {
+        self.max_json_depth = Some(limit);
+        self
+    }
+
+    /// Throttle APK downloads to `limit` bytes per second
+    ///
+    /// Applies to [`Api::stream_apk_to`] and [`Api::download_apk_to_dir`],
+    /// reading the body is paused whenever it got ahead of the limit. A limit
+    /// above the actual throughput has no effect. Unlimited by default.
+    pub fn max_apk_bytes_per_sec(mut self, limit: u64) -> Self {
+        self.max_apk_bytes_per_sec = Some(limit);
+        self
+    }
+
+    /// Send language tags verbatim instead of reducing them to ISO 639-1
+    ///
+    /// By default `"DE"`, `"de-AT"` or `"de_DE"` are all sent as `de`, see
+    /// [`Api::normalize_language`].
+    pub fn exact_language_tags(mut self) -> Self {
+        self.exact_language_tags = true;
+        self
+    }
+
+    /// Revalidate single recipes with `If-Modified-Since` instead of refetching
+    ///
+    /// The body and `Last-Modified` header of each recipe response are kept
+    /// per id, language and recipe type. Subsequent fetches send the
+    /// timestamp and a `304 Not Modified` answer returns the kept recipe.
+    /// Responses without `Last-Modified` are not kept, so such recipes are
+    /// always fetched in full. At most [`ApiBuilder::CONDITIONAL_CACHE_BYTES`]
+    /// of recipes are kept, the least recently used ones are dropped first.
+    /// [`Api::prefetch`] fills the same cache.
+    pub fn conditional_requests(mut self) -> Self {
+        self.conditional_requests = true;
+        self
+    }
+
+    /// Connect to `addr` for requests to `domain`, skipping DNS
+    ///
+    /// Can be called multiple times for different domains. The port of
+    /// `addr` is ignored, requests go to the port of the URL. TLS
+    /// certificates are still validated against `domain`, but anything able
+    /// to listen on `addr` receives all requests, including the bearer token,
+    /// so only pin addresses you control or trust.
+    pub fn resolve(mut self, domain: &str, addr: SocketAddr) -> Self {
+        self.resolve_overrides.push((domain.to_string(), addr));
+        self
+    }
+
+    /// Send all requests through the proxy at `url`, e.g. `http://proxy:3128`
+    ///
+    /// Takes precedence over proxies configured via `HTTP_PROXY` / `HTTPS_PROXY`.
+    /// An invalid URL fails [`ApiBuilder::build`].
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.proxy = Some(url.to_string());
+        self
+    }
+
+    /// Authenticate to the [`ApiBuilder::proxy`] with HTTP basic auth
+    ///
+    /// Prefer this over credentials in the proxy URL, URLs end up in error
+    /// messages. The `Proxy-Authorization` header is marked sensitive, so
+    /// reqwest won't print it in debug output. Read the credentials from the
+    /// environment or a secret store instead of hard-coding them.
+    pub fn proxy_basic_auth(mut self, username: &str, password: &str) -> Self {
+        self.proxy_credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Take time from `clock` instead of the system clock
+    ///
+    /// Used for circuit breaker cooldowns, slow request timing, retry delays
+    /// and cache expiry. Pass a [`crate::clock::MockClock`] to test those
+    /// deterministically.
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Send requests through `transport` instead of the network
+    ///
+    /// Allows in-memory testing or non-reqwest backends. Connection level
+    /// settings like [`ApiBuilder::resolve`] only apply to the default
+    /// [`ReqwestTransport`] and are ignored with a custom transport.
+    pub fn transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Cache DNS lookups for `ttl`, e.g. 5 minutes, instead of resolving per connection
+    ///
+    /// Saves the lookup latency when bulk helpers open many connections.
+    /// TTLs of the DNS records are not honored, a changed address of the
+    /// host is only picked up once `ttl` expired. Failed lookups are not cached.
+    #[cfg(feature = "dns-cache")]
+    pub fn dns_cache(mut self, ttl: Duration) -> Self {
+        self.dns_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Hosts to send requests to, the first one preferred, e.g. a primary and regional mirrors
+    ///
+    /// A request failing to connect to one host, e.g. because it refuses
+    /// the connection or can't be resolved, is sent to the next one. The
+    /// host that answered is used for subsequent calls, also by clones of
+    /// the Api. Other failures like timeouts or error statuses are returned
+    /// as is. Only scheme, host and port of the URLs are used. Defaults to
+    /// [`Api::DEFAULT_BASE_URL`].
+    pub fn base_urls<S: Into<String>>(mut self, urls: Vec<S>) -> Self {
+        self.base_urls = urls.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Send all requests over the Unix domain socket at `path`, e.g. of a sidecar proxy
+    ///
+    /// The base URL is kept for path, query and `Host` header. Only
+    /// available on Unix, on other platforms [`ApiBuilder::build`] fails.
+    /// Replaces the default [`ReqwestTransport`], so connection settings
+    /// like [`ApiBuilder::proxy`] or [`ApiBuilder::resolve`] have no effect.
+    /// Can't be combined with [`ApiBuilder::transport`].
+    #[cfg(feature = "uds")]
+    pub fn unix_socket<P: Into<std::path::PathBuf>>(mut self, path: P) -> Self {
+        self.unix_socket = Some(path.into());
+        self
+    }
+
+    /// Write every raw recipe response below `dir` for debugging
+    ///
+    /// Files are named `<endpoint>/<language>/<recipe type>.json`, with the
+    /// response status and headers next to it as `.headers`. Cookies are
+    /// redacted, as is anything configured with [`ApiBuilder::redact`].
+    /// Request headers like `Authorization` are never written.
+    /// Meant as diagnostic aid when reporting bugs, not for production use.
+    #[cfg(feature = "debug-dump")]
+    pub fn dump_responses_to<P: Into<std::path::PathBuf>>(mut self, dir: P) -> Self {
+        self.dump_dir = Some(dir.into());
+        self
+    }
+
+    /// Mask headers and JSON fields in dumped responses
+    ///
+    /// Defaults to [`crate::redact::Redaction::new`], which masks cookies
+    /// and credentials.
+    #[cfg(feature = "debug-dump")]
+    pub fn redact(mut self, redaction: crate::redact::Redaction) -> Self {
+        self.redaction = Some(redaction);
+        self
+    }
+
+    /// Create the Api instance
+    ///
+    /// Fails for invalid settings, e.g. an explicit [`ApiBuilder::bearer_token`]
+    /// with characters not allowed in headers. A token from `MCC_API_TOKEN`
+    /// that isn't valid is ignored instead, so the environment alone can't
+    /// make this fail.
+    pub fn build(self) -> Result<Api> {
+        self.build_with_env(|name| std::env::var(name).ok())
+    }
+
+    fn build_with_env<F: Fn(&str) -> Option<String>>(self, lookup: F) -> Result<Api> {
+        let on_slow_request = self.on_slow_request;
+        let clock = self.clock.unwrap_or_else(|| Arc::new(SystemClock));
+
+        let bearer_token = match self.bearer_token {
+            Some(token) => {
+                Some(bearer_header(&token).ok_or("Bearer token contains invalid characters")?)
+            }
+            None => token_from_env(lookup),
+        };
+
+        if self.max_apk_bytes_per_sec == Some(0) {
+            return Err("APK bandwidth limit must be positive".into());
+        }
+
+        let base_urls = if self.base_urls.is_empty() {
+            vec![Api::default_base_url()]
+        } else {
+            self.base_urls
+        };
+        let base_urls = base_urls
+            .iter()
+            .map(|url| match reqwest::Url::parse(url) {
+                Ok(parsed) if parsed.has_host() => Ok(parsed),
+                _ => Err(format!("Invalid base URL: {:?}", url)),
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut session = reqwest::Client::builder();
+        for (domain, addr) in &self.resolve_overrides {
+            session = session.resolve(domain, *addr);
+        }
+        match (&self.proxy, &self.proxy_credentials) {
+            (Some(url), credentials) => {
+                let mut proxy = reqwest::Proxy::all(url.as_str())?;
+                if let Some((username, password)) = credentials {
+                    proxy = proxy.basic_auth(username, password);
+                }
+                session = session.proxy(proxy);
+            }
+            (None, Some(_)) => return Err("Proxy credentials given without a proxy".into()),
+            (None, None) => {}
+        }
+        #[cfg(feature = "dns-cache")]
+        if let Some(ttl) = self.dns_cache_ttl {
+            session = session.dns_resolver(Arc::new(super::dns::CachingResolver::new(
+                ttl,
+                clock.clone(),
+            )));
+        }
+
+        let session = session.build()?;
+        let transport = self.transport;
+        #[cfg(feature = "uds")]
+        let transport = match (transport, self.unix_socket) {
+            (Some(_), Some(_)) => {
+                return Err("A unix socket can't be combined with a custom transport".into())
+            }
+            (None, Some(path)) => Some(unix_socket_transport(path)?),
+            (transport, None) => transport,
+        };
+        let transport =
+            transport.unwrap_or_else(|| Arc::new(ReqwestTransport::new(session.clone())));
+
+        Ok(Api {
+            session,
+            transport,
+            bearer_token,
+            circuit_breaker: self.circuit_breaker.map(|(threshold, cooldown)| {
+                Arc::new(CircuitBreaker::new(threshold, cooldown, clock.clone()))
+            }),
+            slow_requests: self
+                .slow_request_threshold
+                .map(|threshold| Arc::new(SlowRequestMonitor::new(threshold, on_slow_request))),
+            semaphore: self.semaphore,
+            query_params: self.query_params,
+            default_headers: self.default_headers,
+            max_body_bytes: self
+                .max_body_bytes
+                .unwrap_or(ApiBuilder::DEFAULT_MAX_BODY_BYTES),
+            max_json_depth: self
+                .max_json_depth
+                .unwrap_or(ApiBuilder::DEFAULT_MAX_JSON_DEPTH),
+            max_apk_bytes_per_sec: self.max_apk_bytes_per_sec,
+            exact_language_tags: self.exact_language_tags,
+            base_urls,
+            preferred_host: Default::default(),
+            clock,
+            conditional: if self.conditional_requests {
+                Some(Arc::new(ConditionalCache::new(
+                    ApiBuilder::CONDITIONAL_CACHE_BYTES,
+                )))
+            } else {
+                None
+            },
+            #[cfg(feature = "debug-dump")]
+            dump_dir: self.dump_dir,
+            #[cfg(feature = "debug-dump")]
+            redaction: self.redaction.unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(all(unix, feature = "uds"))]
+fn unix_socket_transport(path: std::path::PathBuf) -> Result<Arc<dyn HttpTransport>> {
+    Ok(Arc::new(crate::transport::unix::UnixSocketTransport::new(
+        path,
+    )))
+}
+
+#[cfg(all(not(unix), feature = "uds"))]
+fn unix_socket_transport(path: std::path::PathBuf) -> Result<Arc<dyn HttpTransport>> {
+    Err(format!(
+        "Unix domain sockets are not supported on this platform, can't use {}",
+        path.display()
+    )
+    .into())
+}
+
+fn bearer_header(token: &str) -> Option<HeaderValue> {
+    let mut value = HeaderValue::from_str(&format!("Bearer {}", token)).ok()?;
+    value.set_sensitive(true);
+    Some(value)
+}
+
+fn token_from_env<F: Fn(&str) -> Option<String>>(lookup: F) -> Option<HeaderValue> {
+    let token = lookup(ApiBuilder::TOKEN_ENV_VAR).filter(|t| !t.is_empty())?;
+    let header = bearer_header(&token);
+
+    #[cfg(feature = "tracing")]
+    if header.is_none() {
+        tracing::warn!(
+            "Ignoring {}, it contains characters not allowed in headers",
+            ApiBuilder::TOKEN_ENV_VAR
+        );
+    }
+
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::ApkUpdate, tests::get_testdata};
+    use mockito::{mock, Matcher};
+    use reqwest::header::AUTHORIZATION;
+    use rstest::*;
+
+    #[tokio::test]
+    async fn bearer_token_is_sent() {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header(AUTHORIZATION.as_str(), "Bearer secret")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+        let _m2 = mock("GET", "/666a60bc-0ce2-4878-9e3b-23ba3ceaba5a/versions.txt")
+            .match_header(AUTHORIZATION.as_str(), "Bearer secret")
+            .with_status(200)
+            .with_body(get_testdata("versions.txt").expect("Failed to get testdata"))
+            .create();
+
+        let client = Api::builder()
+            .bearer_token("secret")
+            .build()
+            .expect("Failed to build Api");
+
+        client
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+        client
+            .get_apk_updates()
+            .await
+            .expect("Failed to fetch apk updates");
+    }
+
+    #[tokio::test]
+    async fn bearer_token_from_env() {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header(AUTHORIZATION.as_str(), "Bearer from-env")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+
+        Api::builder()
+            .build_with_env(|name| {
+                assert_eq!(name, ApiBuilder::TOKEN_ENV_VAR);
+                Some("from-env".to_string())
+            })
+            .expect("Failed to build Api")
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+    }
+
+    #[rstest]
+    #[case::unset(None, false)]
+    #[case::empty(Some(""), false)]
+    #[case::invalid(Some("line\nbreak"), false)]
+    #[case::valid(Some("from-env"), true)]
+    fn token_from_env(#[case] value: Option<&str>, #[case] used: bool) {
+        let token = super::token_from_env(|_| value.map(String::from));
+
+        assert_eq!(token.is_some(), used);
+    }
+
+    #[test]
+    fn invalid_env_token_is_ignored() {
+        let api = Api::builder()
+            .build_with_env(|_| Some("line\nbreak".to_string()))
+            .expect("Invalid env token should not fail the build");
+
+        assert!(!api.effective_config().bearer_token);
+    }
+
+    #[tokio::test]
+    async fn no_authorization_by_default() {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header(AUTHORIZATION.as_str(), Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+
+        Api::builder()
+            .build_with_env(|_| None)
+            .expect("Failed to build Api")
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+    }
+
+    #[test]
+    fn invalid_token_is_rejected() {
+        assert!(Api::builder().bearer_token("line\nbreak").build().is_err());
+    }
+
+    #[test]
+    fn zero_apk_bandwidth_is_rejected() {
+        assert!(Api::builder().max_apk_bytes_per_sec(0).build().is_err());
+    }
+
+    #[tokio::test]
+    async fn proxy_with_basic_auth() {
+        // Proxied requests carry the absolute URL as target
+        let m = mock(
+            "GET",
+            Matcher::Regex("^http://mcc.invalid/proxied/MCUpdate0.123.apk$".to_string()),
+        )
+        .match_header("proxy-authorization", "Basic dXNlcjpzM2NyM3Q=")
+        .with_status(200)
+        .with_body("apk")
+        .create();
+        let update = ApkUpdate::from_url("http://mcc.invalid/proxied/MCUpdate0.123.apk")
+            .expect("Failed to parse URL");
+
+        let client = Api::builder()
+            .proxy(&mockito::server_url())
+            .proxy_basic_auth("user", "s3cr3t")
+            .build()
+            .expect("Failed to build Api");
+        let mut sink = vec![];
+        client
+            .stream_apk_to(&update, &mut sink)
+            .await
+            .expect("Failed to download via proxy");
+
+        assert_eq!(sink, b"apk");
+        m.assert();
+    }
+
+    #[rstest]
+    #[case::invalid_url(Api::builder().proxy("not a url"))]
+    #[case::credentials_only(Api::builder().proxy_basic_auth("user", "s3cr3t"))]
+    fn invalid_proxy_is_rejected(#[case] builder: ApiBuilder) {
+        assert!(builder.build().is_err());
+    }
+
+    #[tokio::test]
+    async fn resolve_override() {
+        let _m = mock("GET", "/pinned/MCUpdate0.123.apk")
+            .with_status(200)
+            .with_body("apk")
+            .create();
+        let update = ApkUpdate::from_url(&format!(
+            "http://mcc.invalid:{}/pinned/MCUpdate0.123.apk",
+            mockito::server_address().port()
+        ))
+        .expect("Failed to parse URL");
+
+        let client = Api::builder()
+            .resolve("mcc.invalid", mockito::server_address())
+            .build()
+            .expect("Failed to build Api");
+        let mut sink = vec![];
+        let written = client
+            .stream_apk_to(&update, &mut sink)
+            .await
+            .expect("Failed to download via pinned address");
+
+        assert_eq!(written, 3);
+        assert_eq!(sink, b"apk");
+    }
+
+    #[cfg(feature = "uds")]
+    #[test]
+    fn unix_socket_with_transport_is_rejected() {
+        let builder = Api::builder()
+            .transport(Arc::new(ReqwestTransport::default()))
+            .unix_socket("/run/mcc.sock");
+
+        assert!(builder.build().is_err());
+    }
+}
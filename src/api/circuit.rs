@@ -0,0 +1,363 @@
+use crate::{clock::Clock, error::MccError, Result};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Default)]
+struct State {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Fast-fails requests after repeated failures to give the backend a break
+///
+/// After `threshold` consecutive failures the circuit opens and requests are
+/// rejected with [`MccError::CircuitOpen`]. Once `cooldown` passed, a single
+/// trial request is let through: success closes the circuit again, failure
+/// restarts the cooldown.
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    clock: Arc<dyn Clock>,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(threshold: u32, cooldown: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            clock,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub(crate) fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    pub(crate) fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// Check whether a request may be sent right now
+    ///
+    /// The outcome of the request is reported through the returned [`Attempt`].
+    pub(crate) fn before_request(&self) -> Result<Attempt<'_>> {
+        let mut state = self.state.lock().expect("Circuit breaker lock poisoned");
+
+        let mut trial = false;
+        if let Some(opened_at) = state.opened_at {
+            let elapsed = self.clock.now().saturating_duration_since(opened_at);
+            if elapsed < self.cooldown {
+                return Err(MccError::CircuitOpen {
+                    retry_in: self.cooldown - elapsed,
+                }
+                .into());
+            }
+            if state.trial_in_flight {
+                return Err(MccError::CircuitOpen {
+                    retry_in: Duration::from_secs(0),
+                }
+                .into());
+            }
+            state.trial_in_flight = true;
+            trial = true;
+        }
+
+        Ok(Attempt {
+            breaker: self,
+            trial,
+            finished: false,
+        })
+    }
+
+    /// Only the trial decides about an open circuit, other requests were
+    /// let through before it opened and report stale outcomes
+    fn record_success(&self, trial: bool) {
+        let mut state = self.state.lock().expect("Circuit breaker lock poisoned");
+        if state.opened_at.is_some() && !trial {
+            return;
+        }
+        *state = State::default();
+    }
+
+    fn record_failure(&self, trial: bool) {
+        let mut state = self.state.lock().expect("Circuit breaker lock poisoned");
+        if state.opened_at.is_some() && !trial {
+            return;
+        }
+
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        state.trial_in_flight = false;
+        if state.consecutive_failures >= self.threshold {
+            state.opened_at = Some(self.clock.now());
+        }
+    }
+}
+
+/// Request let through by [`CircuitBreaker::before_request`]
+///
+/// Dropping it without reporting an outcome, e.g. because the request future
+/// was cancelled, is neutral for regular requests. An abandoned trial counts
+/// as failed, so the cooldown restarts instead of the circuit staying open.
+#[derive(Debug)]
+pub(crate) struct Attempt<'a> {
+    breaker: &'a CircuitBreaker,
+    trial: bool,
+    finished: bool,
+}
+
+impl Attempt<'_> {
+    pub(crate) fn success(mut self) {
+        self.finished = true;
+        self.breaker.record_success(self.trial);
+    }
+
+    pub(crate) fn failure(mut self) {
+        self.finished = true;
+        self.breaker.record_failure(self.trial);
+    }
+}
+
+impl Drop for Attempt<'_> {
+    fn drop(&mut self) {
+        if !self.finished && self.trial {
+            self.breaker.record_failure(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::Api,
+        clock::{MockClock, SystemClock},
+        tests::get_testdata,
+    };
+    use futures::FutureExt;
+    use mockito::mock;
+
+    fn breaker(threshold: u32, cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(threshold, cooldown, Arc::new(SystemClock))
+    }
+
+    fn is_circuit_open(res: &Result<impl std::fmt::Debug>) -> bool {
+        retry_in(res).is_some()
+    }
+
+    fn retry_in(res: &Result<impl std::fmt::Debug>) -> Option<Duration> {
+        match res
+            .as_ref()
+            .map_err(|e| MccError::without_context(e.as_ref()).downcast_ref::<MccError>())
+        {
+            Err(Some(MccError::CircuitOpen { retry_in })) => Some(*retry_in),
+            _ => None,
+        }
+    }
+
+    fn fail(breaker: &CircuitBreaker) {
+        breaker.before_request().expect("Circuit open").failure();
+    }
+
+    #[test]
+    fn opens_after_threshold() {
+        let breaker = breaker(2, Duration::from_secs(60));
+
+        fail(&breaker);
+        assert!(breaker.before_request().is_ok());
+        fail(&breaker);
+        assert!(is_circuit_open(&breaker.before_request()));
+    }
+
+    #[test]
+    fn success_resets_failures() {
+        let breaker = breaker(2, Duration::from_secs(60));
+
+        fail(&breaker);
+        breaker.before_request().expect("Circuit open").success();
+        fail(&breaker);
+
+        assert!(breaker.before_request().is_ok());
+    }
+
+    #[test]
+    fn single_trial_after_cooldown() {
+        let clock = Arc::new(MockClock::new());
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(10), clock.clone());
+
+        fail(&breaker);
+        clock.advance(Duration::from_secs(10));
+
+        let trial = breaker.before_request().expect("No trial after cooldown");
+        assert_eq!(
+            retry_in(&breaker.before_request()),
+            Some(Duration::from_secs(0))
+        );
+
+        // Failed trial restarts the cooldown
+        trial.failure();
+        assert_eq!(
+            retry_in(&breaker.before_request()),
+            Some(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn abandoned_trial() {
+        let clock = Arc::new(MockClock::new());
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(10), clock.clone());
+
+        fail(&breaker);
+        clock.advance(Duration::from_secs(10));
+        drop(breaker.before_request().expect("No trial after cooldown"));
+
+        assert_eq!(
+            retry_in(&breaker.before_request()),
+            Some(Duration::from_secs(10))
+        );
+        clock.advance(Duration::from_secs(10));
+        assert!(breaker.before_request().is_ok());
+    }
+
+    #[test]
+    fn abandoned_regular_request() {
+        let breaker = breaker(1, Duration::from_secs(60));
+
+        drop(breaker.before_request().expect("Circuit open"));
+
+        assert!(breaker.before_request().is_ok());
+    }
+
+    #[test]
+    fn stale_failure_during_trial() {
+        let clock = Arc::new(MockClock::new());
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(10), clock.clone());
+
+        let stale = breaker.before_request().expect("Circuit open");
+        fail(&breaker);
+        clock.advance(Duration::from_secs(10));
+        let _trial = breaker.before_request().expect("No trial after cooldown");
+
+        stale.failure();
+        assert_eq!(
+            retry_in(&breaker.before_request()),
+            Some(Duration::from_secs(0))
+        );
+    }
+
+    #[test]
+    fn stale_success_while_open() {
+        let clock = Arc::new(MockClock::new());
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(10), clock.clone());
+
+        let stale = breaker.before_request().expect("Circuit open");
+        fail(&breaker);
+
+        stale.success();
+        assert_eq!(
+            retry_in(&breaker.before_request()),
+            Some(Duration::from_secs(10))
+        );
+
+        // The trial still closes it
+        clock.advance(Duration::from_secs(10));
+        breaker.before_request().expect("No trial").success();
+        assert!(breaker.before_request().is_ok());
+    }
+
+    #[test]
+    fn cooldown_follows_clock() {
+        let clock = Arc::new(MockClock::new());
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(60), clock.clone());
+
+        fail(&breaker);
+        clock.advance(Duration::from_secs(59));
+        assert_eq!(
+            retry_in(&breaker.before_request()),
+            Some(Duration::from_secs(1))
+        );
+
+        clock.advance(Duration::from_secs(1));
+        assert!(breaker.before_request().is_ok());
+    }
+
+    #[tokio::test]
+    async fn failure_burst() {
+        let failing = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(503)
+            .expect(3)
+            .create();
+
+        let clock = Arc::new(MockClock::new());
+        let client = Api::builder()
+            .circuit_breaker(3, Duration::from_secs(30))
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to build Api");
+
+        for _ in 0..3 {
+            let res = client.get_recipe_ids("de", None).await;
+            assert!(res.is_err() && !is_circuit_open(&res));
+        }
+        for _ in 0..5 {
+            assert!(is_circuit_open(&client.get_recipe_ids("de", None).await));
+        }
+        failing.assert();
+        drop(failing);
+
+        let recovered = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .expect(2)
+            .create();
+
+        clock.advance(Duration::from_secs(30));
+
+        // Trial request closes the circuit again
+        assert!(client.get_recipe_ids("de", None).await.is_ok());
+        assert!(client.get_recipe_ids("de", None).await.is_ok());
+        recovered.assert();
+    }
+
+    #[tokio::test]
+    async fn dropped_trial_request() {
+        let failing = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(503)
+            .create();
+
+        let clock = Arc::new(MockClock::new());
+        let client = Api::builder()
+            .circuit_breaker(1, Duration::from_secs(30))
+            .clock(clock.clone())
+            .build()
+            .expect("Failed to build Api");
+
+        assert!(client.get_recipe_ids("de", None).await.is_err());
+        drop(failing);
+        clock.advance(Duration::from_secs(30));
+
+        // Polled once, the trial is sent but its response not awaited
+        let trial = client.get_recipe_ids("de", None).now_or_never();
+        assert!(trial.is_none());
+        assert_eq!(
+            retry_in(&client.get_recipe_ids("de", None).await),
+            Some(Duration::from_secs(30))
+        );
+
+        let recovered = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+        clock.advance(Duration::from_secs(30));
+
+        assert!(client.get_recipe_ids("de", None).await.is_ok());
+        recovered.assert();
+    }
+}
@@ -0,0 +1,243 @@
+use reqwest::header::HeaderValue;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Endpoint, language and recipe type of a cached response
+pub(crate) type CacheKey = (String, String, String);
+
+#[derive(Debug, Clone)]
+pub(crate) struct CachedResponse {
+    /// Only `None` for prefetched responses, which are dropped once used
+    pub(crate) last_modified: Option<HeaderValue>,
+    pub(crate) body: Arc<[u8]>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    response: CachedResponse,
+    last_used: u64,
+    /// Stored by a prefetch and not used since
+    prefetched: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<CacheKey, Entry>,
+    total_bytes: u64,
+    /// Monotonic counter ordering entries by last use
+    tick: u64,
+}
+
+impl State {
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.total_bytes -= entry.response.body.len() as u64;
+        }
+    }
+
+    fn insert(&mut self, key: CacheKey, response: CachedResponse, prefetched: bool) {
+        self.remove(&key);
+        self.tick += 1;
+        self.total_bytes += response.body.len() as u64;
+        let last_used = self.tick;
+        self.entries.insert(
+            key,
+            Entry {
+                response,
+                last_used,
+                prefetched,
+            },
+        );
+    }
+
+    /// Remove least recently used entries until `max_bytes` fits
+    fn evict(&mut self, max_bytes: u64) {
+        while self.total_bytes > max_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => self.remove(&key),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Last response of single recipe requests, revalidated with `If-Modified-Since`
+///
+/// Only responses carrying a `Last-Modified` header are kept, prefetched
+/// ones also without until they are used. Once the bodies exceed `max_bytes`
+/// in total, the least recently used ones are dropped.
+#[derive(Debug)]
+pub(crate) struct ConditionalCache {
+    max_bytes: u64,
+    state: Mutex<State>,
+}
+
+impl ConditionalCache {
+    pub(crate) fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Conditional cache lock poisoned")
+    }
+
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<CachedResponse> {
+        let mut state = self.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = state.entries.get_mut(key)?;
+        entry.last_used = tick;
+        Some(entry.response.clone())
+    }
+
+    /// Remember `body`, or forget the entry if the server sent no `Last-Modified`
+    ///
+    /// Bodies larger than the whole cache are not kept either.
+    pub(crate) fn store(&self, key: CacheKey, last_modified: Option<HeaderValue>, body: &[u8]) {
+        let mut state = self.lock();
+        state.remove(&key);
+
+        if last_modified.is_some() && body.len() as u64 <= self.max_bytes {
+            let response = CachedResponse {
+                last_modified,
+                body: body.into(),
+            };
+            state.insert(key, response, false);
+            state.evict(self.max_bytes);
+        }
+    }
+
+    /// Remember a prefetched `body` for [`ConditionalCache::take_prefetched`]
+    ///
+    /// Kept even without `Last-Modified`, but not if larger than the whole cache.
+    pub(crate) fn store_prefetched(
+        &self,
+        key: CacheKey,
+        last_modified: Option<HeaderValue>,
+        body: Arc<[u8]>,
+    ) {
+        let mut state = self.lock();
+        state.remove(&key);
+
+        if body.len() as u64 <= self.max_bytes {
+            state.insert(
+                key,
+                CachedResponse {
+                    last_modified,
+                    body,
+                },
+                true,
+            );
+            state.evict(self.max_bytes);
+        }
+    }
+
+    /// Body of a prefetched response not used yet, to use without revalidating
+    ///
+    /// Afterwards the entry is revalidated like any other, or dropped if it
+    /// has no `Last-Modified` to revalidate with.
+    pub(crate) fn take_prefetched(&self, key: &CacheKey) -> Option<Arc<[u8]>> {
+        let mut state = self.lock();
+        state.tick += 1;
+        let tick = state.tick;
+        let entry = state
+            .entries
+            .get_mut(key)
+            .filter(|entry| entry.prefetched)?;
+        entry.prefetched = false;
+        entry.last_used = tick;
+        let response = entry.response.clone();
+        if response.last_modified.is_none() {
+            state.remove(key);
+        }
+        Some(response.body)
+    }
+
+    /// Total size of the kept bodies in bytes
+    #[cfg(test)]
+    fn size(&self) -> u64 {
+        self.lock().total_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(id: &str) -> CacheKey {
+        (id.to_string(), "de".to_string(), "default".to_string())
+    }
+
+    fn date() -> Option<HeaderValue> {
+        Some(HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"))
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = ConditionalCache::new(250);
+
+        cache.store(key("1"), date(), &[0; 100]);
+        cache.store(key("2"), date(), &[0; 100]);
+        assert!(cache.get(&key("1")).is_some());
+        cache.store(key("3"), date(), &[0; 100]);
+
+        assert!(cache.get(&key("1")).is_some());
+        assert!(cache.get(&key("2")).is_none());
+        assert!(cache.get(&key("3")).is_some());
+        assert_eq!(cache.size(), 200);
+    }
+
+    #[test]
+    fn replaces_and_forgets_entries() {
+        let cache = ConditionalCache::new(1000);
+
+        cache.store(key("1"), date(), &[0; 100]);
+        cache.store(key("1"), date(), &[0; 50]);
+        assert_eq!(cache.size(), 50);
+
+        cache.store(key("1"), None, &[0; 50]);
+        assert!(cache.get(&key("1")).is_none());
+        assert_eq!(cache.size(), 0);
+    }
+
+    #[test]
+    fn prefetched_entries() {
+        let cache = ConditionalCache::new(1000);
+
+        cache.store_prefetched(key("1"), date(), vec![0; 100].into());
+        cache.store_prefetched(key("2"), None, vec![0; 50].into());
+        cache.store(key("3"), date(), &[0; 10]);
+
+        assert!(cache.take_prefetched(&key("3")).is_none());
+        assert!(cache.take_prefetched(&key("1")).is_some());
+        assert!(cache.take_prefetched(&key("1")).is_none());
+        assert!(cache.get(&key("1")).is_some());
+
+        // Without Last-Modified there is nothing to revalidate afterwards
+        assert!(cache.take_prefetched(&key("2")).is_some());
+        assert!(cache.get(&key("2")).is_none());
+        assert_eq!(cache.size(), 110);
+    }
+
+    #[test]
+    fn skips_oversized_bodies() {
+        let cache = ConditionalCache::new(100);
+
+        cache.store(key("1"), date(), &[0; 50]);
+        cache.store(key("2"), date(), &[0; 101]);
+
+        assert!(cache.get(&key("1")).is_some());
+        assert!(cache.get(&key("2")).is_none());
+        assert_eq!(cache.size(), 50);
+    }
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+/// Settings an [`super::Api`] was built with, see [`super::Api::effective_config`]
+///
+/// Language and recipe type are not part of it, they are passed with every
+/// call, as are retries via [`super::RetryBudget`]. Timeouts are up to the
+/// [`crate::transport::HttpTransport`], the default one has none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiConfig {
+    /// Host requests currently go to, the first of `base_urls` unless it failed
+    pub base_url: String,
+    /// All hosts in failover order, see [`super::ApiBuilder::base_urls`]
+    pub base_urls: Vec<String>,
+    /// Whether a bearer token is sent, the token itself is not exposed
+    pub bearer_token: bool,
+    /// Query parameters appended to every request
+    pub query_params: Vec<(String, String)>,
+    pub max_body_bytes: u64,
+    pub max_json_depth: usize,
+    pub max_apk_bytes_per_sec: Option<u64>,
+    /// Whether language tags are sent verbatim
+    pub exact_language_tags: bool,
+    /// Whether single recipes are cached and revalidated
+    pub conditional_requests: bool,
+    /// Failure threshold and cooldown of the circuit breaker, if enabled
+    pub circuit_breaker: Option<(u32, Duration)>,
+    /// Duration from which requests are reported as slow, if enabled
+    pub slow_request_threshold: Option<Duration>,
+    /// Whether requests are limited by a shared semaphore
+    pub shared_request_limit: bool,
+}
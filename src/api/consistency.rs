@@ -0,0 +1,88 @@
+use crate::schemas::Recipe;
+use std::collections::{BTreeMap, HashMap};
+
+/// Structural size of one translation of a recipe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructureCounts {
+    /// Ingredients summed over all ingredient bases
+    pub ingredients: usize,
+    /// Guided cooking steps
+    pub steps: usize,
+}
+
+impl StructureCounts {
+    fn of(recipe: &Recipe) -> Self {
+        Self {
+            ingredients: recipe
+                .data
+                .ingredients_bases
+                .iter()
+                .map(|base| base.ingredients.len())
+                .sum(),
+            steps: recipe.data.guided_cooking.steps.len(),
+        }
+    }
+}
+
+/// Count that differs between translations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountMismatch {
+    Ingredients,
+    Steps,
+}
+
+/// Result of [`super::Api::check_translation_consistency`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// Recipe id checked
+    pub id: u32,
+    /// Counts per language the recipe is available in
+    pub counts: BTreeMap<String, StructureCounts>,
+    /// Requested languages the recipe is not available in
+    pub missing: Vec<String>,
+    /// Counts differing between the available translations
+    pub mismatches: Vec<CountMismatch>,
+}
+
+impl ConsistencyReport {
+    pub(crate) fn new(id: u32, translations: HashMap<String, Option<Recipe>>) -> Self {
+        let mut counts = BTreeMap::new();
+        let mut missing = Vec::new();
+        for (lang, recipe) in translations {
+            match recipe {
+                Some(recipe) => {
+                    counts.insert(lang, StructureCounts::of(&recipe));
+                }
+                None => missing.push(lang),
+            }
+        }
+        missing.sort();
+
+        let differs = |count: fn(&StructureCounts) -> usize| {
+            let mut values = counts.values().map(count);
+            match values.next() {
+                Some(first) => values.any(|other| other != first),
+                None => false,
+            }
+        };
+        let mut mismatches = Vec::new();
+        if differs(|c| c.ingredients) {
+            mismatches.push(CountMismatch::Ingredients);
+        }
+        if differs(|c| c.steps) {
+            mismatches.push(CountMismatch::Steps);
+        }
+
+        Self {
+            id,
+            counts,
+            missing,
+            mismatches,
+        }
+    }
+
+    /// Whether all available translations share the same structure
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
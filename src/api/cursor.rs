@@ -0,0 +1,123 @@
+use super::Api;
+use crate::{schemas::RecipeType, Result};
+
+/// Recipe ids handed out in batches, see [`Api::recipe_ids_cursor`]
+///
+/// The server has no cursor support, the full id list is fetched with the
+/// first [`IdCursor::next_batch`] and chunked on the client. A failed fetch
+/// is attempted again by the next call.
+pub struct IdCursor<'a> {
+    api: &'a Api,
+    language: String,
+    recipe_type: Option<RecipeType>,
+    batch_size: usize,
+    ids: Option<Vec<u32>>,
+    position: usize,
+}
+
+impl<'a> IdCursor<'a> {
+    /// Default for [`IdCursor::batch_size`]
+    pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+    pub(crate) fn new(api: &'a Api, language: &str, recipe_type: Option<RecipeType>) -> Self {
+        Self {
+            api,
+            language: language.to_string(),
+            recipe_type,
+            batch_size: IdCursor::DEFAULT_BATCH_SIZE,
+            ids: None,
+            position: 0,
+        }
+    }
+
+    /// Ids per batch, at least 1
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Next batch of ids in server order, `None` once all were returned
+    pub async fn next_batch(&mut self) -> Result<Option<Vec<u32>>> {
+        let ids = match &self.ids {
+            Some(ids) => ids,
+            None => {
+                let ids = self
+                    .api
+                    .get_recipe_ids(&self.language, self.recipe_type.clone())
+                    .await?;
+                self.ids.get_or_insert(ids)
+            }
+        };
+
+        if self.position >= ids.len() {
+            return Ok(None);
+        }
+        let end = ids.len().min(self.position + self.batch_size);
+        let batch = ids[self.position..end].to_vec();
+        self.position = end;
+
+        Ok(Some(batch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use mockito::mock;
+
+    #[tokio::test]
+    async fn iterate_to_exhaustion() {
+        let m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids_unsorted.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+        let client = Api::new();
+
+        let mut cursor = client.recipe_ids_cursor("de", None).await.batch_size(2);
+        let mut batches = vec![];
+        while let Some(batch) = cursor.next_batch().await.expect("Failed to get batch") {
+            batches.push(batch);
+        }
+
+        assert_eq!(
+            batches,
+            vec![vec![25012, 311], vec![25011, 4711], vec![1002]]
+        );
+        assert_eq!(
+            cursor.next_batch().await.expect("Failed to get batch"),
+            None
+        );
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn retries_failed_fetch() {
+        let client = Api::new();
+        let mut cursor = client.recipe_ids_cursor("de", Some(RecipeType::Beta)).await;
+
+        let failing = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header("x-recipe-type", "beta")
+            .with_status(503)
+            .create();
+        assert!(cursor.next_batch().await.is_err());
+        drop(failing);
+
+        let _ok = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_header("x-recipe-type", "beta")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25011]}"#)
+            .create();
+        assert_eq!(
+            cursor.next_batch().await.expect("Failed to get batch"),
+            Some(vec![25011])
+        );
+        assert_eq!(
+            cursor.next_batch().await.expect("Failed to get batch"),
+            None
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{schemas::Recipe, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Best-effort result of a batch fetch bounded by a deadline
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeadlineReport {
+    /// Recipes fetched before the deadline, in the order of the requested ids
+    ///
+    /// Ids requested more than once are fetched and listed once.
+    pub recipes: Vec<Recipe>,
+    /// Ids whose fetch failed before the deadline, with the error
+    pub failed: BTreeMap<u32, String>,
+    /// Ids still pending when the deadline passed, in requested order
+    pub unfinished: Vec<u32>,
+}
+
+impl DeadlineReport {
+    pub(crate) fn new(ids: &[u32], mut results: HashMap<u32, Result<Recipe>>) -> Self {
+        let mut report = Self::default();
+        let mut seen = HashSet::new();
+
+        for id in ids.iter().filter(|id| seen.insert(**id)) {
+            match results.remove(id) {
+                Some(Ok(recipe)) => report.recipes.push(recipe),
+                Some(Err(err)) => {
+                    report.failed.insert(*id, err.to_string());
+                }
+                None => report.unfinished.push(*id),
+            }
+        }
+
+        report
+    }
+
+    /// Whether every requested recipe was fetched in time
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.unfinished.is_empty()
+    }
+}
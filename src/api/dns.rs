@@ -0,0 +1,116 @@
+use crate::clock::Clock;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug)]
+struct Entry {
+    resolved_at: Instant,
+    addrs: Vec<SocketAddr>,
+}
+
+/// Resolver keeping lookup results for a fixed time
+///
+/// The system resolver is queried on a miss, its TTLs are not known and
+/// therefore not honored. Failed lookups are not cached.
+#[derive(Debug, Clone)]
+pub(crate) struct CachingResolver {
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+impl CachingResolver {
+    pub(crate) fn new(ttl: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            ttl,
+            clock,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().expect("DNS cache lock poisoned")
+    }
+
+    fn cached(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        match self.lock().get(host) {
+            Some(entry) if self.clock.now() - entry.resolved_at < self.ttl => {
+                Some(entry.addrs.clone())
+            }
+            _ => None,
+        }
+    }
+
+    async fn lookup(&self, host: String) -> crate::Result<Vec<SocketAddr>> {
+        if let Some(addrs) = self.cached(&host) {
+            return Ok(addrs);
+        }
+
+        // Port is replaced by the connector with the one of the URL
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+        self.lock().insert(
+            host,
+            Entry {
+                resolved_at: self.clock.now(),
+                addrs: addrs.clone(),
+            },
+        );
+
+        Ok(addrs)
+    }
+}
+
+impl Resolve for CachingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str().to_string()).await?;
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{MockClock, SystemClock};
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn caches_until_ttl() {
+        let clock = Arc::new(MockClock::new());
+        let resolver = CachingResolver::new(Duration::from_secs(60), clock.clone());
+        assert!(resolver.cached("localhost").is_none());
+
+        let addrs: Vec<SocketAddr> = resolver
+            .resolve(Name::from_str("localhost").expect("Invalid name"))
+            .await
+            .expect("Failed to resolve")
+            .collect();
+        assert!(addrs.iter().all(|addr| addr.ip().is_loopback()));
+        assert_eq!(resolver.cached("localhost"), Some(addrs));
+
+        clock.advance(Duration::from_secs(59));
+        assert!(resolver.cached("localhost").is_some());
+        clock.advance(Duration::from_secs(1));
+        assert!(resolver.cached("localhost").is_none());
+    }
+
+    #[tokio::test]
+    async fn failures_are_not_cached() {
+        let resolver = CachingResolver::new(Duration::from_secs(60), Arc::new(SystemClock));
+
+        assert!(resolver
+            .resolve(Name::from_str("mcc.invalid").expect("Invalid name"))
+            .await
+            .is_err());
+        assert!(resolver.lock().is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+use crate::schemas::Recipe;
+use std::collections::BTreeMap;
+
+/// Result of [`super::Api::detect_drift`], all ids sorted ascending
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DriftReport {
+    /// Local recipes whose content differs from the server, see [`Recipe::content_hash`]
+    pub stale: Vec<u32>,
+    /// Local recipes the server no longer offers
+    pub missing_from_server: Vec<u32>,
+    /// Recipes on the server not present locally
+    pub new: Vec<u32>,
+}
+
+impl DriftReport {
+    pub(crate) fn new(local: &[Recipe], remote: &[Recipe]) -> Self {
+        let hashes = |recipes: &[Recipe]| {
+            recipes
+                .iter()
+                .map(|r| (r.data.id, r.content_hash()))
+                .collect::<BTreeMap<u32, u64>>()
+        };
+        let local = hashes(local);
+        let remote = hashes(remote);
+
+        let mut report = Self::default();
+        for (id, hash) in &local {
+            match remote.get(id) {
+                Some(remote_hash) if remote_hash != hash => report.stale.push(*id),
+                Some(_) => {}
+                None => report.missing_from_server.push(*id),
+            }
+        }
+        report.new = remote
+            .keys()
+            .filter(|id| !local.contains_key(id))
+            .copied()
+            .collect();
+
+        report
+    }
+
+    /// Whether the local recipes match the server exactly
+    pub fn is_empty(&self) -> bool {
+        self.stale.is_empty() && self.missing_from_server.is_empty() && self.new.is_empty()
+    }
+}
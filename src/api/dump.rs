@@ -0,0 +1,136 @@
+//! Diagnostic dump of raw recipe responses
+//!
+//! Attach the dumped files when reporting parsing issues, they contain
+//! exactly what the server returned, except for what the [`Redaction`]
+//! masks. Request headers, in particular `Authorization`, are never written.
+use crate::redact::Redaction;
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// Status and headers of a response, captured before its body is consumed
+pub(crate) struct ResponseHead(String);
+
+impl ResponseHead {
+    pub(crate) fn of(response: &reqwest::Response, redaction: &Redaction) -> Self {
+        let mut head = format!("{:?} {}\n", response.version(), response.status());
+        for (name, value) in response.headers() {
+            let value = if redaction.is_redacted_header(name.as_str()) {
+                Redaction::PLACEHOLDER
+            } else {
+                value.to_str().unwrap_or("[binary]")
+            };
+            let _ = writeln!(head, "{}: {}", name, value);
+        }
+        ResponseHead(head)
+    }
+}
+
+/// Path components are taken from fixed endpoint names, ids and language
+/// codes, anything else is replaced to stay inside the dump directory
+fn path_component(s: &str) -> String {
+    if s.is_empty() {
+        return "_".into();
+    }
+    s.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Location of the dump for a request, `<dir>/<endpoint>/<language>/<recipe type>.json`
+pub(crate) fn dump_path(dir: &Path, endpoint: &str, language: &str, recipe_type: &str) -> PathBuf {
+    dir.join(path_component(endpoint))
+        .join(path_component(language))
+        .join(format!("{}.json", path_component(recipe_type)))
+}
+
+/// Write body and response head (as `.headers` next to it), overwriting earlier dumps
+pub(crate) fn write(
+    path: &Path,
+    head: &ResponseHead,
+    body: &[u8],
+    redaction: &Redaction,
+) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, redaction.redact_body(body))?;
+    std::fs::write(path.with_extension("headers"), &head.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::Api, tests::get_testdata};
+    use mockito::mock;
+
+    #[tokio::test]
+    async fn writes_response_for_request() {
+        let body = get_testdata("recipe_ids.json").expect("Failed to get testdata");
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("set-cookie", "session=secret")
+            .with_body(&body)
+            .create();
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+        Api::builder()
+            .bearer_token("secret-token")
+            .dump_responses_to(dir.path())
+            .build()
+            .expect("Failed to build Api")
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        let path = dir.path().join("ids").join("de").join("default.json");
+        assert_eq!(std::fs::read(&path).expect("Dump not written"), body);
+
+        let head =
+            std::fs::read_to_string(path.with_extension("headers")).expect("Headers not written");
+        assert!(head.contains("200 OK"));
+        assert!(head.contains("content-type: application/json"));
+        assert!(head.contains("set-cookie: [redacted]"));
+        assert!(!head.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn redacts_configured_header_and_field() {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("x-user-id", "u-4711")
+            .with_body(r#"{"ids":[25011],"owner":{"uid":"u-4711"}}"#)
+            .create();
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+        Api::builder()
+            .dump_responses_to(dir.path())
+            .redact(Redaction::new().header("X-User-Id").json_field("owner.uid"))
+            .build()
+            .expect("Failed to build Api")
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        let path = dir.path().join("ids").join("de").join("default.json");
+        let body = std::fs::read_to_string(&path).expect("Dump not written");
+        let head =
+            std::fs::read_to_string(path.with_extension("headers")).expect("Headers not written");
+        assert!(head.contains("x-user-id: [redacted]"));
+        assert_eq!(body, r#"{"ids":[25011],"owner":{"uid":"[redacted]"}}"#);
+        assert!(!head.contains("u-4711"));
+    }
+
+    #[test]
+    fn path_stays_inside_dir() {
+        let path = dump_path(Path::new("/dump"), "../all", "", "default");
+
+        assert_eq!(path, Path::new("/dump/___all/_/default.json"));
+    }
+}
@@ -0,0 +1,346 @@
+use super::Api;
+use crate::{hash::fnv1a, schemas::Recipe, Result};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+#[derive(Debug)]
+struct Entry {
+    size: u64,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<String, Entry>,
+    total_bytes: u64,
+    /// Monotonic counter ordering entries by last use
+    tick: u64,
+}
+
+impl State {
+    fn touch(&mut self, filename: &str) -> bool {
+        self.tick += 1;
+        let tick = self.tick;
+        match self.entries.get_mut(filename) {
+            Some(entry) => {
+                entry.last_used = tick;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, filename: String, size: u64) {
+        self.tick += 1;
+        self.total_bytes += size;
+        let previous = self.entries.insert(
+            filename,
+            Entry {
+                size,
+                last_used: self.tick,
+            },
+        );
+        if let Some(previous) = previous {
+            self.total_bytes -= previous.size;
+        }
+    }
+
+    /// Remove least recently used entries until `max_bytes` fits, sparing `keep`
+    fn evict(&mut self, max_bytes: u64, keep: &str) -> Vec<String> {
+        let mut evicted = vec![];
+        while self.total_bytes > max_bytes {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(name, _)| name.as_str() != keep)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(name, _)| name.clone());
+
+            match oldest {
+                Some(name) => {
+                    if let Some(entry) = self.entries.remove(&name) {
+                        self.total_bytes -= entry.size;
+                    }
+                    evicted.push(name);
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+}
+
+/// On-disk cache for recipe images, bounded by total size
+///
+/// Images are stored in a flat directory, named after a hash of their URL.
+/// When the cache grows beyond `max_bytes`, the least recently used images
+/// are deleted. Usage order survives restarts via the file modification time.
+/// The image just fetched is never evicted, even if it alone exceeds the budget.
+pub struct ImageCache {
+    api: Api,
+    dir: PathBuf,
+    max_bytes: u64,
+    state: Mutex<State>,
+}
+
+impl ImageCache {
+    /// Open cache in `dir`, picking up images cached by earlier runs
+    pub fn new<P: Into<PathBuf>>(api: Api, dir: P, max_bytes: u64) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut files = vec![];
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let filename = entry.file_name().to_string_lossy().into_owned();
+            if !metadata.is_file() {
+                continue;
+            }
+            if filename.ends_with(".part") || crate::files::is_temp_file(&filename) {
+                // Interrupted download
+                let _ = fs::remove_file(entry.path());
+                continue;
+            }
+            files.push((
+                metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                filename,
+                metadata.len(),
+            ));
+        }
+        files.sort();
+
+        let mut state = State::default();
+        for (_, filename, size) in files {
+            state.insert(filename, size);
+        }
+
+        let cache = Self {
+            api,
+            dir,
+            max_bytes,
+            state: Mutex::new(state),
+        };
+        cache.remove_files(cache.lock().evict(max_bytes, ""));
+
+        Ok(cache)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Image cache lock poisoned")
+    }
+
+    /// File name for an URL, keeping a short alphanumeric extension
+    fn filename(url: &str) -> String {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .filter(|ext| ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        match extension {
+            Some(ext) => format!("{:016x}.{}", fnv1a(url.as_bytes()), ext.to_lowercase()),
+            None => format!("{:016x}", fnv1a(url.as_bytes())),
+        }
+    }
+
+    fn remove_files(&self, filenames: Vec<String>) {
+        for filename in filenames {
+            // Already gone is just as good
+            let _ = fs::remove_file(self.dir.join(filename));
+        }
+    }
+
+    /// Path of the cached image, downloading it on cache miss
+    ///
+    /// Downloads are limited by [`super::ApiBuilder::max_body_bytes`] like
+    /// recipe responses. The bearer token is only sent if the image is on
+    /// one of the base hosts.
+    pub async fn image(&self, url: &str) -> Result<PathBuf> {
+        let filename = ImageCache::filename(url);
+        let path = self.dir.join(&filename);
+
+        if self.lock().touch(&filename) && path.is_file() {
+            // Persist usage order, failing to do so only affects eviction after restart
+            if let Ok(file) = fs::File::options().append(true).open(&path) {
+                let _ = file.set_modified(SystemTime::now());
+            }
+            return Ok(path);
+        }
+
+        let _permit = self.api.acquire_permit().await?;
+        let response = self
+            .api
+            .send(
+                self.api
+                    .request(reqwest::Method::GET, reqwest::Url::parse(url)?),
+            )
+            .await?
+            .error_for_status()?;
+        let body = self.api.read_body(response).await?;
+        let size = body.len() as u64;
+
+        crate::files::write_atomic_async(path.clone(), body).await?;
+
+        let evicted = {
+            let mut state = self.lock();
+            state.insert(filename.clone(), size);
+            state.evict(self.max_bytes, &filename)
+        };
+        self.remove_files(evicted);
+
+        Ok(path)
+    }
+
+    /// Cached paths of all images of a recipe, see [`Recipe::image_urls`]
+    pub async fn recipe_images(&self, recipe: &Recipe) -> Result<Vec<PathBuf>> {
+        let mut paths = vec![];
+        for url in recipe.image_urls() {
+            paths.push(self.image(&url).await?);
+        }
+        Ok(paths)
+    }
+
+    /// Total size of all cached images in bytes
+    pub fn size(&self) -> u64 {
+        self.lock().total_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use mockito::{mock, Mock};
+
+    fn image_mock(name: &str, hits: usize) -> Mock {
+        mock("GET", format!("/recipe_images/{}", name).as_str())
+            .with_status(200)
+            .with_header("content-type", "image/jpeg")
+            .with_body(vec![0u8; 100])
+            .expect(hits)
+            .create()
+    }
+
+    fn url(name: &str) -> String {
+        format!("{}/recipe_images/{}", mockito::server_url(), name)
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used() {
+        let mocks = vec![
+            image_mock("a.jpg", 1),
+            image_mock("b.jpg", 2),
+            image_mock("c.jpg", 1),
+        ];
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let cache = ImageCache::new(Api::new(), dir.path(), 250).expect("Failed to open cache");
+
+        let a = cache.image(&url("a.jpg")).await.expect("Failed to get a");
+        let b = cache.image(&url("b.jpg")).await.expect("Failed to get b");
+        // Hit, a becomes the most recently used
+        assert_eq!(
+            cache.image(&url("a.jpg")).await.expect("Failed to get a"),
+            a
+        );
+
+        let c = cache.image(&url("c.jpg")).await.expect("Failed to get c");
+
+        assert!(a.is_file());
+        assert!(!b.exists(), "b should have been evicted");
+        assert!(c.is_file());
+        assert_eq!(cache.size(), 200);
+
+        // Evicted image is downloaded again
+        cache.image(&url("b.jpg")).await.expect("Failed to get b");
+        for m in mocks {
+            m.assert();
+        }
+    }
+
+    #[tokio::test]
+    async fn image_too_large() {
+        let m = image_mock("e.jpg", 1);
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let api = Api::builder()
+            .max_body_bytes(50)
+            .build()
+            .expect("Failed to build Api");
+        let cache = ImageCache::new(api, dir.path(), 1000).expect("Failed to open cache");
+
+        let err = cache
+            .image(&url("e.jpg"))
+            .await
+            .expect_err("Image should be too large");
+
+        assert!(matches!(
+            err.downcast_ref::<crate::MccError>(),
+            Some(crate::MccError::BodyTooLarge { limit: 50 })
+        ));
+        assert_eq!(cache.size(), 0);
+        assert_eq!(fs::read_dir(dir.path()).expect("Failed to list").count(), 0);
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn no_token_for_other_hosts() {
+        let m = mock("GET", "/recipe_images/f.jpg")
+            .match_header("authorization", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(vec![0u8; 10])
+            .expect(1)
+            .create();
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let api = Api::builder()
+            .base_urls(vec!["http://mcc.invalid"])
+            .bearer_token("secret")
+            .build()
+            .expect("Failed to build Api");
+        let cache = ImageCache::new(api, dir.path(), 1000).expect("Failed to open cache");
+
+        cache.image(&url("f.jpg")).await.expect("Failed to get f");
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn picks_up_existing_files() {
+        let m = image_mock("d.jpg", 1);
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+        let first = ImageCache::new(Api::new(), dir.path(), 1000).expect("Failed to open cache");
+        let path = first.image(&url("d.jpg")).await.expect("Failed to get d");
+        drop(first);
+
+        let second = ImageCache::new(Api::new(), dir.path(), 1000).expect("Failed to open cache");
+        assert_eq!(second.size(), 100);
+        assert_eq!(
+            second.image(&url("d.jpg")).await.expect("Failed to get d"),
+            path
+        );
+        m.assert();
+    }
+
+    #[tokio::test]
+    async fn recipe_images() {
+        let m = image_mock("Platzhalter_Madame.jpg", 1);
+        let json = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let mut recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+        recipe.data.image_base = format!("{}/recipe_images/", mockito::server_url());
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let cache = ImageCache::new(Api::new(), dir.path(), 1000).expect("Failed to open cache");
+
+        let paths = cache
+            .recipe_images(&recipe)
+            .await
+            .expect("Failed to get recipe images");
+
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].extension(), Some(std::ffi::OsStr::new("jpg")));
+        m.assert();
+    }
+}
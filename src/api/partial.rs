@@ -0,0 +1,282 @@
+use crate::{
+    schemas::{IngredientsBase, Nutrient, Tag},
+    Result,
+};
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+use std::fmt;
+
+/// Field of [`crate::schemas::RecipeData`] selectable for [`PartialRecipe`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecipeField {
+    Id,
+    Name,
+    Tags,
+    Duration,
+    DurationTotal,
+    Complexity,
+    Yield,
+    YieldUnit,
+    ImageBase,
+    ImageName,
+    Nutrients,
+    Instructions,
+    IngredientsBases,
+    Updated,
+}
+
+impl RecipeField {
+    /// Field for JSON key `key`, accepting the same snake_case aliases as `RecipeData`
+    fn from_key(key: &str) -> Option<Self> {
+        Some(match key {
+            "id" => RecipeField::Id,
+            "name" => RecipeField::Name,
+            "tags" => RecipeField::Tags,
+            "duration" => RecipeField::Duration,
+            "durationTotal" | "duration_total" => RecipeField::DurationTotal,
+            "complexity" => RecipeField::Complexity,
+            "yield" => RecipeField::Yield,
+            "yieldUnit" | "yield_unit" => RecipeField::YieldUnit,
+            "imageBase" | "image_base" => RecipeField::ImageBase,
+            "imageName" | "image_name" => RecipeField::ImageName,
+            "nutrients" => RecipeField::Nutrients,
+            "instructions" => RecipeField::Instructions,
+            "ingredientsBases" | "ingredients_bases" => RecipeField::IngredientsBases,
+            "updated" => RecipeField::Updated,
+            _ => return None,
+        })
+    }
+}
+
+/// Recipe with only the requested fields, see [`crate::api::Api::get_recipe_fields`]
+///
+/// Fields not requested, or missing in the response, are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PartialRecipe {
+    pub id: Option<u32>,
+    pub name: Option<String>,
+    pub tags: Option<Vec<Tag>>,
+    pub duration: Option<i64>,
+    pub duration_total: Option<i64>,
+    pub complexity: Option<String>,
+    pub yield_field: Option<i64>,
+    pub yield_unit: Option<String>,
+    pub image_base: Option<String>,
+    pub image_name: Option<String>,
+    pub nutrients: Option<Vec<Nutrient>>,
+    pub instructions: Option<Vec<String>>,
+    pub ingredients_bases: Option<Vec<IngredientsBase>>,
+    pub updated: Option<String>,
+}
+
+impl PartialRecipe {
+    /// Parse the recipe fields in `fields` from a recipe response
+    ///
+    /// Accepts the same layouts as [`crate::schemas::Recipe`]: fields inside
+    /// `data`, the id next to `data`, or no `data` wrapper at all. Everything
+    /// else is skipped without being deserialized.
+    pub(crate) fn from_slice(body: &[u8], fields: &[RecipeField]) -> Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_slice(body);
+        let recipe = deserializer.deserialize_map(Envelope(fields))?;
+        deserializer.end()?;
+        recipe.ok_or_else(|| "Recipe response has no data".into())
+    }
+}
+
+/// Field for JSON key `key`, `None` if it is not one of `fields`
+fn selected(fields: &[RecipeField], key: &str) -> Option<RecipeField> {
+    RecipeField::from_key(key).filter(|field| fields.contains(field))
+}
+
+/// Deserialize the value of `field` into `recipe`
+fn read_field<'de, A: MapAccess<'de>>(
+    recipe: &mut PartialRecipe,
+    field: RecipeField,
+    map: &mut A,
+) -> std::result::Result<(), A::Error> {
+    match field {
+        RecipeField::Id => recipe.id = Some(map.next_value()?),
+        RecipeField::Name => recipe.name = Some(map.next_value()?),
+        RecipeField::Tags => recipe.tags = Some(map.next_value()?),
+        RecipeField::Duration => recipe.duration = Some(map.next_value()?),
+        RecipeField::DurationTotal => recipe.duration_total = Some(map.next_value()?),
+        RecipeField::Complexity => recipe.complexity = Some(map.next_value()?),
+        RecipeField::Yield => recipe.yield_field = Some(map.next_value()?),
+        RecipeField::YieldUnit => recipe.yield_unit = Some(map.next_value()?),
+        RecipeField::ImageBase => recipe.image_base = Some(map.next_value()?),
+        RecipeField::ImageName => recipe.image_name = Some(map.next_value()?),
+        RecipeField::Nutrients => recipe.nutrients = Some(map.next_value()?),
+        RecipeField::Instructions => recipe.instructions = Some(map.next_value()?),
+        RecipeField::IngredientsBases => recipe.ingredients_bases = Some(map.next_value()?),
+        RecipeField::Updated => recipe.updated = Some(map.next_value()?),
+    }
+    Ok(())
+}
+
+/// Top level object of a recipe response, in any of the recipe layouts
+///
+/// Mirrors the layout detection of `Recipe`: the first `data` object holds
+/// the recipe, an id inside it wins over one next to it. Without `data`, the
+/// top level object is the recipe itself.
+struct Envelope<'a>(&'a [RecipeField]);
+
+impl<'de> Visitor<'de> for Envelope<'_> {
+    type Value = Option<PartialRecipe>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a recipe response")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut data: Option<PartialRecipe> = None;
+        let mut outer_id: Option<u32> = None;
+        let mut flat = PartialRecipe::default();
+        let mut is_flat = false;
+
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "data" && data.is_none() {
+                data = Some(map.next_value_seed(Data(self.0))?);
+            } else if key == "id" && outer_id.is_none() {
+                outer_id = Some(map.next_value()?);
+            } else if let Some(field) = RecipeField::from_key(&key) {
+                is_flat = true;
+                match selected(self.0, &key) {
+                    Some(_) => read_field(&mut flat, field, &mut map)?,
+                    None => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        let outer_id = outer_id.filter(|_| self.0.contains(&RecipeField::Id));
+        Ok(match data {
+            Some(mut data) => {
+                data.id = data.id.or(outer_id);
+                Some(data)
+            }
+            None if is_flat || outer_id.is_some() => {
+                flat.id = outer_id;
+                Some(flat)
+            }
+            None => None,
+        })
+    }
+}
+
+/// `data` object of a recipe response, deserializing only the selected fields
+struct Data<'a>(&'a [RecipeField]);
+
+impl<'de> DeserializeSeed<'de> for Data<'_> {
+    type Value = PartialRecipe;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Data<'_> {
+    type Value = PartialRecipe;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("recipe data")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut recipe = PartialRecipe::default();
+        while let Some(key) = map.next_key::<String>()? {
+            match selected(self.0, &key) {
+                Some(field) => read_field(&mut recipe, field, &mut map)?,
+                None => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(recipe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    #[rstest]
+    #[case::wrapped("recipe_single_25011.json")]
+    #[case::id_outside("recipe_single_25011_id_outside.json")]
+    #[case::flat("recipe_single_25011_flat.json")]
+    fn all_fields_match_full_recipe(#[case] filename: &str) {
+        let json = get_testdata(filename).expect("Failed to get testdata");
+        let full: crate::schemas::Recipe =
+            serde_json::from_slice(&json).expect("Failed to deserialize");
+        let all = [
+            RecipeField::Id,
+            RecipeField::Name,
+            RecipeField::Tags,
+            RecipeField::Duration,
+            RecipeField::DurationTotal,
+            RecipeField::Complexity,
+            RecipeField::Yield,
+            RecipeField::YieldUnit,
+            RecipeField::ImageBase,
+            RecipeField::ImageName,
+            RecipeField::Nutrients,
+            RecipeField::Instructions,
+            RecipeField::IngredientsBases,
+            RecipeField::Updated,
+        ];
+
+        let partial = PartialRecipe::from_slice(&json, &all).expect("Failed to parse");
+
+        let data = full.data;
+        assert_eq!(
+            partial,
+            PartialRecipe {
+                id: Some(data.id),
+                name: Some(data.name),
+                tags: Some(data.tags),
+                duration: Some(data.duration),
+                duration_total: Some(data.duration_total),
+                complexity: Some(data.complexity),
+                yield_field: Some(data.yield_field),
+                yield_unit: Some(data.yield_unit),
+                image_base: Some(data.image_base),
+                image_name: Some(data.image_name),
+                nutrients: Some(data.nutrients),
+                instructions: Some(data.instructions),
+                ingredients_bases: Some(data.ingredients_bases),
+                updated: Some(data.updated),
+            }
+        );
+    }
+
+    #[rstest]
+    #[case::wrapped(r#"{"id":1,"data":{"id":2,"name":"a"}}"#, Some(2))]
+    #[case::id_outside(r#"{"id":1,"data":{"name":"a"}}"#, Some(1))]
+    #[case::flat(r#"{"id":1,"name":"a","meta":{}}"#, Some(1))]
+    #[case::no_id(r#"{"name":"a"}"#, None)]
+    fn id_by_layout(#[case] json: &str, #[case] id: Option<u32>) {
+        let partial =
+            PartialRecipe::from_slice(json.as_bytes(), &[RecipeField::Id, RecipeField::Name])
+                .expect("Failed to parse");
+
+        assert_eq!(partial.id, id);
+        assert_eq!(partial.name.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn no_data() {
+        assert!(PartialRecipe::from_slice(br#"{"meta":{}}"#, &[RecipeField::Id]).is_err());
+    }
+}
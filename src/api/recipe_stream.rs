@@ -0,0 +1,78 @@
+use crate::{
+    error::{MccError, RequestContext},
+    json::ArraySplitter,
+    schemas::Recipe,
+    Result,
+};
+use tokio::sync::SemaphorePermit;
+
+/// Response of the "all" endpoint, parsed recipe by recipe as it arrives
+///
+/// The body as a whole is unbounded, but a single recipe larger than
+/// `max_element_bytes` fails the stream with [`MccError::BodyTooLarge`], so
+/// only that much is ever buffered. Errors are wrapped in
+/// [`MccError::Request`] for the request the response answers.
+pub(crate) struct RecipeStream<'a> {
+    response: reqwest::Response,
+    context: Option<RequestContext>,
+    splitter: ArraySplitter,
+    max_depth: usize,
+    max_element_bytes: u64,
+    _permit: Option<SemaphorePermit<'a>>,
+}
+
+impl<'a> RecipeStream<'a> {
+    pub(crate) fn new(
+        response: reqwest::Response,
+        max_depth: usize,
+        max_element_bytes: u64,
+        permit: Option<SemaphorePermit<'a>>,
+    ) -> Self {
+        Self {
+            context: super::Api::sent_context(&response),
+            response,
+            splitter: ArraySplitter::new(),
+            // Elements sit inside the array
+            max_depth: max_depth.saturating_sub(1),
+            max_element_bytes,
+            _permit: permit,
+        }
+    }
+
+    fn too_large(&self) -> Box<dyn std::error::Error + Send + Sync> {
+        MccError::BodyTooLarge {
+            limit: self.max_element_bytes,
+        }
+        .into()
+    }
+
+    pub(crate) async fn next(&mut self) -> Option<Result<Recipe>> {
+        let next = self.read_next().await?;
+        Some(next.map_err(|err| MccError::in_context(self.context.clone(), err)))
+    }
+
+    async fn read_next(&mut self) -> Option<Result<Recipe>> {
+        loop {
+            match self.splitter.next_element() {
+                Ok(Some(element)) if element.len() as u64 > self.max_element_bytes => {
+                    return Some(Err(self.too_large()))
+                }
+                Ok(Some(element)) => {
+                    return Some(crate::json::from_slice(&element, self.max_depth))
+                }
+                Ok(None) if self.splitter.is_done() => return None,
+                Ok(None) if self.splitter.buffered() as u64 > self.max_element_bytes => {
+                    return Some(Err(self.too_large()))
+                }
+                Ok(None) => {}
+                Err(e) => return Some(Err(e)),
+            }
+
+            match self.response.chunk().await {
+                Ok(Some(chunk)) => self.splitter.push(&chunk),
+                Ok(None) => return Some(Err("Recipe list ended unexpectedly".into())),
+                Err(e) => return Some(Err(MccError::from_transport(e))),
+            }
+        }
+    }
+}
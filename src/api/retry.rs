@@ -0,0 +1,128 @@
+use super::backoff::{BackoffStrategy, Fixed};
+use crate::error::MccError;
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Retries shared by all items of a batch operation
+///
+/// Each retry of any item takes one from the budget. Once it is used up,
+/// failing items are not retried anymore and the operation gives up, so an
+/// outage doesn't multiply the load by the number of retries per item.
+#[derive(Debug)]
+pub struct RetryBudget {
+    remaining: AtomicU32,
+    retries_per_item: u32,
+    backoff: Arc<dyn BackoffStrategy>,
+}
+
+impl RetryBudget {
+    /// Default upper bound of retries of a single item
+    pub const DEFAULT_RETRIES_PER_ITEM: u32 = 3;
+
+    /// Default pause before retrying an item, see [`Fixed`]
+    pub const DEFAULT_DELAY: Duration = Duration::from_millis(200);
+
+    /// Budget of `total` retries across all items
+    pub fn new(total: u32) -> Self {
+        Self {
+            remaining: AtomicU32::new(total),
+            retries_per_item: RetryBudget::DEFAULT_RETRIES_PER_ITEM,
+            backoff: Arc::new(Fixed::new(RetryBudget::DEFAULT_DELAY)),
+        }
+    }
+
+    /// Retry a single item at most `retries` times, budget permitting
+    pub fn retries_per_item(mut self, retries: u32) -> Self {
+        self.retries_per_item = retries;
+        self
+    }
+
+    /// Pause `delay` before each retry
+    pub fn delay(self, delay: Duration) -> Self {
+        self.backoff(Fixed::new(delay))
+    }
+
+    /// Pause as told by `strategy` before each retry of an item
+    ///
+    /// An item isn't retried anymore once the strategy returns `None`, or
+    /// after [`RetryBudget::retries_per_item`] retries, whatever comes first.
+    pub fn backoff<B: BackoffStrategy + 'static>(mut self, strategy: B) -> Self {
+        self.backoff = Arc::new(strategy);
+        self
+    }
+
+    /// Retries left for the whole batch
+    pub fn remaining(&self) -> u32 {
+        self.remaining.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn retries_per_item_limit(&self) -> u32 {
+        self.retries_per_item
+    }
+
+    /// Pause before retry number `attempt` of an item, `None` to give up
+    pub(crate) fn retry_delay(&self, attempt: u32) -> Option<Duration> {
+        self.backoff.next_delay(attempt)
+    }
+
+    /// Take one retry from the budget, `false` if it is used up
+    pub(crate) fn take(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
+/// Whether a failed request may succeed when sent again
+///
+/// Server errors and transport problems are, client errors and responses
+/// the crate can't parse are not.
+pub(crate) fn is_retryable(err: &(dyn Error + Send + Sync + 'static)) -> bool {
+    let err = MccError::without_context(err);
+    if let Some(err) = err.downcast_ref::<MccError>() {
+        return matches!(
+            err,
+            MccError::ConnectionRefused { .. }
+                | MccError::DnsFailure { .. }
+                | MccError::Timeout { .. }
+        );
+    }
+    if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+        return match err.status() {
+            Some(status) => status.is_server_error(),
+            None => err.is_connect() || err.is_timeout() || err.is_request() || err.is_body(),
+        };
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_is_shared() {
+        let budget = RetryBudget::new(2);
+
+        assert!(budget.take());
+        assert!(budget.take());
+        assert!(!budget.take());
+        assert_eq!(budget.remaining(), 0);
+    }
+
+    #[test]
+    fn backoff_replaces_delay() {
+        let budget = RetryBudget::new(2).delay(Duration::from_millis(5));
+        assert_eq!(budget.retry_delay(7), Some(Duration::from_millis(5)));
+
+        let budget = budget.backoff(Fixed::new(Duration::from_millis(1)).max_retries(1));
+        assert_eq!(budget.retry_delay(1), Some(Duration::from_millis(1)));
+        assert_eq!(budget.retry_delay(2), None);
+    }
+}
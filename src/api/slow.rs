@@ -0,0 +1,124 @@
+use std::{fmt, time::Duration};
+
+/// Recipe request that took longer than the configured threshold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowRequest {
+    /// Recipe endpoint queried, e.g. `ids`, `all` or a recipe id
+    pub endpoint: String,
+    /// Time until the full response body was received
+    pub elapsed: Duration,
+}
+
+pub(crate) type SlowRequestCallback = Box<dyn Fn(&SlowRequest) + Send + Sync>;
+
+/// Reports requests exceeding `threshold`
+///
+/// Only created if a threshold was configured, otherwise requests are not timed at all.
+pub(crate) struct SlowRequestMonitor {
+    threshold: Duration,
+    callback: Option<SlowRequestCallback>,
+}
+
+impl fmt::Debug for SlowRequestMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowRequestMonitor")
+            .field("threshold", &self.threshold)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+impl SlowRequestMonitor {
+    pub(crate) fn new(threshold: Duration, callback: Option<SlowRequestCallback>) -> Self {
+        Self {
+            threshold,
+            callback,
+        }
+    }
+
+    pub(crate) fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    pub(crate) fn observe(&self, endpoint: &str, elapsed: Duration) {
+        if elapsed < self.threshold {
+            return;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            endpoint,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Slow recipe request"
+        );
+
+        if let Some(callback) = &self.callback {
+            callback(&SlowRequest {
+                endpoint: endpoint.to_string(),
+                elapsed,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::Api, tests::get_testdata};
+    use mockito::mock;
+    use std::sync::{Arc, Mutex};
+
+    fn recording_client(threshold: Duration) -> (Api, Arc<Mutex<Vec<SlowRequest>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+
+        let client = Api::builder()
+            .slow_request_threshold(threshold)
+            .on_slow_request(move |req| sink.lock().unwrap().push(req.clone()))
+            .build()
+            .expect("Failed to build Api");
+
+        (client, seen)
+    }
+
+    #[tokio::test]
+    async fn slow_request_triggers_callback() {
+        let body = get_testdata("recipe_ids.json").expect("Failed to get testdata");
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_fn(move |w| {
+                std::thread::sleep(Duration::from_millis(100));
+                w.write_all(&body)
+            })
+            .create();
+
+        let (client, seen) = recording_client(Duration::from_millis(50));
+        client
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].endpoint, "ids");
+        assert!(seen[0].elapsed >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn fast_request_is_not_reported() {
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_ids.json").expect("Failed to get testdata"))
+            .create();
+
+        let (client, seen) = recording_client(Duration::from_secs(10));
+        client
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get recipe ids");
+
+        assert!(seen.lock().unwrap().is_empty());
+    }
+}
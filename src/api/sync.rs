@@ -0,0 +1,161 @@
+use crate::{schemas::Recipe, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io, path::Path};
+
+/// Progress of [`super::Api::sync_catalog_events`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncEvent {
+    /// Id list was fetched, `total` recipes are on the server
+    Started { total: usize },
+    /// Recipe was fetched
+    RecipeSynced { id: u32 },
+    /// Recipe is already present locally and was not fetched
+    RecipeSkipped { id: u32 },
+    /// Recipe could not be fetched, the sync continues
+    RecipeFailed { id: u32, error: String },
+    /// Last event, summarizing the sync
+    Finished { report: SyncReport },
+}
+
+/// Result of [`super::Api::sync_catalog`] and [`super::Api::sync_catalog_events`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncReport {
+    /// Recipes fetched, in server order
+    pub synced: Vec<Recipe>,
+    /// Ids already up to date locally
+    pub skipped: Vec<u32>,
+    /// Ids that failed to fetch, with the error
+    pub failed: BTreeMap<u32, String>,
+    /// Ids present locally but no longer on the server
+    pub removed: Vec<u32>,
+}
+
+/// Content hashes of the recipes present locally, keyed by id
+///
+/// Written and read by [`super::Api::sync_catalog`], next to the recipes as
+/// [`Manifest::FILENAME`]. Hashes are [`Recipe::content_hash`] values, so a
+/// manifest stays valid across processes and crate versions with unchanged
+/// schemas. The `Last-Modified` the server sent for a recipe is kept as
+/// well, to revalidate it on the next sync.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    hashes: BTreeMap<u32, u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    last_modified: BTreeMap<u32, String>,
+}
+
+impl Manifest {
+    /// File name of the manifest inside a synced directory
+    pub const FILENAME: &'static str = "manifest.json";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a manifest written by [`Manifest::save`], empty if `path` doesn't exist
+    pub fn load(path: &Path) -> Result<Manifest> {
+        Manifest::parse(path, std::fs::read(path))
+    }
+
+    /// Like [`Manifest::load`], without blocking the async executor
+    pub(crate) async fn load_async(path: &Path) -> Result<Manifest> {
+        Manifest::parse(path, tokio::fs::read(path).await)
+    }
+
+    fn parse(path: &Path, data: io::Result<Vec<u8>>) -> Result<Manifest> {
+        match data {
+            Ok(data) => Ok(serde_json::from_slice(&data)
+                .map_err(|e| format!("Corrupt manifest {}: {}", path.display(), e))?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Manifest::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the manifest to `path`, atomically replacing an existing one
+    pub fn save(&self, path: &Path) -> Result<()> {
+        crate::files::write_atomic(path, &serde_json::to_vec(self)?)
+    }
+
+    /// Hash of the locally present recipe with `id`
+    pub fn get(&self, id: u32) -> Option<u64> {
+        self.hashes.get(&id).copied()
+    }
+
+    /// Record `hash` for `id`, returns false if it was already recorded
+    pub fn insert(&mut self, id: u32, hash: u64) -> bool {
+        self.hashes.insert(id, hash) != Some(hash)
+    }
+
+    pub fn remove(&mut self, id: u32) -> Option<u64> {
+        self.last_modified.remove(&id);
+        self.hashes.remove(&id)
+    }
+
+    /// `Last-Modified` the server sent with the recipe with `id`
+    pub fn last_modified(&self, id: u32) -> Option<&str> {
+        self.last_modified.get(&id).map(String::as_str)
+    }
+
+    /// Record the `Last-Modified` of `id`, `None` if the server sent none
+    pub fn set_last_modified(&mut self, id: u32, last_modified: Option<String>) {
+        match last_modified {
+            Some(value) => self.last_modified.insert(id, value),
+            None => self.last_modified.remove(&id),
+        };
+    }
+
+    /// Recipe ids, ascending
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.hashes.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_roundtrip() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("manifest.json");
+        assert!(Manifest::load(&path).expect("Failed to load").is_empty());
+
+        let mut manifest = Manifest::new();
+        assert!(manifest.insert(25011, 1));
+        assert!(!manifest.insert(25011, 1));
+        assert!(manifest.insert(25012, u64::MAX));
+        manifest.set_last_modified(25012, Some("Wed, 21 Oct 2015 07:28:00 GMT".into()));
+        manifest.save(&path).expect("Failed to save");
+
+        assert_eq!(Manifest::load(&path).expect("Failed to load"), manifest);
+
+        manifest.remove(25012);
+        assert_eq!(manifest.last_modified(25012), None);
+    }
+
+    #[test]
+    fn manifest_without_last_modified() {
+        let manifest: Manifest =
+            serde_json::from_str(r#"{"hashes":{"25011":1}}"#).expect("Failed to parse");
+
+        assert_eq!(manifest.get(25011), Some(1));
+        assert_eq!(manifest.last_modified(25011), None);
+    }
+
+    #[test]
+    fn manifest_corrupt() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("manifest.json");
+        std::fs::write(&path, b"[1, 2").expect("Failed to write");
+
+        assert!(Manifest::load(&path).is_err());
+    }
+}
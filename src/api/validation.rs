@@ -0,0 +1,46 @@
+use crate::{error::MccError, schemas::Recipe, Result};
+use std::collections::BTreeMap;
+
+/// Result of [`super::Api::validate_catalog`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Number of recipe ids checked
+    pub checked: usize,
+    /// Recipes the server returned but the crate could not parse, with the parser error
+    pub parse_errors: BTreeMap<u32, String>,
+    /// Recipes that could not be fetched at all, e.g. due to HTTP or transport errors
+    pub fetch_errors: BTreeMap<u32, String>,
+}
+
+impl ValidationReport {
+    pub(crate) fn new(ids: &[u32], results: Vec<Result<Recipe>>) -> Self {
+        let mut report = Self {
+            checked: ids.len(),
+            ..Self::default()
+        };
+
+        for (id, result) in ids.iter().zip(results) {
+            if let Err(err) = result {
+                let cause = MccError::without_context(err.as_ref());
+                let unparseable = cause.is::<serde_json::Error>()
+                    || matches!(
+                        cause.downcast_ref::<MccError>(),
+                        Some(MccError::JsonTooDeep { .. })
+                    );
+                let errors = if unparseable {
+                    &mut report.parse_errors
+                } else {
+                    &mut report.fetch_errors
+                };
+                errors.insert(*id, err.to_string());
+            }
+        }
+
+        report
+    }
+
+    /// Whether every recipe was fetched and parsed
+    pub fn is_valid(&self) -> bool {
+        self.parse_errors.is_empty() && self.fetch_errors.is_empty()
+    }
+}
@@ -0,0 +1,100 @@
+//! Source of time for timeouts, cooldowns and retry delays
+//!
+//! [`SystemClock`] is used unless another clock is passed to
+//! [`crate::api::ApiBuilder::clock`]. [`MockClock`] only moves when told to,
+//! so time dependent behavior can be tested without sleeping.
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Future returned by [`Clock::sleep`]
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Current point in time
+    fn now(&self) -> Instant;
+
+    /// Wait until `duration` passed on this clock
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+/// Wall clock time, sleeping via tokio
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Clock standing still until advanced manually
+///
+/// Sleeping doesn't wait but advances the clock by the slept duration.
+#[derive(Debug)]
+pub struct MockClock {
+    start: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::from_secs(0)),
+        }
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().expect("Mock clock lock poisoned") += duration;
+    }
+
+    /// Total time the clock was advanced by
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().expect("Mock clock lock poisoned")
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.advance(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mock_clock_moves_on_demand() {
+        let clock = MockClock::new();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(clock.now() - start, Duration::from_secs(5));
+
+        clock.sleep(Duration::from_secs(60)).await;
+        assert_eq!(clock.now() - start, Duration::from_secs(65));
+    }
+}
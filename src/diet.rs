@@ -0,0 +1,134 @@
+//! Typed dietary and allergen tags, parsed from the localized recipe tags
+use crate::schemas::{Recipe, Tag};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DietaryTag {
+    Vegetarian,
+    Vegan,
+    GlutenFree,
+    LactoseFree,
+    NutFree,
+    LowCarb,
+    /// Tag of a dietary category not known to this crate, trimmed raw name
+    Unknown(String),
+}
+
+impl DietaryTag {
+    /// Tag categories holding dietary information
+    const CATEGORIES: &'static [&'static str] = &["diet", "nutrition", "allergen", "ernährung"];
+
+    /// Parse German or English tag names, case-insensitive, `None` if not a known diet
+    pub fn parse(s: &str) -> Option<DietaryTag> {
+        let normalized = s.trim().to_lowercase().replace(['-', '_'], " ");
+
+        let tag = match normalized.as_str() {
+            "vegetarisch" | "vegetarian" | "veggie" => DietaryTag::Vegetarian,
+            "vegan" => DietaryTag::Vegan,
+            "glutenfrei" | "gluten free" | "ohne gluten" => DietaryTag::GlutenFree,
+            "laktosefrei" | "lactose free" | "ohne laktose" => DietaryTag::LactoseFree,
+            "nussfrei" | "nut free" | "ohne nüsse" => DietaryTag::NutFree,
+            "low carb" | "kohlenhydratarm" => DietaryTag::LowCarb,
+            _ => return None,
+        };
+        Some(tag)
+    }
+
+    /// Dietary meaning of a recipe tag
+    ///
+    /// Known names are recognized in any category, other names only in
+    /// dietary categories like `diet`, ending up as [`DietaryTag::Unknown`].
+    pub fn from_tag(tag: &Tag) -> Option<DietaryTag> {
+        DietaryTag::parse(&tag.name).or_else(|| {
+            let category = tag.category.trim().to_lowercase();
+            if DietaryTag::CATEGORIES.contains(&category.as_str()) {
+                Some(DietaryTag::Unknown(tag.name.trim().to_string()))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Whether a recipe carrying this tag satisfies `diet`, vegan implies vegetarian
+    pub fn satisfies(&self, diet: &DietaryTag) -> bool {
+        self == diet || (*self == DietaryTag::Vegan && *diet == DietaryTag::Vegetarian)
+    }
+}
+
+impl fmt::Display for DietaryTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DietaryTag::Vegetarian => f.write_str("vegetarian"),
+            DietaryTag::Vegan => f.write_str("vegan"),
+            DietaryTag::GlutenFree => f.write_str("gluten-free"),
+            DietaryTag::LactoseFree => f.write_str("lactose-free"),
+            DietaryTag::NutFree => f.write_str("nut-free"),
+            DietaryTag::LowCarb => f.write_str("low carb"),
+            DietaryTag::Unknown(raw) => f.write_str(raw),
+        }
+    }
+}
+
+impl Recipe {
+    /// Dietary tags of the recipe, empty if it has none
+    pub fn dietary_tags(&self) -> Vec<DietaryTag> {
+        let mut tags = vec![];
+        for tag in self.data.tags.iter().filter_map(DietaryTag::from_tag) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// Whether any dietary tag of the recipe satisfies `diet`, see [`DietaryTag::satisfies`]
+    pub fn is_suitable_for(&self, diet: &DietaryTag) -> bool {
+        self.dietary_tags().iter().any(|tag| tag.satisfies(diet))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    fn tag(name: &str, category: &str) -> Tag {
+        Tag {
+            name: name.into(),
+            category: category.into(),
+        }
+    }
+
+    #[rstest]
+    #[case::german("Vegetarisch", "other", Some(DietaryTag::Vegetarian))]
+    #[case::english("gluten-free", "diet", Some(DietaryTag::GlutenFree))]
+    #[case::padded(" Laktosefrei ", "diet", Some(DietaryTag::LactoseFree))]
+    #[case::unknown_diet("Ohne Zucker", "diet", Some(DietaryTag::Unknown("Ohne Zucker".into())))]
+    #[case::not_a_diet("Suppe", "course", None)]
+    fn from_tag(#[case] name: &str, #[case] category: &str, #[case] expected: Option<DietaryTag>) {
+        assert_eq!(DietaryTag::from_tag(&tag(name, category)), expected);
+    }
+
+    #[rstest]
+    #[case::with_tags(
+        "recipe_single_25013_diet.json",
+        vec![DietaryTag::Vegan, DietaryTag::GlutenFree, DietaryTag::Unknown("Ohne Zucker".into())]
+    )]
+    #[case::without_tags("recipe_single_25012.json", vec![])]
+    fn dietary_tags(#[case] filename: &str, #[case] expected: Vec<DietaryTag>) {
+        let json = get_testdata(filename).expect("Failed to get testdata");
+        let recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        assert_eq!(recipe.dietary_tags(), expected);
+    }
+
+    #[test]
+    fn vegan_is_vegetarian() {
+        let json = get_testdata("recipe_single_25013_diet.json").expect("Failed to get testdata");
+        let recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        assert!(recipe.is_suitable_for(&DietaryTag::Vegetarian));
+        assert!(!recipe.is_suitable_for(&DietaryTag::LactoseFree));
+    }
+}
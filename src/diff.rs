@@ -0,0 +1,139 @@
+//! Field level differences between two versions of a recipe
+use crate::schemas::Recipe;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Single field that differs, `None` on the side the field is missing on
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// Path in the JSON form of the recipe, e.g. `data.ingredientsBases[0].ingredients[1].amount`
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Result of [`Recipe::diff`]
+///
+/// Changes are ordered by path, object keys alphabetically and list entries
+/// by position.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecipeDiff {
+    pub changes: Vec<FieldChange>,
+}
+
+impl RecipeDiff {
+    /// Whether both recipes have the same content
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Paths of all changed fields
+    pub fn paths(&self) -> Vec<&str> {
+        self.changes.iter().map(|c| c.path.as_str()).collect()
+    }
+}
+
+fn diff_values(path: String, before: Option<&Value>, after: Option<&Value>, diff: &mut RecipeDiff) {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+            for key in keys {
+                diff_values(
+                    format!("{}.{}", path, key),
+                    before.get(key),
+                    after.get(key),
+                    diff,
+                );
+            }
+        }
+        (Some(Value::Array(before)), Some(Value::Array(after))) => {
+            for idx in 0..before.len().max(after.len()) {
+                diff_values(
+                    format!("{}[{}]", path, idx),
+                    before.get(idx),
+                    after.get(idx),
+                    diff,
+                );
+            }
+        }
+        (before, after) if before != after => diff.changes.push(FieldChange {
+            path,
+            before: before.cloned(),
+            after: after.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+impl Recipe {
+    /// Fields changed from `self` to `other`
+    ///
+    /// Both are compared in their [`Recipe::normalized`] form, so cosmetic
+    /// differences don't show up. Lists are compared by position, an
+    /// inserted step shows up as change of all steps following it.
+    pub fn diff(&self, other: &Recipe) -> RecipeDiff {
+        let before =
+            serde_json::to_value(self.normalized()).expect("Recipes are always serializable");
+        let after =
+            serde_json::to_value(other.normalized()).expect("Recipes are always serializable");
+
+        let mut diff = RecipeDiff::default();
+        diff_values(String::new(), Some(&before), Some(&after), &mut diff);
+        for change in &mut diff.changes {
+            change.path = change.path.trim_start_matches('.').to_string();
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use serde_json::json;
+
+    fn recipe() -> Recipe {
+        let json = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        serde_json::from_slice(&json).expect("Failed to deserialize")
+    }
+
+    #[test]
+    fn identical() {
+        let mut other = recipe();
+        other.data.name = format!("  {}  ", other.data.name);
+
+        assert!(recipe().diff(&other).is_empty());
+    }
+
+    #[test]
+    fn field_changes() {
+        let recipe = recipe();
+        let mut other = recipe.clone();
+        other.data.name = "Moscow Mule (Beta)".into();
+        other.data.ingredients_bases[0].ingredients[1].amount = "60".into();
+        other.data.instructions.push("Kalt servieren".into());
+
+        let diff = recipe.diff(&other);
+
+        assert_eq!(
+            diff.changes,
+            vec![
+                FieldChange {
+                    path: "data.ingredientsBases[0].ingredients[1].amount".into(),
+                    before: Some(json!("100")),
+                    after: Some(json!("60")),
+                },
+                FieldChange {
+                    path: "data.instructions[1]".into(),
+                    before: None,
+                    after: Some(json!("Kalt servieren")),
+                },
+                FieldChange {
+                    path: "data.name".into(),
+                    before: Some(json!("Moscow Mule")),
+                    after: Some(json!("Moscow Mule (Beta)")),
+                },
+            ]
+        );
+    }
+}
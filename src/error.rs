@@ -0,0 +1,352 @@
+use std::{error::Error, fmt, io, time::Duration};
+
+/// Errors raised by this crate itself
+///
+/// They are returned boxed inside [`crate::Result`], use
+/// `err.downcast_ref::<MccError>()` to match on a specific variant. Failed
+/// requests are wrapped in [`MccError::Request`], unwrap them with
+/// [`MccError::without_context`] first.
+#[derive(Debug)]
+pub enum MccError {
+    /// Request failed, `source` is the actual error
+    Request {
+        context: Box<RequestContext>,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// Too many consecutive failures, requests are rejected without contacting the server
+    CircuitOpen { retry_in: Duration },
+    /// Server actively refused the connection
+    ConnectionRefused { source: reqwest::Error },
+    /// Hostname could not be resolved
+    DnsFailure { source: reqwest::Error },
+    /// Request did not complete in time
+    Timeout { source: reqwest::Error },
+    /// TLS handshake or certificate validation failed
+    Tls { source: reqwest::Error },
+    /// Response body exceeded the configured maximum size
+    BodyTooLarge { limit: u64 },
+    /// JSON response nested deeper than the configured limit
+    JsonTooDeep { limit: usize },
+    /// Server returned no recipe ids to choose from
+    EmptyCatalog,
+    /// Request failed again after the shared retry budget was used up, `source` is the last error
+    RetryBudgetExhausted {
+        source: Box<dyn Error + Send + Sync>,
+    },
+}
+
+/// What a failed request was sent for
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestContext {
+    pub method: reqwest::Method,
+    /// URL as sent, after failing over to another base host
+    pub url: reqwest::Url,
+    /// Language tag as sent, empty if no `Accept-Language` was sent
+    pub language: String,
+    /// Recipe type as sent, empty for requests other than recipes
+    pub recipe_type: String,
+}
+
+impl RequestContext {
+    /// Context of `request`, language and recipe type taken from its headers
+    pub(crate) fn of(request: &reqwest::Request) -> Self {
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        RequestContext {
+            method: request.method().clone(),
+            url: request.url().clone(),
+            language: header(reqwest::header::ACCEPT_LANGUAGE.as_str()),
+            recipe_type: header("x-recipe-type"),
+        }
+    }
+}
+
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn or_dash(value: &str) -> &str {
+            if value.is_empty() {
+                "-"
+            } else {
+                value
+            }
+        }
+        write!(
+            f,
+            "{} {} [lang={}, type={}]",
+            self.method,
+            self.url,
+            or_dash(&self.language),
+            or_dash(&self.recipe_type)
+        )
+    }
+}
+
+impl MccError {
+    /// `err` without the context of a failed request, `err` itself if it has none
+    pub fn without_context<'a>(err: &'a (dyn Error + 'static)) -> &'a (dyn Error + 'static) {
+        match err.downcast_ref::<MccError>() {
+            Some(MccError::Request { source, .. }) => source.as_ref(),
+            _ => err,
+        }
+    }
+
+    /// Request `err` was raised for, if it is a failed request
+    pub fn context<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a RequestContext> {
+        match err.downcast_ref::<MccError>() {
+            Some(MccError::Request { context, .. }) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Wrap `source` in [`MccError::Request`], unless it has context already
+    pub(crate) fn in_context(
+        context: Option<RequestContext>,
+        source: Box<dyn Error + Send + Sync>,
+    ) -> Box<dyn Error + Send + Sync> {
+        match context {
+            Some(context) if MccError::context(source.as_ref()).is_none() => MccError::Request {
+                context: Box::new(context),
+                source,
+            }
+            .into(),
+            _ => source,
+        }
+    }
+
+    /// Classify a transport error returned by reqwest
+    ///
+    /// Errors not matching any of the transport variants, e.g. HTTP status
+    /// or decoding errors, are passed through unchanged.
+    pub(crate) fn from_transport(err: reqwest::Error) -> Box<dyn Error + Send + Sync> {
+        if err.is_timeout() {
+            return MccError::Timeout { source: err }.into();
+        }
+        if !err.is_connect() {
+            return err.into();
+        }
+
+        let mut source = err.source();
+        while let Some(cause) = source {
+            if let Some(io_err) = cause.downcast_ref::<io::Error>() {
+                match io_err.kind() {
+                    io::ErrorKind::ConnectionRefused => {
+                        return MccError::ConnectionRefused { source: err }.into()
+                    }
+                    io::ErrorKind::TimedOut => return MccError::Timeout { source: err }.into(),
+                    _ => {}
+                }
+            }
+
+            // Neither hyper nor the TLS backends expose typed errors, go by the message
+            let msg = cause.to_string().to_lowercase();
+            if msg.contains("dns error") || msg.contains("lookup address") {
+                return MccError::DnsFailure { source: err }.into();
+            }
+            if msg.contains("ssl") || msg.contains("tls") || msg.contains("certificate") {
+                return MccError::Tls { source: err }.into();
+            }
+
+            source = cause.source();
+        }
+
+        err.into()
+    }
+}
+
+impl fmt::Display for MccError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MccError::Request { context, source } => write!(f, "failed {}: {}", context, source),
+            MccError::CircuitOpen { retry_in } => write!(
+                f,
+                "Circuit breaker is open, not sending request for another {:?}",
+                retry_in
+            ),
+            MccError::ConnectionRefused { source } => write!(f, "Connection refused: {}", source),
+            MccError::DnsFailure { source } => write!(f, "DNS lookup failed: {}", source),
+            MccError::Timeout { source } => write!(f, "Request timed out: {}", source),
+            MccError::Tls { source } => write!(f, "TLS error: {}", source),
+            MccError::BodyTooLarge { limit } => {
+                write!(f, "Response body exceeds limit of {} bytes", limit)
+            }
+            MccError::JsonTooDeep { limit } => {
+                write!(f, "JSON nesting exceeds depth limit of {}", limit)
+            }
+            MccError::EmptyCatalog => write!(f, "No recipes available"),
+            MccError::RetryBudgetExhausted { source } => {
+                write!(f, "Retry budget exhausted, last error: {}", source)
+            }
+        }
+    }
+}
+
+impl Error for MccError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MccError::CircuitOpen { .. }
+            | MccError::BodyTooLarge { .. }
+            | MccError::JsonTooDeep { .. }
+            | MccError::EmptyCatalog => None,
+            MccError::Request { source, .. } | MccError::RetryBudgetExhausted { source } => {
+                Some(source.as_ref())
+            }
+            MccError::ConnectionRefused { source }
+            | MccError::DnsFailure { source }
+            | MccError::Timeout { source }
+            | MccError::Tls { source } => Some(source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::mock;
+
+    async fn classify(client: reqwest::Client, url: &str) -> Box<dyn Error + Send + Sync> {
+        let err = match client.get(url).send().await {
+            Ok(response) => response
+                .bytes()
+                .await
+                .expect_err("Request unexpectedly succeeded"),
+            Err(err) => err,
+        };
+        MccError::from_transport(err)
+    }
+
+    fn variant<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a MccError> {
+        err.downcast_ref::<MccError>()
+    }
+
+    #[tokio::test]
+    async fn connection_refused() {
+        // Grab a free port, nothing listens on it once the listener is dropped
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .expect("Failed to bind")
+            .port();
+
+        let err = classify(
+            reqwest::Client::new(),
+            &format!("http://127.0.0.1:{}/", port),
+        )
+        .await;
+
+        assert!(matches!(
+            variant(err.as_ref()),
+            Some(MccError::ConnectionRefused { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn dns_failure() {
+        // .invalid is guaranteed to never resolve
+        let err = classify(reqwest::Client::new(), "http://mcc.invalid/").await;
+
+        assert!(matches!(
+            variant(err.as_ref()),
+            Some(MccError::DnsFailure { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        let _m = mock("GET", "/slow")
+            .with_status(200)
+            .with_body_from_fn(|w| {
+                std::thread::sleep(Duration::from_millis(200));
+                w.write_all(b"late")
+            })
+            .create();
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(50))
+            .build()
+            .expect("Failed to build client");
+        let err = classify(client, &format!("{}/slow", mockito::server_url())).await;
+
+        assert!(matches!(
+            variant(err.as_ref()),
+            Some(MccError::Timeout { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn tls() {
+        // Server answering the TLS handshake with plain HTTP
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+        let port = listener.local_addr().expect("Failed to get address").port();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let _ = std::io::Write::write_all(
+                    &mut stream,
+                    b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n",
+                );
+            }
+        });
+
+        let err = classify(
+            reqwest::Client::new(),
+            &format!("https://127.0.0.1:{}/", port),
+        )
+        .await;
+
+        assert!(matches!(variant(err.as_ref()), Some(MccError::Tls { .. })));
+    }
+
+    #[test]
+    fn request_context() {
+        let err: Box<dyn Error + Send + Sync> = MccError::Request {
+            context: Box::new(RequestContext {
+                method: reqwest::Method::GET,
+                url: "https://mc20.monsieur-cuisine.com/mcc/api/v1/recipe/ids"
+                    .parse()
+                    .unwrap(),
+                language: String::new(),
+                recipe_type: "live".to_string(),
+            }),
+            source: MccError::EmptyCatalog.into(),
+        }
+        .into();
+
+        assert_eq!(
+            err.to_string(),
+            "failed GET https://mc20.monsieur-cuisine.com/mcc/api/v1/recipe/ids [lang=-, type=live]: No recipes available"
+        );
+        assert!(matches!(
+            variant(MccError::without_context(err.as_ref())),
+            Some(MccError::EmptyCatalog)
+        ));
+        assert!(err.source().is_some());
+        assert!(MccError::context(MccError::without_context(err.as_ref())).is_none());
+
+        // Context already attached is kept
+        let context = MccError::context(err.as_ref()).cloned();
+        let mut other = context.clone().unwrap();
+        other.recipe_type = String::new();
+        let err = MccError::in_context(Some(other), err);
+        assert_eq!(MccError::context(err.as_ref()).cloned(), context);
+        assert!(err.to_string().contains("type=live"));
+    }
+
+    #[tokio::test]
+    async fn status_errors_pass_through() {
+        let _m = mock("GET", "/missing").with_status(404).create();
+
+        let err = reqwest::get(&format!("{}/missing", mockito::server_url()))
+            .await
+            .and_then(|r| r.error_for_status())
+            .expect_err("Request unexpectedly succeeded");
+        let err = MccError::from_transport(err);
+
+        assert!(variant(err.as_ref()).is_none());
+        assert!(err.downcast_ref::<reqwest::Error>().is_some());
+    }
+}
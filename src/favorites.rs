@@ -0,0 +1,141 @@
+//! Store of favorite recipes, exportable to move it between devices
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Set of favorite recipe ids
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Favorites {
+    ids: BTreeSet<u32>,
+}
+
+/// On-disk / on-wire representation of [`Favorites`]
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportFormat {
+    format: String,
+    version: u32,
+    recipes: Vec<u32>,
+}
+
+impl Favorites {
+    /// Identifies exported favorites, to reject unrelated JSON documents
+    pub const EXPORT_FORMAT: &'static str = "mcc-favorites";
+    /// Version of the export format written by [`Favorites::export`]
+    pub const EXPORT_VERSION: u32 = 1;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark recipe as favorite, returns false if it already was one
+    pub fn add(&mut self, id: u32) -> bool {
+        self.ids.insert(id)
+    }
+
+    /// Unmark recipe as favorite, returns false if it wasn't one
+    pub fn remove(&mut self, id: u32) -> bool {
+        self.ids.remove(&id)
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        self.ids.contains(&id)
+    }
+
+    /// Favorite recipe ids, ascending
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.ids.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Serialize into the portable, versioned JSON format
+    pub fn export(&self) -> String {
+        let export = ExportFormat {
+            format: Favorites::EXPORT_FORMAT.to_string(),
+            version: Favorites::EXPORT_VERSION,
+            recipes: self.ids().collect(),
+        };
+
+        serde_json::to_string(&export).expect("Favorites are always serializable")
+    }
+
+    /// Restore favorites previously written by [`Favorites::export`]
+    pub fn import(s: &str) -> Result<Favorites> {
+        let export: ExportFormat =
+            serde_json::from_str(s).map_err(|e| format!("Corrupt favorites data: {}", e))?;
+
+        if export.format != Favorites::EXPORT_FORMAT {
+            return Err(format!("Not a favorites export, format: {:?}", export.format).into());
+        }
+        if export.version != Favorites::EXPORT_VERSION {
+            return Err(format!(
+                "Unsupported favorites format version {}, supported: {}",
+                export.version,
+                Favorites::EXPORT_VERSION
+            )
+            .into());
+        }
+
+        Ok(Favorites {
+            ids: export.recipes.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[test]
+    fn round_trip() {
+        let mut favorites = Favorites::new();
+        favorites.add(25012);
+        favorites.add(25011);
+        assert!(!favorites.add(25011));
+
+        let imported = Favorites::import(&favorites.export()).expect("Failed to import");
+
+        assert_eq!(imported, favorites);
+        assert_eq!(imported.ids().collect::<Vec<_>>(), vec![25011, 25012]);
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        let favorites = Favorites::new();
+
+        let imported = Favorites::import(&favorites.export()).expect("Failed to import");
+
+        assert!(imported.is_empty());
+    }
+
+    #[rstest]
+    #[case::corrupt("{\"format\":\"mcc-favorites\",", "Corrupt")]
+    #[case::wrong_type(
+        "{\"format\":\"mcc-favorites\",\"version\":1,\"recipes\":[\"a\"]}",
+        "Corrupt"
+    )]
+    #[case::foreign(
+        "{\"format\":\"bookmarks\",\"version\":1,\"recipes\":[]}",
+        "Not a favorites export"
+    )]
+    #[case::future_version(
+        "{\"format\":\"mcc-favorites\",\"version\":2,\"recipes\":[1]}",
+        "version 2"
+    )]
+    fn import_rejects(#[case] data: &str, #[case] message: &str) {
+        let err = Favorites::import(data).expect_err("Import unexpectedly succeeded");
+
+        assert!(
+            err.to_string().contains(message),
+            "Unexpected error: {}",
+            err
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Crash safe file writes
+use crate::Result;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Distinguishes temporary files of concurrent writes within the process
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Whether `filename` is a temporary file left behind by an interrupted [`write_atomic`]
+pub(crate) fn is_temp_file(filename: &str) -> bool {
+    filename.starts_with('.') && filename.ends_with(".tmp")
+}
+
+/// Replace `path` with `contents`, readers see either the old or the new file
+///
+/// Writes into a temporary file next to `path`, syncs it to disk and renames
+/// it into place. The directory is synced as well, so the rename survives a
+/// crash. The temporary file is removed if anything fails. Blocks, use
+/// [`write_atomic_async`] in async code.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+/// [`write_atomic`] on the blocking thread pool, keeping the async executor free
+pub(crate) async fn write_atomic_async(path: PathBuf, contents: Vec<u8>) -> Result<()> {
+    tokio::task::spawn_blocking(move || write_atomic(&path, &contents))
+        .await
+        .map_err(|e| format!("File write task failed: {}", e))?
+}
+
+fn write_atomic_with<F>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut fs::File) -> io::Result<()>,
+{
+    let temp = temp_path(path);
+    let written = (|| {
+        let mut file = fs::File::create(&temp)?;
+        write(&mut file)?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written?;
+    Ok(sync_parent(path)?)
+}
+
+/// Sync the directory containing `path`, where a rename is recorded
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::File::open(dir)?.sync_all()
+}
+
+/// Directories can't be opened for syncing on other platforms
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .expect("Failed to list dir")
+            .map(|e| {
+                e.expect("Failed to read entry")
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn replaces_file() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("25011.json");
+
+        write_atomic(&path, b"old").expect("Failed to write");
+        write_atomic(&path, b"new").expect("Failed to write");
+
+        assert_eq!(fs::read(&path).expect("Failed to read"), b"new");
+        assert_eq!(entries(dir.path()), vec!["25011.json"]);
+    }
+
+    #[test]
+    fn temp_file_is_recognized() {
+        let temp = temp_path(Path::new("/cache/25011.json"));
+        let name = temp.file_name().unwrap().to_string_lossy();
+
+        assert!(is_temp_file(&name));
+        assert!(!is_temp_file("25011.json"));
+    }
+
+    #[test]
+    fn interrupted_write_is_invisible() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("25011.json");
+        write_atomic(&path, br#"{"complete":true}"#).expect("Failed to write");
+
+        let res = write_atomic_with(&path, |file| {
+            file.write_all(br#"{"compl"#)?;
+            // Mid-write, only the complete old file is visible under its name
+            assert_eq!(
+                fs::read(&path).expect("Failed to read"),
+                br#"{"complete":true}"#
+            );
+            Err(io::Error::other("disk full"))
+        });
+
+        assert!(res.is_err());
+        assert_eq!(
+            fs::read(&path).expect("Failed to read"),
+            br#"{"complete":true}"#
+        );
+        assert_eq!(entries(dir.path()), vec!["25011.json"]);
+    }
+
+    #[tokio::test]
+    async fn write_async() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("25011.json");
+
+        write_atomic_async(path.clone(), b"new".to_vec())
+            .await
+            .expect("Failed to write");
+
+        assert_eq!(fs::read(&path).expect("Failed to read"), b"new");
+        assert_eq!(entries(dir.path()), vec!["25011.json"]);
+    }
+
+    #[test]
+    fn relative_path() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+
+        assert!(sync_parent(&dir.path().join("x.json")).is_ok());
+        assert!(sync_parent(Path::new("x.json")).is_ok());
+    }
+
+    #[test]
+    fn missing_directory() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+
+        assert!(write_atomic(&dir.path().join("gone").join("x.json"), b"x").is_err());
+    }
+}
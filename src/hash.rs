@@ -0,0 +1,272 @@
+//! Stable content hashes for change detection
+//!
+//! Recipes are hashed over a canonical JSON serialization (object keys
+//! sorted, no whitespace) of their [`Recipe::normalized`] form using 64 bit
+//! FNV-1a, so hashes are stable across processes, platforms and crate
+//! versions with unchanged schemas.
+use crate::schemas::Recipe;
+use serde_json::Value;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(FNV_OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// FNV-1a over raw bytes, e.g. to derive stable file names
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::new();
+    hasher.write(bytes);
+    hasher.0
+}
+
+fn write_canonical(hasher: &mut Fnv1a, value: &Value) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+
+            hasher.write(b"{");
+            for (idx, (key, value)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    hasher.write(b",");
+                }
+                write_canonical(hasher, &Value::String(key.clone()));
+                hasher.write(b":");
+                write_canonical(hasher, value);
+            }
+            hasher.write(b"}");
+        }
+        Value::Array(items) => {
+            hasher.write(b"[");
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    hasher.write(b",");
+                }
+                write_canonical(hasher, item);
+            }
+            hasher.write(b"]");
+        }
+        // Scalars serialize identically regardless of map ordering
+        scalar => hasher.write(scalar.to_string().as_bytes()),
+    }
+}
+
+/// Trim and collapse runs of whitespace into a single space
+fn normalize_text(text: &mut String) {
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    *text = normalized;
+}
+
+impl Recipe {
+    /// Copy with cosmetic differences removed, for comparison and hashing
+    ///
+    /// Whitespace in text fields is trimmed and collapsed, tags and
+    /// nutrients, which have no meaningful order, are sorted. Steps,
+    /// instructions and ingredients keep their order as it is content.
+    pub fn normalized(&self) -> Recipe {
+        let mut recipe = self.clone();
+        let data = &mut recipe.data;
+
+        for text in [
+            &mut data.name,
+            &mut data.unit,
+            &mut data.yield_unit,
+            &mut data.complexity,
+        ] {
+            normalize_text(text);
+        }
+        data.instructions.iter_mut().for_each(normalize_text);
+
+        for tag in &mut data.tags {
+            normalize_text(&mut tag.name);
+            normalize_text(&mut tag.category);
+        }
+        data.tags
+            .sort_by(|a, b| (&a.category, &a.name).cmp(&(&b.category, &b.name)));
+
+        for nutrient in &mut data.nutrients {
+            normalize_text(&mut nutrient.unit);
+            normalize_text(&mut nutrient.amount);
+        }
+        data.nutrients.sort_by(|a, b| {
+            (&a.type_field, &a.unit, &a.amount).cmp(&(&b.type_field, &b.unit, &b.amount))
+        });
+
+        for base in &mut data.ingredients_bases {
+            normalize_text(&mut base.name);
+            for ingredient in &mut base.ingredients {
+                normalize_text(&mut ingredient.name);
+                normalize_text(&mut ingredient.unit);
+                normalize_text(&mut ingredient.amount);
+            }
+        }
+        for step in &mut data.guided_cooking.steps {
+            normalize_text(&mut step.text);
+        }
+
+        recipe
+    }
+
+    /// Stable hash over the semantic content of the recipe
+    ///
+    /// Independent of the field order the server sent the recipe with, and
+    /// of the cosmetic differences removed by [`Recipe::normalized`].
+    pub fn content_hash(&self) -> u64 {
+        let value =
+            serde_json::to_value(self.normalized()).expect("Recipes are always serializable");
+
+        let mut hasher = Fnv1a::new();
+        write_canonical(&mut hasher, &value);
+        hasher.0
+    }
+}
+
+/// Hash over a whole catalog as 16 digit hex string
+///
+/// Changes whenever any recipe is added, removed or modified. The order of
+/// `recipes` doesn't matter.
+pub fn catalog_hash(recipes: &[Recipe]) -> String {
+    let mut entries: Vec<(i64, u64)> = recipes
+        .iter()
+        // Hashed as 8 bytes, as before ids were u32
+        .map(|r| (i64::from(r.data.id), r.content_hash()))
+        .collect();
+    entries.sort_unstable();
+
+    let mut hasher = Fnv1a::new();
+    for (id, hash) in entries {
+        hasher.write(&id.to_le_bytes());
+        hasher.write(&hash.to_le_bytes());
+    }
+    format!("{:016x}", hasher.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    fn load(filename: &str) -> Recipe {
+        let json = get_testdata(filename).expect("Failed to get testdata");
+        serde_json::from_slice(&json).expect("Failed to deserialize")
+    }
+
+    #[fixture]
+    fn recipe() -> Recipe {
+        load("recipe_single_25011.json")
+    }
+
+    /// Re-serialize a JSON document with the keys of every object reversed
+    fn reversed_keys(value: Value) -> String {
+        fn reverse(value: Value) -> String {
+            match value {
+                Value::Object(map) => {
+                    let entries: Vec<String> = map
+                        .into_iter()
+                        .rev()
+                        .map(|(k, v)| format!("{}:{}", Value::String(k), reverse(v)))
+                        .collect();
+                    format!("{{{}}}", entries.join(","))
+                }
+                Value::Array(items) => {
+                    let items: Vec<String> = items.into_iter().map(reverse).collect();
+                    format!("[{}]", items.join(","))
+                }
+                scalar => scalar.to_string(),
+            }
+        }
+        reverse(value)
+    }
+
+    #[rstest]
+    fn identical_recipes_hash_equal(recipe: Recipe) {
+        assert_eq!(recipe.content_hash(), recipe.clone().content_hash());
+        assert_eq!(
+            recipe.content_hash(),
+            load("recipe_single_25011.json").content_hash()
+        );
+    }
+
+    #[rstest]
+    fn field_order_is_ignored(recipe: Recipe) {
+        let json = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let value: Value = serde_json::from_slice(&json).expect("Failed to parse");
+        let reordered = reversed_keys(value);
+        assert_ne!(reordered.as_bytes(), &json[..]);
+
+        let reordered: Recipe = serde_json::from_str(&reordered).expect("Failed to deserialize");
+
+        assert_eq!(reordered.content_hash(), recipe.content_hash());
+    }
+
+    #[rstest]
+    fn content_change_changes_hash(recipe: Recipe) {
+        let mut changed = recipe.clone();
+        changed.data.ingredients_bases[0].ingredients[1].amount = "120".into();
+
+        assert_ne!(changed.content_hash(), recipe.content_hash());
+    }
+
+    #[rstest]
+    fn whitespace_is_ignored(recipe: Recipe) {
+        let mut padded = recipe.clone();
+        padded.data.name = format!("  {} ", padded.data.name.replace(' ', " \t "));
+        padded.data.ingredients_bases[0].ingredients[0]
+            .name
+            .push('\n');
+        padded.data.guided_cooking.steps[0].text.insert_str(0, "  ");
+        assert_ne!(padded, recipe);
+
+        assert_eq!(padded.normalized(), recipe.normalized());
+        assert_eq!(padded.content_hash(), recipe.content_hash());
+    }
+
+    #[rstest]
+    fn unordered_collections_are_sorted(mut recipe: Recipe) {
+        recipe.data.tags.push(crate::schemas::Tag {
+            name: "Vegan".into(),
+            category: "diet".into(),
+        });
+        let mut reordered = recipe.clone();
+        reordered.data.tags.reverse();
+        reordered.data.nutrients.reverse();
+
+        assert_eq!(reordered.normalized(), recipe.normalized());
+        assert_eq!(reordered.content_hash(), recipe.content_hash());
+    }
+
+    #[rstest]
+    fn step_order_is_content(recipe: Recipe) {
+        let mut reordered = recipe.clone();
+        reordered.data.guided_cooking.steps.swap(0, 1);
+
+        assert_ne!(reordered.normalized(), recipe.normalized());
+        assert_ne!(reordered.content_hash(), recipe.content_hash());
+    }
+
+    #[test]
+    fn catalog_hash_ignores_order() {
+        let a = load("recipe_single_25011.json");
+        let b = load("recipe_single_25012.json");
+
+        let hash = catalog_hash(&[a.clone(), b.clone()]);
+
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, catalog_hash(&[b.clone(), a.clone()]));
+        assert_ne!(hash, catalog_hash(&[a]));
+    }
+}
@@ -0,0 +1,196 @@
+//! Typed recipe images, with the resolution variants the server lists
+use crate::schemas::Recipe;
+use serde::{Deserialize, Serialize};
+
+/// What an image shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageRole {
+    /// Picture of the finished dish
+    #[default]
+    Main,
+    /// Small version of the main picture
+    Thumbnail,
+    /// Picture of a single step
+    Step,
+    /// Role not known to this crate
+    #[serde(other)]
+    Other,
+}
+
+impl ImageRole {
+    /// Whether the image is a resolution of the main picture
+    fn shows_dish(&self) -> bool {
+        matches!(self, ImageRole::Main | ImageRole::Thumbnail)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub url: String,
+    /// Width in pixels, if the server tells
+    pub width: Option<u32>,
+    /// Height in pixels, if the server tells
+    pub height: Option<u32>,
+    pub role: ImageRole,
+}
+
+/// URL of image `name`, relative to `base` unless it is an absolute URL itself
+pub(crate) fn resolve(base: &str, name: &str) -> String {
+    let name = name.trim();
+    if name.starts_with("https://") || name.starts_with("http://") {
+        return name.to_string();
+    }
+    format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        name.trim_start_matches('/')
+    )
+}
+
+impl Recipe {
+    /// All images of the recipe, the one from `imageName` first
+    ///
+    /// The `imageName` one has no dimensions, unless it is listed in
+    /// [`crate::schemas::RecipeData::images`] as well. Empty for recipes
+    /// without images.
+    pub fn images(&self) -> Vec<Image> {
+        let data = &self.data;
+        let mut images: Vec<Image> = vec![];
+
+        let main = data.image_name.trim();
+        if !main.is_empty() {
+            images.push(Image {
+                url: resolve(&data.image_base, main),
+                width: None,
+                height: None,
+                role: ImageRole::Main,
+            });
+        }
+
+        for variant in &data.images {
+            let image = Image {
+                url: resolve(&data.image_base, &variant.name),
+                width: variant.width,
+                height: variant.height,
+                role: variant.role,
+            };
+            match images.iter_mut().find(|i| i.url == image.url) {
+                Some(known) => *known = image,
+                None => images.push(image),
+            }
+        }
+
+        images
+    }
+
+    /// Picture of the dish in the resolution best suited for `max_width` pixels
+    ///
+    /// Picks the widest variant fitting into `max_width`, or the smallest
+    /// one if none fits. Variants of unknown width are only picked if no
+    /// width is known at all. Step pictures are never picked.
+    pub fn best_image(&self, max_width: u32) -> Option<Image> {
+        let candidates: Vec<Image> = self
+            .images()
+            .into_iter()
+            .filter(|i| i.role.shows_dish())
+            .collect();
+
+        let fitting = candidates
+            .iter()
+            .filter(|i| matches!(i.width, Some(width) if width <= max_width))
+            .max_by_key(|i| i.width);
+        let smallest = candidates
+            .iter()
+            .filter(|i| i.width.is_some())
+            .min_by_key(|i| i.width);
+
+        fitting.or(smallest).or_else(|| candidates.first()).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    fn load(filename: &str) -> Recipe {
+        let json = get_testdata(filename).expect("Failed to get testdata");
+        serde_json::from_slice(&json).expect("Failed to deserialize")
+    }
+
+    #[test]
+    fn images() {
+        let images = load("recipe_single_25011_images.json").images();
+
+        let summary: Vec<_> = images
+            .iter()
+            .map(|i| (i.url.as_str(), i.width, i.role))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "https://example.com/Platzhalter_Madame.jpg",
+                    Some(1280),
+                    ImageRole::Main
+                ),
+                (
+                    "https://example.com/thumbs/Platzhalter_Madame.jpg",
+                    Some(320),
+                    ImageRole::Thumbnail
+                ),
+                (
+                    "https://example.com/Platzhalter_Madame_640.jpg",
+                    Some(640),
+                    ImageRole::Main
+                ),
+                (
+                    "https://cdn.example.com/steps/25011_1.jpg",
+                    Some(800),
+                    ImageRole::Step
+                ),
+                (
+                    "https://example.com/Platzhalter_Madame.gif",
+                    None,
+                    ImageRole::Other
+                ),
+            ]
+        );
+        assert_eq!(images[0].height, Some(960));
+    }
+
+    #[rstest]
+    #[case::fits(1000, Some("https://example.com/Platzhalter_Madame_640.jpg"))]
+    #[case::exact(1280, Some("https://example.com/Platzhalter_Madame.jpg"))]
+    #[case::larger(4000, Some("https://example.com/Platzhalter_Madame.jpg"))]
+    #[case::smaller_than_all(100, Some("https://example.com/thumbs/Platzhalter_Madame.jpg"))]
+    fn best_image(#[case] max_width: u32, #[case] expected: Option<&str>) {
+        let recipe = load("recipe_single_25011_images.json");
+
+        assert_eq!(
+            recipe.best_image(max_width).map(|i| i.url).as_deref(),
+            expected
+        );
+    }
+
+    #[test]
+    fn single_image() {
+        let recipe = load("recipe_single_25011.json");
+
+        assert_eq!(recipe.images().len(), 1);
+        let best = recipe.best_image(320).expect("No image");
+        assert_eq!(best.url, "https://example.com/Platzhalter_Madame.jpg");
+        assert_eq!(best.width, None);
+    }
+
+    #[test]
+    fn no_image() {
+        let mut recipe = load("recipe_single_25011.json");
+        recipe.data.image_name = " ".into();
+
+        assert!(recipe.images().is_empty());
+        assert_eq!(recipe.best_image(320), None);
+    }
+}
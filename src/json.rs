@@ -0,0 +1,112 @@
+//! JSON parsing backend for the bulk recipe responses
+//!
+//! `serde_json` is used by default. Enabling the `simd-json` feature swaps in
+//! simd-json, which uses SIMD instructions for parsing. Whether that is faster
+//! depends on payload size and CPU, measure with the `json` bench before
+//! relying on it, the README lists results. Types and results are the same
+//! with either backend.
+//!
+//! Before parsing, documents are scanned for their nesting depth, so deeply
+//! nested input is rejected without recursing into it.
+use crate::{error::MccError, Result};
+use serde::de::DeserializeOwned;
+
+mod splitter;
+
+pub(crate) use splitter::ArraySplitter;
+
+/// Fail if arrays / objects in `bytes` are nested deeper than `limit`
+///
+/// Strings are skipped, the document is not validated otherwise.
+pub(crate) fn check_depth(bytes: &[u8], limit: usize) -> Result<()> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for &c in bytes {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == b'\\' {
+                escaped = true;
+            } else if c == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match c {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > limit {
+                    return Err(MccError::JsonTooDeep { limit }.into());
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a single document with serde_json, see [`check_depth`]
+pub(crate) fn from_slice<T: DeserializeOwned>(bytes: &[u8], max_depth: usize) -> Result<T> {
+    check_depth(bytes, max_depth)?;
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+#[cfg(not(feature = "simd-json"))]
+pub(crate) fn from_bytes<T: DeserializeOwned>(bytes: Vec<u8>, max_depth: usize) -> Result<T> {
+    from_slice(&bytes, max_depth)
+}
+
+#[cfg(feature = "simd-json")]
+pub(crate) fn from_bytes<T: DeserializeOwned>(mut bytes: Vec<u8>, max_depth: usize) -> Result<T> {
+    check_depth(&bytes, max_depth)?;
+    Ok(simd_json::serde::from_slice(&mut bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schemas::Recipe, tests::get_testdata};
+    use rstest::*;
+
+    #[test]
+    fn parses_like_serde_json() {
+        let json = get_testdata("recipe_all_catalog.json").expect("Failed to get testdata");
+
+        let expected: Vec<Recipe> = serde_json::from_slice(&json).expect("Failed to deserialize");
+        let res: Vec<Recipe> = from_bytes(json, 64).expect("Failed to deserialize");
+
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(from_bytes::<Vec<Recipe>>(b"[{".to_vec(), 64).is_err());
+    }
+
+    #[rstest]
+    #[case::flat(r#"{"a":[1,2]}"#, 2, true)]
+    #[case::too_deep(r#"{"a":[[1]]}"#, 2, false)]
+    #[case::brackets_in_strings(r#"["[[[{{{", "\"]["]"#, 1, true)]
+    #[case::siblings(r#"[[],[],{}]"#, 2, true)]
+    fn depth_limit(#[case] json: &str, #[case] limit: usize, #[case] ok: bool) {
+        assert_eq!(check_depth(json.as_bytes(), limit).is_ok(), ok);
+    }
+
+    #[test]
+    fn rejects_deeply_nested_payload() {
+        let json = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+
+        let err = from_bytes::<serde_json::Value>(json.into_bytes(), 64)
+            .expect_err("Nested payload was accepted");
+        assert!(matches!(
+            err.downcast_ref::<MccError>(),
+            Some(MccError::JsonTooDeep { limit: 64 })
+        ));
+    }
+}
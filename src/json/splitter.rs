@@ -0,0 +1,237 @@
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Before the opening bracket of the array
+    Start,
+    /// Between elements
+    InArray,
+    /// Inside an element
+    InElement,
+    /// Closing bracket seen, trailing input is ignored
+    Done,
+}
+
+/// Splits a JSON array into its raw elements while the bytes arrive
+///
+/// Only the element currently being received is buffered, completed elements
+/// are handed out and dropped from the buffer. Accepts a bare array as well
+/// as an object whose first key is `"recipes"` holding the array.
+#[derive(Debug)]
+pub(crate) struct ArraySplitter {
+    buf: Vec<u8>,
+    /// Next byte of `buf` to scan
+    pos: usize,
+    state: State,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl ArraySplitter {
+    /// Give up looking for the opening bracket after this many bytes
+    const MAX_PREFIX: usize = 64;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            buf: vec![],
+            pos: 0,
+            state: State::Start,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+        }
+    }
+
+    pub(crate) fn push(&mut self, bytes: &[u8]) {
+        if self.state != State::Done {
+            self.buf.extend_from_slice(bytes);
+        }
+    }
+
+    /// Bytes currently held back, once [`ArraySplitter::next_element`] asks for
+    /// more input that is the partial element only
+    pub(crate) fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Whether the closing bracket of the array was seen
+    pub(crate) fn is_done(&self) -> bool {
+        self.state == State::Done
+    }
+
+    fn find_start(&mut self) -> Result<bool> {
+        let idx = match self.buf.iter().position(|b| *b == b'[') {
+            Some(idx) => idx,
+            None if self.buf.len() > ArraySplitter::MAX_PREFIX => {
+                return Err("Expected JSON array of recipes".into())
+            }
+            None => return Ok(false),
+        };
+
+        let prefix: String = String::from_utf8_lossy(&self.buf[..idx])
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if !prefix.is_empty() && prefix != "{\"recipes\":" {
+            return Err("Expected JSON array of recipes".into());
+        }
+
+        self.buf.drain(..=idx);
+        self.pos = 0;
+        self.state = State::InArray;
+        Ok(true)
+    }
+
+    /// Next complete element, `None` if more input is needed or the array ended
+    pub(crate) fn next_element(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.state == State::Start && !self.find_start()? {
+            return Ok(None);
+        }
+
+        while self.pos < self.buf.len() {
+            let c = self.buf[self.pos];
+
+            match self.state {
+                State::Start => unreachable!("Array start is found beforehand"),
+                State::Done => return Ok(None),
+                State::InArray => match c {
+                    b']' => {
+                        self.state = State::Done;
+                        self.buf = vec![];
+                        self.pos = 0;
+                        return Ok(None);
+                    }
+                    b',' => self.pos += 1,
+                    c if c.is_ascii_whitespace() => self.pos += 1,
+                    _ => {
+                        // Drop separators, the element starts at the beginning of the buffer
+                        self.buf.drain(..self.pos);
+                        self.pos = 0;
+                        self.state = State::InElement;
+                    }
+                },
+                State::InElement => {
+                    let mut end = None;
+
+                    if self.in_string {
+                        if self.escaped {
+                            self.escaped = false;
+                        } else if c == b'\\' {
+                            self.escaped = true;
+                        } else if c == b'"' {
+                            self.in_string = false;
+                        }
+                    } else {
+                        match c {
+                            b'"' => self.in_string = true,
+                            b'{' | b'[' => self.depth += 1,
+                            b'}' | b']' if self.depth > 0 => {
+                                self.depth -= 1;
+                                if self.depth == 0 {
+                                    end = Some(self.pos + 1);
+                                }
+                            }
+                            // End of a scalar element
+                            b',' | b']' if self.depth == 0 => end = Some(self.pos),
+                            c if c.is_ascii_whitespace() && self.depth == 0 => end = Some(self.pos),
+                            _ => {}
+                        }
+                    }
+
+                    match end {
+                        Some(end) => {
+                            let element: Vec<u8> = self.buf.drain(..end).collect();
+                            self.pos = 0;
+                            self.state = State::InArray;
+                            return Ok(Some(element));
+                        }
+                        None => self.pos += 1,
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{schemas::Recipe, tests::get_testdata};
+    use rstest::*;
+
+    fn split(input: &[u8], chunk_size: usize) -> Result<Vec<Vec<u8>>> {
+        let mut splitter = ArraySplitter::new();
+        let mut elements = vec![];
+        for chunk in input.chunks(chunk_size) {
+            splitter.push(chunk);
+            while let Some(element) = splitter.next_element()? {
+                elements.push(element);
+            }
+        }
+        if !splitter.is_done() {
+            return Err("Array not terminated".into());
+        }
+        Ok(elements)
+    }
+
+    #[rstest]
+    #[case::objects(br#"[{"a":1}, {"b":"]}"}]"#, vec![r#"{"a":1}"#, r#"{"b":"]}"}"#])]
+    #[case::escapes(br#"[ "x\"]", "y" ]"#, vec![r#""x\"]""#, r#""y""#])]
+    #[case::scalars(b"[1,2 , 3]", vec!["1", "2", "3"])]
+    #[case::nested(br#"[[1,[2]],{"c":[{}],"d":2}]"#, vec!["[1,[2]]", r#"{"c":[{}],"d":2}"#])]
+    #[case::wrapped(br#" { "recipes" : [{"a":1}]}"#, vec![r#"{"a":1}"#])]
+    #[case::empty(b"[]", vec![])]
+    fn splits_elements(#[case] input: &[u8], #[case] expected: Vec<&str>) {
+        for chunk_size in &[1, 3, input.len()] {
+            let elements = split(input, *chunk_size).expect("Failed to split");
+            let elements: Vec<String> = elements
+                .into_iter()
+                .map(|e| String::from_utf8(e).expect("Invalid UTF-8"))
+                .collect();
+            assert_eq!(elements, expected, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[rstest]
+    #[case::object(br#"{"data":{}}"#)]
+    #[case::other_key(br#"{"items":[]}"#)]
+    fn rejects_non_array(#[case] input: &[u8]) {
+        assert!(split(input, 4).is_err());
+    }
+
+    #[test]
+    fn memory_stays_bounded() {
+        let json = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+        let recipes: Vec<Recipe> = (0..200)
+            .map(|i| {
+                let mut r = recipe.clone();
+                r.data.id = i;
+                r
+            })
+            .collect();
+        let input = serde_json::to_vec(&recipes).expect("Failed to serialize");
+        let element_len = serde_json::to_vec(&recipe)
+            .expect("Failed to serialize")
+            .len();
+        let chunk_size = 512;
+
+        let mut splitter = ArraySplitter::new();
+        let mut ids = vec![];
+        for chunk in input.chunks(chunk_size) {
+            splitter.push(chunk);
+            while let Some(element) = splitter.next_element().expect("Failed to split") {
+                let r: Recipe = serde_json::from_slice(&element).expect("Failed to deserialize");
+                ids.push(r.data.id);
+            }
+            // At most one partial element plus the chunk not yet scanned
+            assert!(splitter.buffered() <= element_len + chunk_size);
+        }
+
+        assert!(input.len() > 100 * (element_len + chunk_size));
+        assert_eq!(ids, (0..200).collect::<Vec<_>>());
+    }
+}
@@ -1,7 +1,31 @@
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
 pub mod api;
+pub mod clock;
+pub mod diet;
+pub mod diff;
+pub mod error;
+pub mod favorites;
+mod files;
+pub mod hash;
+pub mod images;
+mod json;
+pub mod locale;
+pub mod nutrition;
+pub mod plural;
+pub mod redact;
+pub mod render;
 pub mod schemas;
+pub mod search;
+pub mod shopping;
+pub mod sort;
+pub mod stats;
+pub mod temperature;
+pub mod text;
+pub mod transport;
+pub mod units;
+
+pub use error::MccError;
 
 // Re-export serde
 pub use serde;
@@ -11,6 +35,26 @@ pub use serde_json;
 mod tests {
     use super::Result;
     use std::path::PathBuf;
+    #[cfg(feature = "tracing")]
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+
+    #[cfg(feature = "tracing")]
+    struct Fields<'a>(&'a mut BTreeMap<String, String>);
+
+    #[cfg(feature = "tracing")]
+    impl tracing::field::Visit for Fields<'_> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
 
     pub fn get_testdata(filename: &str) -> Result<Vec<u8>> {
         let mut filepath = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -18,4 +62,71 @@ mod tests {
         filepath.push(filename);
         Ok(std::fs::read(filepath)?)
     }
+
+    /// Fields of every tracing event emitted on this thread while running `f`
+    #[cfg(feature = "tracing")]
+    pub fn capture_events<F: FnOnce()>(f: F) -> Vec<BTreeMap<String, String>> {
+        use tracing::{span, Event, Metadata};
+
+        struct Capture(Arc<Mutex<Vec<BTreeMap<String, String>>>>);
+
+        impl tracing::Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = BTreeMap::new();
+                event.record(&mut Fields(&mut fields));
+                self.0.lock().unwrap().push(fields);
+            }
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let events = Arc::new(Mutex::new(vec![]));
+        tracing::subscriber::with_default(Capture(events.clone()), f);
+        let events = events.lock().unwrap().clone();
+        events
+    }
+
+    /// Fields of every span created on this thread while running `f`, with
+    /// values recorded later, and the span name as `name`
+    #[cfg(feature = "opentelemetry")]
+    pub fn capture_spans<F: FnOnce()>(f: F) -> Vec<BTreeMap<String, String>> {
+        use tracing::{span, Event, Metadata};
+
+        struct Capture(Arc<Mutex<Vec<BTreeMap<String, String>>>>);
+
+        impl tracing::Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+                let mut fields = BTreeMap::new();
+                fields.insert("name".to_string(), attrs.metadata().name().to_string());
+                attrs.record(&mut Fields(&mut fields));
+                let mut spans = self.0.lock().unwrap();
+                spans.push(fields);
+                span::Id::from_u64(spans.len() as u64)
+            }
+            fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+                let mut spans = self.0.lock().unwrap();
+                values.record(&mut Fields(&mut spans[id.into_u64() as usize - 1]));
+            }
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, _: &Event<'_>) {}
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let spans = Arc::new(Mutex::new(vec![]));
+        tracing::subscriber::with_default(Capture(spans.clone()), f);
+        let spans = spans.lock().unwrap().clone();
+        spans
+    }
 }
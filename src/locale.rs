@@ -0,0 +1,145 @@
+//! Language of the user's environment, for tools without a language setting,
+//! and number formats of the recipe languages
+use crate::api::Api;
+
+/// Variables consulted in order, the first one set decides like for POSIX locales
+const LOCALE_ENV_VARS: &[&str] = &["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// ISO 639-1 code of the system locale, if the API offers recipes in it
+///
+/// Reads `LC_ALL`, `LC_MESSAGES` and `LANG`, the first non-empty one wins,
+/// e.g. `de_DE.UTF-8` gives `de`. `None` for the `C` / `POSIX` locales,
+/// languages not in [`Api::SUPPORTED_LANGUAGES`], or if none is set, as
+/// is usually the case on Windows.
+pub fn system_language() -> Option<String> {
+    language_from_env(|name| std::env::var(name).ok())
+}
+
+fn language_from_env<F: Fn(&str) -> Option<String>>(lookup: F) -> Option<String> {
+    let locale = LOCALE_ENV_VARS
+        .iter()
+        .filter_map(|name| lookup(name))
+        .find(|value| !value.trim().is_empty())?;
+
+    // Strip codeset and modifier, e.g. `de_AT.UTF-8@euro`
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let language = Api::normalize_language(locale);
+
+    if Api::SUPPORTED_LANGUAGES.contains(&language.as_str()) {
+        Some(language)
+    } else {
+        None
+    }
+}
+
+/// Decimal and digit grouping separators numbers are written with in a language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal: char,
+    pub grouping: char,
+}
+
+impl NumberFormat {
+    /// Decimal point, e.g. "1,500.5"
+    pub const POINT: NumberFormat = NumberFormat {
+        decimal: '.',
+        grouping: ',',
+    };
+
+    /// Decimal comma, e.g. "1.500,5"
+    pub const COMMA: NumberFormat = NumberFormat {
+        decimal: ',',
+        grouping: '.',
+    };
+
+    /// Format of recipes in `language`, the decimal comma unless English
+    pub fn for_language(language: &str) -> NumberFormat {
+        match Api::normalize_language(language).as_str() {
+            "en" => NumberFormat::POINT,
+            _ => NumberFormat::COMMA,
+        }
+    }
+
+    /// Parse a single number written in this format
+    ///
+    /// The grouping separator only counts as such between groups of three
+    /// digits, "1.500" is 1500 with a decimal comma. Otherwise, and if there
+    /// is no decimal separator, it is taken as decimal separator, as recipes
+    /// don't always follow their language: "0.5" is 0.5 either way.
+    pub fn parse(&self, s: &str) -> Option<f64> {
+        let s = s.trim();
+        let (integer, fraction) = match s.split_once(self.decimal) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (s, None),
+        };
+
+        let integer = if is_grouped(integer, self.grouping) {
+            integer.replace(self.grouping, "")
+        } else if fraction.is_none() {
+            integer.replacen(self.grouping, ".", 1)
+        } else {
+            integer.to_string()
+        };
+        let number = match fraction {
+            Some(fraction) => format!("{}.{}", integer, fraction),
+            None => integer,
+        };
+
+        number.parse::<f64>().ok().filter(|v| v.is_finite())
+    }
+}
+
+/// Whether `s` is an integer with digits grouped by `separator`, like "1.234.567"
+fn is_grouped(s: &str, separator: char) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    let mut groups = digits.split(separator);
+    let first = groups.next().unwrap_or_default();
+    let all_digits = |group: &str| group.chars().all(|c| c.is_ascii_digit());
+
+    digits.contains(separator)
+        && (1..=3).contains(&first.len())
+        && all_digits(first)
+        && groups.all(|group| group.len() == 3 && all_digits(group))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NumberFormat;
+    use rstest::*;
+    use std::collections::HashMap;
+
+    #[rstest]
+    #[case::german(&[("LANG", "de_DE.UTF-8")], Some("de"))]
+    #[case::modifier(&[("LANG", "de_AT@euro")], Some("de"))]
+    #[case::language_only(&[("LANG", "fr")], Some("fr"))]
+    #[case::lc_all_wins(&[("LC_ALL", "pl_PL.UTF-8"), ("LANG", "de_DE.UTF-8")], Some("pl"))]
+    #[case::lc_messages(&[("LC_MESSAGES", "it_IT"), ("LANG", "de_DE")], Some("it"))]
+    #[case::empty_skipped(&[("LC_ALL", ""), ("LANG", "es_ES.UTF-8")], Some("es"))]
+    #[case::unsupported(&[("LANG", "ja_JP.UTF-8")], None)]
+    #[case::unsupported_wins(&[("LC_ALL", "ja_JP.UTF-8"), ("LANG", "de_DE.UTF-8")], None)]
+    #[case::posix(&[("LANG", "C.UTF-8")], None)]
+    #[case::unset(&[], None)]
+    fn language_from_env(#[case] vars: &[(&str, &str)], #[case] expected: Option<&str>) {
+        let env: HashMap<&str, &str> = vars.iter().copied().collect();
+
+        let language = super::language_from_env(|name| env.get(name).map(|v| v.to_string()));
+
+        assert_eq!(language.as_deref(), expected);
+    }
+
+    #[rstest]
+    #[case::integer("de", "100", Some(100.0))]
+    #[case::decimal_comma("de", "0,75", Some(0.75))]
+    #[case::grouped_de("de", "1.500", Some(1500.0))]
+    #[case::grouped_decimal_de("de", "1.234,5", Some(1234.5))]
+    #[case::point_in_de("de", "0.5", Some(0.5))]
+    #[case::grouped_en("en", "1,500", Some(1500.0))]
+    #[case::grouped_decimal_en("en", "1,234.5", Some(1234.5))]
+    #[case::comma_in_en("en", "0,75", Some(0.75))]
+    #[case::negative("fr", "-1.000", Some(-1000.0))]
+    #[case::bad_grouping("de", "1.23,5", None)]
+    #[case::text("de", "etwas", None)]
+    fn parse_number(#[case] language: &str, #[case] s: &str, #[case] expected: Option<f64>) {
+        assert_eq!(NumberFormat::for_language(language).parse(s), expected);
+    }
+}
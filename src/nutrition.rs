@@ -0,0 +1,193 @@
+//! Typed nutrition values, totals and per serving
+//!
+//! The server lists nutrients as free-text amounts together with the
+//! quantity they refer to in [`crate::schemas::RecipeData::unit`], e.g.
+//! "Pro Portion". Values referring to a portion are multiplied by the yield
+//! to get recipe totals, anything else is taken as total of the recipe.
+use crate::{
+    locale::NumberFormat,
+    schemas::{parse_amount, NutrientType, Recipe},
+};
+
+/// Nutrition values, `None` where the recipe lists no parseable amount
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Nutrition {
+    /// Energy in kJ
+    pub joules: Option<f64>,
+    /// Energy in kcal
+    pub calories: Option<f64>,
+    /// Grams of protein
+    pub protein: Option<f64>,
+    /// Grams of carbohydrate
+    pub carbohydrate: Option<f64>,
+    /// Grams of fat
+    pub fat: Option<f64>,
+}
+
+impl Nutrition {
+    /// Every value multiplied by `factor`
+    pub fn scale(&self, factor: f64) -> Nutrition {
+        let scale = |value: Option<f64>| value.map(|v| v * factor);
+        Nutrition {
+            joules: scale(self.joules),
+            calories: scale(self.calories),
+            protein: scale(self.protein),
+            carbohydrate: scale(self.carbohydrate),
+            fat: scale(self.fat),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Nutrition::default()
+    }
+}
+
+/// Fragments of [`crate::schemas::RecipeData::unit`] marking per portion values
+const PER_PORTION_MARKERS: &[&str] = &["portion", "porzione", "porci", "serving", "person"];
+
+impl Recipe {
+    /// Whether the listed nutrients refer to a single portion
+    fn nutrients_per_portion(&self) -> bool {
+        let unit = self.data.unit.to_lowercase();
+        PER_PORTION_MARKERS
+            .iter()
+            .any(|marker| unit.contains(marker))
+    }
+
+    /// Number of servings, `None` if the recipe has no positive yield
+    fn servings(&self) -> Option<f64> {
+        Some(self.data.yield_field as f64).filter(|servings| *servings > 0.0)
+    }
+
+    /// Nutrients as listed by the server, `None` if there are none
+    fn listed_nutrition(&self) -> Option<Nutrition> {
+        let mut nutrition = Nutrition::default();
+        let format = NumberFormat::for_language(&self.data.language);
+        for nutrient in &self.data.nutrients {
+            let value = parse_amount(&nutrient.amount, Some(format));
+            let slot = match nutrient.type_field {
+                NutrientType::Joules => &mut nutrition.joules,
+                NutrientType::Calories => &mut nutrition.calories,
+                NutrientType::Protein => &mut nutrition.protein,
+                NutrientType::Carbohydrate => &mut nutrition.carbohydrate,
+                NutrientType::Fat => &mut nutrition.fat,
+            };
+            *slot = slot.or(value);
+        }
+
+        Some(nutrition).filter(|n| !n.is_empty())
+    }
+
+    /// Nutrition of the whole recipe
+    ///
+    /// `None` if the recipe lists no nutrients, or lists them per portion
+    /// without a positive yield.
+    pub fn nutrition(&self) -> Option<Nutrition> {
+        let listed = self.listed_nutrition()?;
+        if self.nutrients_per_portion() {
+            return Some(listed.scale(self.servings()?));
+        }
+        Some(listed)
+    }
+
+    /// Nutrition of a single serving, the total divided by the yield
+    ///
+    /// `None` without nutrients or without a positive yield.
+    pub fn nutrition_per_serving(&self) -> Option<Nutrition> {
+        let servings = self.servings()?;
+        Some(self.nutrition()?.scale(1.0 / servings))
+    }
+
+    /// Nutrition of `servings` servings, e.g. to display a scaled recipe
+    pub fn nutrition_scaled(&self, servings: f64) -> Option<Nutrition> {
+        Some(self.nutrition_per_serving()?.scale(servings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    /// 6 portions, 350 kcal / 11 g protein / 22 g carbs / 25 g fat per portion
+    #[fixture]
+    fn recipe() -> Recipe {
+        let json = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        serde_json::from_slice(&json).expect("Failed to deserialize")
+    }
+
+    #[rstest]
+    fn listed_per_portion(recipe: Recipe) {
+        let per_serving = recipe.nutrition_per_serving().expect("No nutrition");
+        assert_eq!(per_serving.joules, Some(1465.0));
+        assert_eq!(per_serving.calories, Some(350.0));
+        assert_eq!(per_serving.fat, Some(25.0));
+
+        let total = recipe.nutrition().expect("No nutrition");
+        assert_eq!(total.calories, Some(2100.0));
+        assert_eq!(total.protein, Some(66.0));
+    }
+
+    #[test]
+    fn grouped_numbers() {
+        let json = get_testdata("recipe_single_25012_grouped_numbers.json")
+            .expect("Failed to get testdata");
+        let recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        let per_serving = recipe.nutrition_per_serving().expect("No nutrition");
+        assert_eq!(per_serving.joules, Some(1465.5));
+        assert_eq!(per_serving.calories, Some(350.0));
+    }
+
+    #[rstest]
+    fn listed_total(mut recipe: Recipe) {
+        recipe.data.unit = "Gesamt".into();
+
+        let per_serving = recipe.nutrition_per_serving().expect("No nutrition");
+        assert_eq!(per_serving.carbohydrate, Some(22.0 / 6.0));
+        assert_eq!(per_serving.protein, Some(11.0 / 6.0));
+        assert_eq!(
+            recipe.nutrition().expect("No nutrition").protein,
+            Some(11.0)
+        );
+    }
+
+    #[rstest]
+    fn scaled(recipe: Recipe) {
+        let scaled = recipe.nutrition_scaled(2.0).expect("No nutrition");
+
+        assert_eq!(scaled.calories, Some(700.0));
+        assert_eq!(scaled.carbohydrate, Some(44.0));
+    }
+
+    #[rstest]
+    fn unparseable_amount(mut recipe: Recipe) {
+        recipe.data.nutrients[1].amount = "k.A.".into();
+
+        let per_serving = recipe.nutrition_per_serving().expect("No nutrition");
+        assert_eq!(per_serving.calories, None);
+        assert_eq!(per_serving.joules, Some(1465.0));
+    }
+
+    #[rstest]
+    fn no_nutrients(mut recipe: Recipe) {
+        recipe.data.nutrients.clear();
+
+        assert_eq!(recipe.nutrition(), None);
+        assert_eq!(recipe.nutrition_per_serving(), None);
+        assert_eq!(recipe.nutrition_scaled(4.0), None);
+    }
+
+    #[rstest]
+    fn no_servings(mut recipe: Recipe) {
+        recipe.data.yield_field = 0;
+
+        assert_eq!(recipe.nutrition(), None);
+        assert_eq!(recipe.nutrition_per_serving(), None);
+
+        recipe.data.unit = "Gesamt".into();
+        assert!(recipe.nutrition().is_some());
+        assert_eq!(recipe.nutrition_per_serving(), None);
+    }
+}
@@ -0,0 +1,182 @@
+//! Plural forms of ingredient names and units for display
+//!
+//! Only words in the lookup tables are pluralized, everything else keeps the
+//! form the server sent. Amounts of exactly 1 are singular, all others
+//! (including fractions like "1 1/2") plural.
+use crate::{api::Api, schemas::Ingredient, units::Unit};
+
+/// Singular and plural, lowercase
+const GERMAN: &[(&str, &str)] = &[
+    ("apfel", "äpfel"),
+    ("banane", "bananen"),
+    ("birne", "birnen"),
+    ("dose", "dosen"),
+    ("ei", "eier"),
+    ("gurke", "gurken"),
+    ("karotte", "karotten"),
+    ("kartoffel", "kartoffeln"),
+    ("knoblauchzehe", "knoblauchzehen"),
+    ("limette", "limetten"),
+    ("möhre", "möhren"),
+    ("packung", "packungen"),
+    ("paprikaschote", "paprikaschoten"),
+    ("prise", "prisen"),
+    ("scheibe", "scheiben"),
+    ("schote", "schoten"),
+    ("tomate", "tomaten"),
+    ("zehe", "zehen"),
+    ("zitrone", "zitronen"),
+    ("zwiebel", "zwiebeln"),
+];
+
+/// Singular and plural, lowercase
+const ENGLISH: &[(&str, &str)] = &[
+    ("apple", "apples"),
+    ("can", "cans"),
+    ("carrot", "carrots"),
+    ("clove", "cloves"),
+    ("cup", "cups"),
+    ("egg", "eggs"),
+    ("half", "halves"),
+    ("leaf", "leaves"),
+    ("lemon", "lemons"),
+    ("lime", "limes"),
+    ("loaf", "loaves"),
+    ("onion", "onions"),
+    ("package", "packages"),
+    ("pinch", "pinches"),
+    ("potato", "potatoes"),
+    ("slice", "slices"),
+    ("tablespoon", "tablespoons"),
+    ("teaspoon", "teaspoons"),
+    ("tomato", "tomatoes"),
+];
+
+fn table(language: &str) -> &'static [(&'static str, &'static str)] {
+    match Api::normalize_language(language).as_str() {
+        "de" => GERMAN,
+        "en" => ENGLISH,
+        _ => &[],
+    }
+}
+
+/// Plural of a single word from `table`, keeping a leading capital
+fn plural_word(word: &str, table: &[(&str, &str)]) -> Option<String> {
+    let lower = word.to_lowercase();
+    let (_, plural) = table.iter().find(|(singular, _)| *singular == lower)?;
+
+    let mut chars = plural.chars();
+    let first = chars.next()?;
+    if word.starts_with(char::is_uppercase) {
+        Some(first.to_uppercase().chain(chars).collect())
+    } else {
+        Some(plural.to_string())
+    }
+}
+
+/// `word` in the number matching `count`, for `language` like `de` or `en-GB`
+///
+/// Phrases are looked up as a whole, then by their last word, so "red onion"
+/// becomes "red onions". Words not in the table are returned unchanged.
+/// Fractions up to one count as singular, "1/2 Limette".
+pub fn pluralize(word: &str, count: f64, language: &str) -> String {
+    if count > 0.0 && count <= 1.0 {
+        return word.to_string();
+    }
+
+    let table = table(language);
+    if let Some(plural) = plural_word(word.trim(), table) {
+        return plural;
+    }
+    match word.trim().rsplit_once(' ') {
+        Some((head, last)) => match plural_word(last, table) {
+            Some(plural) => format!("{} {}", head, plural),
+            None => word.to_string(),
+        },
+        None => word.to_string(),
+    }
+}
+
+impl Ingredient {
+    /// Amount, unit and name for display, pluralized for `language`
+    ///
+    /// The unit is pluralized if it is a word like "Prise", abbreviations
+    /// stay as they are. The name only follows the amount if it is counted
+    /// in pieces, "2 Eier" but "200 g Mehl". Unquantified amounts like
+    /// "etwas" are shown as sent.
+    pub fn format_ingredient(&self, language: &str) -> String {
+        let (unit, name) = match self.quantity_in(language) {
+            Some(count) => {
+                let counted =
+                    matches!(Unit::parse(&self.unit), Unit::Piece) || self.unit.trim().is_empty();
+                let name = if counted {
+                    pluralize(&self.name, count, language)
+                } else {
+                    self.name.clone()
+                };
+                (pluralize(&self.unit, count, language), name)
+            }
+            None => (self.unit.clone(), self.name.clone()),
+        };
+
+        [self.amount.as_str(), &unit, &name]
+            .iter()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::singular("Ei", 1.0, "de", "Ei")]
+    #[case::german("Ei", 2.0, "de", "Eier")]
+    #[case::umlaut("Apfel", 3.0, "de", "Äpfel")]
+    #[case::fraction("Limette", 1.5, "de", "Limetten")]
+    #[case::half("Limette", 0.5, "de", "Limette")]
+    #[case::none("Ei", 0.0, "de", "Eier")]
+    #[case::region("Zwiebel", 2.0, "de-AT", "Zwiebeln")]
+    #[case::english("potato", 2.0, "en", "potatoes")]
+    #[case::capital("Egg", 2.0, "en-GB", "Eggs")]
+    #[case::phrase("red onion", 2.0, "en", "red onions")]
+    #[case::unknown("Vodka", 2.0, "de", "Vodka")]
+    #[case::already_plural("Kartoffeln", 2.0, "de", "Kartoffeln")]
+    #[case::other_language("oeuf", 2.0, "fr", "oeuf")]
+    fn pluralize(
+        #[case] word: &str,
+        #[case] count: f64,
+        #[case] language: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(super::pluralize(word, count, language), expected);
+    }
+
+    #[rstest]
+    #[case::piece("2", "Stk", "Zwiebel", "2 Stk Zwiebeln")]
+    #[case::no_unit("2", "", "Ei", "2 Eier")]
+    #[case::one("1", "", "Ei", "1 Ei")]
+    #[case::unit_word("2", "Prise", "Salz", "2 Prisen Salz")]
+    #[case::measured("200", "g", "Kartoffel", "200 g Kartoffel")]
+    #[case::mixed_fraction("1 1/2", "Stk", "Limette", "1 1/2 Stk Limetten")]
+    #[case::half("1/2", "Stk", "Limette", "1/2 Stk Limette")]
+    #[case::unquantified("etwas", "", "Salz", "etwas Salz")]
+    fn format_ingredient(
+        #[case] amount: &str,
+        #[case] unit: &str,
+        #[case] name: &str,
+        #[case] expected: &str,
+    ) {
+        let ingredient = Ingredient {
+            name: name.to_string(),
+            unit: unit.to_string(),
+            amount: amount.to_string(),
+        };
+
+        assert_eq!(ingredient.format_ingredient("de"), expected);
+    }
+}
@@ -0,0 +1,217 @@
+//! Masking of sensitive data in requests and responses written for debugging
+//!
+//! Applied to response dumps ([`crate::api::ApiBuilder::dump_responses_to`])
+//! and recorded cassettes (`transport::vcr::RecordingTransport`).
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// Headers, query parameters and JSON fields replaced by [`Redaction::PLACEHOLDER`]
+///
+/// Header and query parameter names are matched case-insensitively. JSON fields are given as
+/// dot separated path of object keys, e.g. `data.uid`. Arrays on the way
+/// are descended into, so `data.tags.name` masks the name of every tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redaction {
+    headers: Vec<String>,
+    query_params: Vec<String>,
+    json_fields: Vec<Vec<String>>,
+}
+
+impl Redaction {
+    /// Value written instead of redacted data
+    pub const PLACEHOLDER: &'static str = "[redacted]";
+
+    /// Headers redacted by default, on top of those added with [`Redaction::header`]
+    pub const DEFAULT_HEADERS: &'static [&'static str] = &[
+        "authorization",
+        "proxy-authorization",
+        "cookie",
+        "set-cookie",
+    ];
+
+    /// Query parameters redacted by default, on top of those added with [`Redaction::query_param`]
+    pub const DEFAULT_QUERY_PARAMS: &'static [&'static str] = &["access_token", "api_key", "token"];
+
+    /// Redact [`Redaction::DEFAULT_HEADERS`] and [`Redaction::DEFAULT_QUERY_PARAMS`] only
+    pub fn new() -> Self {
+        Self {
+            headers: Redaction::DEFAULT_HEADERS
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+            query_params: Redaction::DEFAULT_QUERY_PARAMS
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            json_fields: vec![],
+        }
+    }
+
+    /// Redact header `name` as well
+    pub fn header(mut self, name: &str) -> Self {
+        self.headers.push(name.to_ascii_lowercase());
+        self
+    }
+
+    /// Redact the value of query parameter `name` in URLs as well
+    pub fn query_param(mut self, name: &str) -> Self {
+        self.query_params.push(name.to_ascii_lowercase());
+        self
+    }
+
+    /// Redact the JSON field at dot separated `path` in response bodies
+    pub fn json_field(mut self, path: &str) -> Self {
+        self.json_fields
+            .push(path.split('.').map(String::from).collect());
+        self
+    }
+
+    pub fn is_redacted_header(&self, name: &str) -> bool {
+        self.headers.iter().any(|h| h.eq_ignore_ascii_case(name))
+    }
+
+    pub fn is_redacted_query_param(&self, name: &str) -> bool {
+        self.query_params
+            .iter()
+            .any(|p| p.eq_ignore_ascii_case(name))
+    }
+
+    /// `url` with the values of the configured query parameters masked
+    pub fn redact_url(&self, url: &reqwest::Url) -> reqwest::Url {
+        let mut url = url.clone();
+        if !url
+            .query_pairs()
+            .any(|(name, _)| self.is_redacted_query_param(&name))
+        {
+            return url;
+        }
+
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if self.is_redacted_query_param(&name) {
+                    Redaction::PLACEHOLDER.into()
+                } else {
+                    value
+                };
+                (name.into_owned(), value.into_owned())
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+        url
+    }
+
+    /// `body` with the configured JSON fields masked
+    ///
+    /// Bodies are returned unchanged if no field is configured, they are not
+    /// JSON, or contain none of the fields. Otherwise they are written anew.
+    pub fn redact_body<'a>(&self, body: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.json_fields.is_empty() {
+            return Cow::Borrowed(body);
+        }
+        let mut value: Value = match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(_) => return Cow::Borrowed(body),
+        };
+
+        let mut redacted = false;
+        for path in &self.json_fields {
+            redacted |= redact_path(&mut value, path);
+        }
+        if !redacted {
+            return Cow::Borrowed(body);
+        }
+        Cow::Owned(serde_json::to_vec(&value).expect("JSON values are serializable"))
+    }
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Mask the field at `path` below `value`, whether there was one
+fn redact_path(value: &mut Value, path: &[String]) -> bool {
+    match (value, path) {
+        (Value::Array(items), path) => items
+            .iter_mut()
+            .fold(false, |found, item| redact_path(item, path) | found),
+        (Value::Object(map), [key]) => match map.get_mut(key) {
+            Some(field) => {
+                *field = Value::String(Redaction::PLACEHOLDER.to_string());
+                true
+            }
+            None => false,
+        },
+        (Value::Object(map), [key, rest @ ..]) => match map.get_mut(key) {
+            Some(field) => redact_path(field, rest),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers() {
+        let redaction = Redaction::new().header("X-User-Id");
+
+        assert!(redaction.is_redacted_header("x-user-id"));
+        assert!(redaction.is_redacted_header("Set-Cookie"));
+        assert!(!redaction.is_redacted_header("content-type"));
+    }
+
+    #[test]
+    fn urls() {
+        let redaction = Redaction::new().query_param("Session");
+        let url: reqwest::Url = "https://mcc.example/recipe/1?session=s-1&lang=de&token=t-1"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            redaction.redact_url(&url).as_str(),
+            "https://mcc.example/recipe/1?session=%5Bredacted%5D&lang=de&token=%5Bredacted%5D"
+        );
+        let plain: reqwest::Url = "https://mcc.example/recipe/1?lang=de".parse().unwrap();
+        assert_eq!(redaction.redact_url(&plain), plain);
+    }
+
+    #[test]
+    fn json_fields() {
+        let redaction = Redaction::new()
+            .json_field("data.uid")
+            .json_field("data.tags.name")
+            .json_field("missing.field");
+        let body = br#"{"data":{"uid":"u-123","tags":[{"name":"a"},{"name":"b"}],"id":1}}"#;
+
+        let redacted: Value =
+            serde_json::from_slice(&redaction.redact_body(body)).expect("Invalid JSON");
+
+        assert_eq!(
+            redacted,
+            serde_json::json!({"data": {
+                "uid": "[redacted]",
+                "tags": [{"name": "[redacted]"}, {"name": "[redacted]"}],
+                "id": 1
+            }})
+        );
+    }
+
+    #[test]
+    fn untouched_bodies() {
+        let redaction = Redaction::new().json_field("data.uid");
+
+        assert!(matches!(
+            redaction.redact_body(b"not json"),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            redaction.redact_body(br#"{"data":{"id":1}}"#),
+            Cow::Borrowed(_)
+        ));
+    }
+}
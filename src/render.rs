@@ -0,0 +1,236 @@
+//! Export recipes into printable formats
+//!
+//! Only lightweight text formats are provided here. Heavier targets like PDF
+//! are meant to live in downstream crates implementing [`RecipeRenderer`].
+use crate::{schemas::Recipe, Result};
+use std::{fmt::Write, io};
+
+/// Turn a recipe into a document of some format
+pub trait RecipeRenderer {
+    fn render(&self, recipe: &Recipe) -> Result<Vec<u8>>;
+}
+
+/// Renders recipes as Markdown document
+#[derive(Debug, Default, Clone)]
+pub struct MarkdownRenderer;
+
+/// Renders recipes as standalone HTML document
+#[derive(Debug, Default, Clone)]
+pub struct HtmlRenderer;
+
+/// Steps with actual instruction text, pure machine steps are skipped
+fn step_texts(recipe: &Recipe) -> impl Iterator<Item = &str> {
+    recipe
+        .data
+        .guided_cooking
+        .steps
+        .iter()
+        .map(|s| s.text.trim())
+        .filter(|t| !t.is_empty())
+}
+
+fn summary(recipe: &Recipe) -> String {
+    let data = &recipe.data;
+    format!(
+        "{} {} · {} min · {}",
+        data.yield_field, data.yield_unit, data.duration_total, data.complexity
+    )
+}
+
+impl RecipeRenderer for MarkdownRenderer {
+    fn render(&self, recipe: &Recipe) -> Result<Vec<u8>> {
+        let mut out = String::new();
+
+        writeln!(out, "# {}\n", recipe.data.name)?;
+        writeln!(out, "{}\n", summary(recipe))?;
+
+        for base in &recipe.data.ingredients_bases {
+            writeln!(out, "## {}\n", base.name)?;
+            for ingredient in &base.ingredients {
+                writeln!(
+                    out,
+                    "- {}",
+                    ingredient.format_ingredient(&recipe.data.language)
+                )?;
+            }
+            writeln!(out)?;
+        }
+
+        writeln!(out, "## Steps\n")?;
+        for (idx, text) in step_texts(recipe).enumerate() {
+            writeln!(out, "{}. {}", idx + 1, text)?;
+        }
+
+        Ok(out.into_bytes())
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl RecipeRenderer for HtmlRenderer {
+    fn render(&self, recipe: &Recipe) -> Result<Vec<u8>> {
+        let mut out = String::new();
+        let title = escape_html(&recipe.data.name);
+
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(
+            out,
+            "<html lang=\"{}\">",
+            escape_html(&recipe.data.language)
+        )?;
+        writeln!(
+            out,
+            "<head><meta charset=\"utf-8\"><title>{}</title></head>",
+            title
+        )?;
+        writeln!(out, "<body>")?;
+        writeln!(out, "<h1>{}</h1>", title)?;
+        writeln!(out, "<p>{}</p>", escape_html(&summary(recipe)))?;
+
+        for base in &recipe.data.ingredients_bases {
+            writeln!(out, "<h2>{}</h2>", escape_html(&base.name))?;
+            writeln!(out, "<ul>")?;
+            for ingredient in &base.ingredients {
+                writeln!(
+                    out,
+                    "<li>{}</li>",
+                    escape_html(&ingredient.format_ingredient(&recipe.data.language))
+                )?;
+            }
+            writeln!(out, "</ul>")?;
+        }
+
+        writeln!(out, "<h2>Steps</h2>")?;
+        writeln!(out, "<ol>")?;
+        for text in step_texts(recipe) {
+            writeln!(out, "<li>{}</li>", escape_html(text))?;
+        }
+        writeln!(out, "</ol>")?;
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")?;
+
+        Ok(out.into_bytes())
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break, RFC 4180 style
+fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Write a CSV summary with a header and one row per recipe
+///
+/// Columns are id, title, servings, total_time (minutes), difficulty as
+/// provided by the server and ingredient_count, summed over all ingredient
+/// bases. Rows end with CRLF, as spreadsheet applications expect.
+pub fn recipes_to_csv(recipes: &[Recipe], mut writer: impl io::Write) -> Result<()> {
+    let mut out = String::from("id,title,servings,total_time,difficulty,ingredient_count\r\n");
+
+    for recipe in recipes {
+        let data = &recipe.data;
+        let ingredient_count: usize = data
+            .ingredients_bases
+            .iter()
+            .map(|base| base.ingredients.len())
+            .sum();
+        write!(
+            out,
+            "{},{},{},{},{},{}\r\n",
+            data.id,
+            escape_csv(&data.name),
+            data.yield_field,
+            data.duration_total,
+            escape_csv(&data.complexity),
+            ingredient_count
+        )?;
+    }
+
+    writer.write_all(out.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    #[fixture]
+    fn recipe() -> Recipe {
+        let json = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        serde_json::from_slice(&json).expect("Failed to deserialize")
+    }
+
+    fn render_string(renderer: &dyn RecipeRenderer, recipe: &Recipe) -> String {
+        let bytes = renderer.render(recipe).expect("Failed to render");
+        String::from_utf8(bytes).expect("Rendered output is not UTF-8")
+    }
+
+    #[rstest]
+    fn html_renderer(recipe: Recipe) {
+        let html = render_string(&HtmlRenderer, &recipe);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Moscow Mule</h1>"));
+        assert!(html.contains("<h2>Für den Drink</h2>"));
+        assert!(html.contains("<li>1/2 Stk Limette</li>"));
+        assert!(html.contains("<li>100 ml Vodka</li>"));
+        assert!(html.contains("<li>Vodka hinzugeben.</li>"));
+        // Step without text is skipped
+        assert!(!html.contains("<li></li>"));
+    }
+
+    #[rstest]
+    fn html_renderer_escapes(mut recipe: Recipe) {
+        recipe.data.name = "Fish & <Chips>".into();
+
+        let html = render_string(&HtmlRenderer, &recipe);
+
+        assert!(html.contains("<h1>Fish &amp; &lt;Chips&gt;</h1>"));
+        assert!(!html.contains("<Chips>"));
+    }
+
+    #[rstest]
+    fn markdown_renderer(recipe: Recipe) {
+        let md = render_string(&MarkdownRenderer, &recipe);
+
+        assert!(md.starts_with("# Moscow Mule\n"));
+        assert!(md.contains("- 400 ml Ginger beer\n"));
+        assert!(md.contains("1. Limettensaft auspressen und in den Topf geben.\n"));
+        assert!(md.contains("4. Ginger Beer hinzugeben.\n"));
+    }
+
+    #[rstest]
+    fn csv(recipe: Recipe) {
+        let mut quoted = recipe.clone();
+        quoted.data.id = 4711;
+        quoted.data.name = "Salt, \"pepper\"".into();
+
+        let mut out = vec![];
+        recipes_to_csv(&[recipe, quoted], &mut out).expect("Failed to write CSV");
+
+        assert_eq!(
+            String::from_utf8(out).expect("Invalid UTF-8"),
+            "id,title,servings,total_time,difficulty,ingredient_count\r\n\
+             25011,Moscow Mule,6,63,Mittel,3\r\n\
+             4711,\"Salt, \"\"pepper\"\"\",6,63,Mittel,3\r\n"
+        );
+    }
+}
@@ -1,7 +1,11 @@
-use serde::{Deserialize, Serialize};
+use crate::{images::ImageRole, locale::NumberFormat, units::Unit};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::fmt;
 use std::str::FromStr;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+mod layout;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum RecipeType {
     Default,
@@ -9,12 +13,17 @@ pub enum RecipeType {
     Beta,
 }
 
-impl ToString for RecipeType {
-    fn to_string(&self) -> String {
+impl RecipeType {
+    /// All recipe types known to the API
+    pub const ALL: [RecipeType; 3] = [RecipeType::Default, RecipeType::Live, RecipeType::Beta];
+}
+
+impl fmt::Display for RecipeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            RecipeType::Default => "default".into(),
-            RecipeType::Live => "live".into(),
-            RecipeType::Beta => "beta".into(),
+            RecipeType::Default => f.write_str("default"),
+            RecipeType::Live => f.write_str("live"),
+            RecipeType::Beta => f.write_str("beta"),
         }
     }
 }
@@ -44,7 +53,7 @@ pub enum LedColor {
     Off,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum NutrientType {
     Joules,
@@ -54,7 +63,8 @@ pub enum NutrientType {
     Fat,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Appliance program / mode a guided cooking step runs in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum StepMode {
     Preparation,
@@ -70,21 +80,248 @@ pub enum StepMode {
     End,
 }
 
+impl StepMode {
+    /// Whether the step runs a program of the appliance, not just showing text
+    pub fn is_program(&self) -> bool {
+        !matches!(self, StepMode::Instruction | StepMode::End)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RecipeIds {
-    pub ids: Vec<i32>,
+    /// Accepts numbers as well as strings holding a number, e.g. `"25011"`
+    #[serde(deserialize_with = "ids_from_numbers_or_strings")]
+    pub ids: Vec<u32>,
+}
+
+fn ids_from_numbers_or_strings<'de, D>(deserializer: D) -> Result<Vec<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u32),
+        Text(String),
+    }
+
+    Vec::<Id>::deserialize(deserializer)?
+        .into_iter()
+        .map(|id| match id {
+            Id::Number(id) => Ok(id),
+            Id::Text(text) => text
+                .trim()
+                .parse()
+                .map_err(|_| de::Error::custom(format!("invalid recipe id: {:?}", text))),
+        })
+        .collect()
 }
 
+/// Body of the "all" endpoint, depending on the backend version either a
+/// bare array or an object wrapping it
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RecipeList {
+    Bare(Vec<Recipe>),
+    Wrapped { recipes: Vec<Recipe> },
+}
+
+impl RecipeList {
+    pub fn into_recipes(self) -> Vec<Recipe> {
+        match self {
+            RecipeList::Bare(recipes) | RecipeList::Wrapped { recipes } => recipes,
+        }
+    }
+}
+
+/// Single recipe as returned by the API
+///
+/// Deserializes from `{"data": {"id": .., ..}}`, `{"id": .., "data": {..}}`
+/// or the recipe data without wrapper, as endpoints differ. Always
+/// serialized as `{"data": {..}}`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Recipe {
     pub data: RecipeData,
 }
 
+impl Recipe {
+    /// Values of `schemeVersion` this crate is able to represent
+    ///
+    /// When the server starts returning a new scheme version, compare a
+    /// sample response against `RecipeData`, update the structs accordingly
+    /// and append the new version here.
+    pub const SUPPORTED_SCHEME_VERSIONS: &'static [i64] = &[1];
+
+    /// Format marker (`schemeVersion`) the server attached to this recipe
+    pub fn scheme_version(&self) -> i64 {
+        self.data.scheme_version
+    }
+
+    /// URL of the main image of the recipe, see [`Recipe::images`] for all of them
+    pub fn image_urls(&self) -> Vec<String> {
+        let name = self.data.image_name.trim();
+        if name.is_empty() {
+            return vec![];
+        }
+
+        vec![crate::images::resolve(&self.data.image_base, name)]
+    }
+
+    /// Appliance programs used by the guided cooking steps, in order of first use
+    ///
+    /// Empty for recipes without guided cooking, or only instruction steps.
+    pub fn programs(&self) -> Vec<StepMode> {
+        let mut programs = vec![];
+        for step in &self.data.guided_cooking.steps {
+            if step.mode.is_program() && !programs.contains(&step.mode) {
+                programs.push(step.mode);
+            }
+        }
+        programs
+    }
+
+    /// Guided cooking steps grouped into their sections, in order
+    ///
+    /// Consecutive steps with the same [`Step::section`] form a section.
+    /// Steps without one end up in untitled sections, so recipes without
+    /// any grouping have a single untitled section. Empty for recipes
+    /// without steps.
+    pub fn step_sections(&self) -> Vec<StepSection> {
+        let mut sections: Vec<StepSection> = vec![];
+        for step in &self.data.guided_cooking.steps {
+            let title = step
+                .section
+                .as_deref()
+                .map(str::trim)
+                .filter(|title| !title.is_empty());
+            match sections.last_mut() {
+                Some(section) if section.title.as_deref() == title => {
+                    section.steps.push(step.clone())
+                }
+                _ => sections.push(StepSection {
+                    title: title.map(String::from),
+                    steps: vec![step.clone()],
+                }),
+            }
+        }
+        sections
+    }
+
+    /// Check the recipe is well-formed, collecting every broken invariant
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut issues = vec![];
+
+        if self.data.name.trim().is_empty() {
+            issues.push(ValidationIssue::EmptyTitle);
+        }
+        if self
+            .data
+            .ingredients_bases
+            .iter()
+            .all(|base| base.ingredients.is_empty())
+        {
+            issues.push(ValidationIssue::NoIngredients);
+        }
+        if self.data.guided_cooking.steps.is_empty() {
+            issues.push(ValidationIssue::NoSteps);
+        }
+        if self.data.yield_field <= 0 {
+            issues.push(ValidationIssue::NonPositiveYield(self.data.yield_field));
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// Error out if the recipe uses a scheme version this crate does not know
+    pub fn assert_schema_compatible(&self) -> crate::Result<()> {
+        if Recipe::SUPPORTED_SCHEME_VERSIONS.contains(&self.scheme_version()) {
+            return Ok(());
+        }
+
+        Err(format!(
+            "Unsupported recipe scheme version {} for recipe {}, supported: {:?}",
+            self.scheme_version(),
+            self.data.id,
+            Recipe::SUPPORTED_SCHEME_VERSIONS
+        )
+        .into())
+    }
+}
+
+/// Steps of a recipe section, see [`Recipe::step_sections`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepSection {
+    /// Section title as sent by the server, `None` for ungrouped steps
+    pub title: Option<String>,
+    pub steps: Vec<Step>,
+}
+
+/// Broken invariant reported by [`Recipe::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// Title is empty or whitespace only
+    EmptyTitle,
+    /// No ingredient in any ingredient base
+    NoIngredients,
+    /// No guided cooking step
+    NoSteps,
+    /// Yield / servings is zero or negative
+    NonPositiveYield(i64),
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::EmptyTitle => f.write_str("Recipe has no title"),
+            ValidationIssue::NoIngredients => f.write_str("Recipe has no ingredients"),
+            ValidationIssue::NoSteps => f.write_str("Recipe has no steps"),
+            ValidationIssue::NonPositiveYield(value) => {
+                write!(f, "Recipe yield must be positive, got {}", value)
+            }
+        }
+    }
+}
+
+/// Single recipe response including the metadata the server attached
+///
+/// [`Recipe`] is the same envelope without metadata, both deserialize from
+/// the same layouts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecipeEnvelope {
+    pub data: RecipeData,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl From<RecipeEnvelope> for Recipe {
+    fn from(envelope: RecipeEnvelope) -> Self {
+        Recipe {
+            data: envelope.data,
+        }
+    }
+}
+
+/// Response metadata, all fields are optional as backends differ
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Meta {
+    /// Server timestamp of the response
+    pub timestamp: Option<String>,
+    /// Backend / API version
+    pub version: Option<String>,
+    /// Any further metadata, as sent by the server
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RecipeData {
-    pub id: i64,
+    pub id: u32,
     pub new: i64,
     pub name: String,
     pub tags: Vec<Tag>,
@@ -98,27 +335,60 @@ pub struct RecipeData {
     pub download: serde_json::Value,
     pub duration: i64,
     pub language: String,
-    #[serde(rename = "originID")]
+    #[serde(rename = "originID", alias = "origin_id")]
     pub origin_id: String,
-    #[serde(rename = "valid_to")]
+    #[serde(rename = "valid_to", alias = "validTo")]
     pub valid_to: Option<String>,
+    #[serde(alias = "image_base")]
     pub image_base: String,
+    #[serde(alias = "image_name")]
     pub image_name: String,
     pub nutrients: Vec<Nutrient>,
+    #[serde(alias = "yield_unit")]
     pub yield_unit: String,
     pub complexity: String,
-    #[serde(rename = "valid_from")]
+    #[serde(rename = "valid_from", alias = "validFrom")]
     pub valid_from: Option<String>,
+    #[serde(alias = "machine_type")]
     pub machine_type: String,
+    #[serde(alias = "version_tags")]
     pub version_tags: Option<Vec<serde_json::Value>>,
     pub instructions: Vec<String>,
     pub preparations: Vec<serde_json::Value>,
+    #[serde(alias = "duration_total")]
     pub duration_total: i64,
+    #[serde(alias = "guided_cooking")]
     pub guided_cooking: GuidedCooking,
+    #[serde(alias = "scheme_version")]
     pub scheme_version: i64,
+    #[serde(alias = "machine_version")]
     pub machine_version: i64,
+    #[serde(alias = "ingredients_bases")]
     pub ingredients_bases: Vec<IngredientsBase>,
     pub betarecipetype: Option<String>,
+    /// Ids of related recipes, empty if the server doesn't link any
+    #[serde(
+        default,
+        alias = "related_recipes",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub related_recipes: Vec<u32>,
+    /// Further images and resolutions, empty if the server only sends `imageName`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageVariant>,
+}
+
+/// Image listed in [`RecipeData::images`], see [`Recipe::images`] for the typed view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageVariant {
+    /// File name below [`RecipeData::image_base`], or an absolute URL
+    pub name: String,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub role: ImageRole,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -151,7 +421,14 @@ pub struct Step {
     pub step: i64,
     pub text: String,
     pub measurement: Measurement,
+    #[serde(alias = "machine_values")]
     pub machine_values: MachineValues,
+    /// Title of the section / phase the step belongs to, e.g. "Zubereitung"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Unit of the `temp` fields, changed by [`Recipe::convert_temperatures`]
+    #[serde(skip)]
+    pub(crate) temp_unit: crate::temperature::TempUnit,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -195,6 +472,66 @@ pub struct Ingredient {
     pub amount: String,
 }
 
+impl Ingredient {
+    /// Parse the free-text amount into a number
+    ///
+    /// Accepts plain numbers ("100"), decimal commas ("0,75"), fractions ("1/2")
+    /// and mixed fractions ("1 1/2"). Returns `None` for amounts that are not
+    /// quantifiable, like "etwas" or an empty string.
+    ///
+    /// Without knowing the language, a "." is always taken as decimal point.
+    /// Use [`Ingredient::quantity_in`] for amounts of a recipe.
+    pub fn quantity(&self) -> Option<f64> {
+        parse_amount(&self.amount, None)
+    }
+
+    /// Parse the free-text amount written in `language`, the recipe language
+    ///
+    /// Digit grouping is understood as in [`NumberFormat::parse`], so "1.500"
+    /// in a German recipe is 1500 rather than 1.5.
+    pub fn quantity_in(&self, language: &str) -> Option<f64> {
+        parse_amount(&self.amount, Some(NumberFormat::for_language(language)))
+    }
+
+    /// Parse the free-text unit, see [`Unit::parse`]
+    pub fn typed_unit(&self) -> Unit {
+        Unit::parse(&self.unit)
+    }
+}
+
+/// Parse an amount written in `format`, any decimal separator if `None`
+pub(crate) fn parse_amount(amount: &str, format: Option<NumberFormat>) -> Option<f64> {
+    let parse_decimal = |s: &str| match format {
+        Some(format) => format.parse(s),
+        None => s
+            .replace(',', ".")
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite()),
+    };
+
+    let parse_part = |s: &str| match s.split_once('/') {
+        Some((num, denom)) => {
+            let denom = parse_decimal(denom)?;
+            if denom == 0.0 {
+                return None;
+            }
+            Some(parse_decimal(num)? / denom)
+        }
+        None => parse_decimal(s),
+    };
+
+    let parts: Vec<&str> = amount.split_whitespace().collect();
+    match parts.as_slice() {
+        [single] => parse_part(single),
+        // Mixed fraction, e.g. "1 1/2"
+        [whole, fraction] if fraction.contains('/') => {
+            Some(parse_decimal(whole)? + parse_part(fraction)?)
+        }
+        _ => None,
+    }
+}
+
 // Non-recipe schemas
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -280,11 +617,222 @@ mod tests {
 
     #[rstest]
     #[case("recipe_single_25011.json", 25011)]
-    fn deserialize_recipe_single(#[case] filename: &str, #[case] expected_id: i64) {
+    fn deserialize_recipe_single(#[case] filename: &str, #[case] expected_id: u32) {
         let json = tests::get_testdata(filename).expect("Failed to get testdata");
 
         let res: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
 
         assert_eq!(res.data.id, expected_id);
     }
+
+    #[rstest]
+    #[case::numbers("recipe_ids.json")]
+    #[case::strings("recipe_ids_strings.json")]
+    fn deserialize_recipe_ids(#[case] filename: &str) {
+        let json = tests::get_testdata(filename).expect("Failed to get testdata");
+
+        let res: RecipeIds = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        assert_eq!(res.ids, vec![25011, 25012]);
+    }
+
+    #[rstest]
+    #[case::mixed(r#"{"ids":[1," 2",3]}"#, Some(vec![1, 2, 3]))]
+    #[case::not_a_number(r#"{"ids":["x"]}"#, None)]
+    #[case::negative(r#"{"ids":[-1]}"#, None)]
+    #[case::negative_string(r#"{"ids":["-1"]}"#, None)]
+    fn coerce_recipe_ids(#[case] json: &str, #[case] expected: Option<Vec<u32>>) {
+        let res = serde_json::from_str::<RecipeIds>(json).ok().map(|r| r.ids);
+
+        assert_eq!(res, expected);
+    }
+
+    #[rstest]
+    #[case::integer("100", Some(100.0))]
+    #[case::decimal_comma("0,75", Some(0.75))]
+    #[case::decimal_point("1.5", Some(1.5))]
+    #[case::fraction("1/2", Some(0.5))]
+    #[case::mixed_fraction("1 1/2", Some(1.5))]
+    #[case::text("etwas", None)]
+    #[case::empty("", None)]
+    #[case::zero_denominator("1/0", None)]
+    fn ingredient_quantity(#[case] amount: &str, #[case] expected: Option<f64>) {
+        let ingredient = Ingredient {
+            name: "Zucker".into(),
+            unit: "g".into(),
+            amount: amount.into(),
+        };
+
+        assert_eq!(ingredient.quantity(), expected);
+    }
+
+    #[rstest]
+    #[case::supported(1, true)]
+    #[case::unknown(2, false)]
+    #[case::zero(0, false)]
+    fn schema_compatibility(#[case] version: i64, #[case] compatible: bool) {
+        let json = tests::get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let mut recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+        recipe.data.scheme_version = version;
+
+        let res = recipe.assert_schema_compatible();
+
+        assert_eq!(res.is_ok(), compatible);
+        if let Err(e) = res {
+            assert!(e.to_string().contains(&version.to_string()));
+        }
+    }
+
+    #[rstest]
+    #[case("recipe_single_25011.json")]
+    #[case("recipe_single_25012.json")]
+    fn validate_valid(#[case] filename: &str) {
+        let json = tests::get_testdata(filename).expect("Failed to get testdata");
+        let recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        assert_eq!(recipe.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_reports_all_issues() {
+        let json = tests::get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let mut recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+        recipe.data.name = "  ".into();
+        for base in &mut recipe.data.ingredients_bases {
+            base.ingredients.clear();
+        }
+        recipe.data.guided_cooking.steps.clear();
+        recipe.data.yield_field = 0;
+
+        assert_eq!(
+            recipe.validate(),
+            Err(vec![
+                ValidationIssue::EmptyTitle,
+                ValidationIssue::NoIngredients,
+                ValidationIssue::NoSteps,
+                ValidationIssue::NonPositiveYield(0),
+            ])
+        );
+    }
+
+    #[rstest]
+    #[case::ramp("recipe_single_25011.json", vec![StepMode::Scale, StepMode::Ramp])]
+    #[case::cooking("recipe_single_25012.json", vec![StepMode::Scale, StepMode::Cooking, StepMode::Turbo])]
+    fn programs(#[case] filename: &str, #[case] expected: Vec<StepMode>) {
+        let json = tests::get_testdata(filename).expect("Failed to get testdata");
+        let recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        assert_eq!(recipe.programs(), expected);
+    }
+
+    #[test]
+    fn programs_without_guided_cooking() {
+        let json = tests::get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let mut recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+        recipe
+            .data
+            .guided_cooking
+            .steps
+            .retain(|s| !s.mode.is_program());
+
+        assert!(recipe.programs().is_empty());
+        recipe.data.guided_cooking.steps.clear();
+        assert!(recipe.programs().is_empty());
+    }
+
+    #[rstest]
+    #[case::plain("https://example.com", "a.jpg", vec!["https://example.com/a.jpg"])]
+    #[case::slashes("https://example.com/", "/a.jpg", vec!["https://example.com/a.jpg"])]
+    #[case::no_image("https://example.com", "", vec![])]
+    #[case::absolute("https://example.com", "https://cdn.example.com/a.jpg", vec!["https://cdn.example.com/a.jpg"])]
+    fn image_urls(#[case] base: &str, #[case] name: &str, #[case] expected: Vec<&str>) {
+        let json = tests::get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let mut recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+        recipe.data.image_base = base.into();
+        recipe.data.image_name = name.into();
+
+        assert_eq!(recipe.image_urls(), expected);
+    }
+
+    #[test]
+    fn step_sections() {
+        let json = tests::get_testdata("recipe_single_25011_sections.json")
+            .expect("Failed to get testdata");
+        let recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        let sections = recipe.step_sections();
+        let sections: Vec<(Option<&str>, Vec<i64>)> = sections
+            .iter()
+            .map(|s| (s.title.as_deref(), s.steps.iter().map(|s| s.step).collect()))
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (Some("Vorbereitung"), vec![1]),
+                (Some("Zubereitung"), vec![2, 3]),
+                (Some("Servieren"), vec![4, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn step_sections_without_grouping() {
+        let json = tests::get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let mut recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        let sections = recipe.step_sections();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].title, None);
+        assert_eq!(sections[0].steps, recipe.data.guided_cooking.steps);
+
+        recipe.data.guided_cooking.steps.clear();
+        assert!(recipe.step_sections().is_empty());
+    }
+
+    #[rstest]
+    #[case::bare("recipe_all_catalog.json")]
+    #[case::wrapped("recipe_all_catalog_wrapped.json")]
+    fn deserialize_recipe_list(#[case] filename: &str) {
+        let json = tests::get_testdata(filename).expect("Failed to get testdata");
+        let list: RecipeList = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        let ids: Vec<u32> = list.into_recipes().iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![25012, 25011]);
+    }
+
+    #[test]
+    fn deserialize_envelope_meta() {
+        let json =
+            tests::get_testdata("recipe_single_25011_meta.json").expect("Failed to get testdata");
+        let envelope: RecipeEnvelope =
+            serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        let meta = envelope.meta.clone().expect("Missing meta");
+        assert_eq!(meta.timestamp.as_deref(), Some("2021-11-06T12:00:00.000Z"));
+        assert_eq!(meta.version.as_deref(), Some("1.4.2"));
+        assert_eq!(meta.extra["requestId"], "c0ffee");
+        assert_eq!(Recipe::from(envelope).data.id, 25011);
+    }
+
+    #[test]
+    fn deserialize_envelope_without_meta() {
+        let json = tests::get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let envelope: RecipeEnvelope =
+            serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        assert!(envelope.meta.is_none());
+    }
+
+    #[test]
+    fn deserialize_snake_case_aliases() {
+        let camel =
+            tests::get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let snake = tests::get_testdata("recipe_single_25011_snake_case.json")
+            .expect("Failed to get testdata");
+
+        let camel: Recipe = serde_json::from_slice(&camel).expect("Failed to deserialize");
+        let snake: Recipe = serde_json::from_slice(&snake).expect("Failed to deserialize");
+
+        assert_eq!(snake, camel);
+    }
 }
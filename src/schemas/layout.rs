@@ -0,0 +1,386 @@
+//! Deserialization of [`Recipe`] and [`RecipeEnvelope`] from the layouts
+//! different endpoints send
+//!
+//! - `{"data": {"id": 1, ..}}`, the usual one
+//! - `{"id": 1, "data": {..}}`, id next to instead of inside `data`
+//! - `{"id": 1, "name": .., ..}`, recipe data without the `data` wrapper
+//!
+//! The first two are parsed streaming, a missing `id` in `data` is filled
+//! in with a placeholder and replaced by the outer id afterwards. Only the
+//! flat layout is buffered. `meta` is kept for [`RecipeEnvelope`] in all
+//! layouts.
+use super::{Recipe, RecipeData, RecipeEnvelope};
+use serde::de::{
+    self, value::StrDeserializer, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess,
+    SeqAccess, Visitor,
+};
+use std::{cell::Cell, fmt};
+
+const ID: &str = "id";
+
+impl<'de> de::Deserialize<'de> for Recipe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (data, _) = deserializer.deserialize_map(RecipeVisitor)?;
+        Ok(Recipe { data })
+    }
+}
+
+impl<'de> de::Deserialize<'de> for RecipeEnvelope {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (data, meta) = deserializer.deserialize_map(RecipeVisitor)?;
+        let meta = match meta {
+            Some(meta) => serde_json::from_value(meta).map_err(de::Error::custom)?,
+            None => None,
+        };
+        Ok(RecipeEnvelope { data, meta })
+    }
+}
+
+/// Recipe data and the raw `meta` object next to it
+struct RecipeVisitor;
+
+impl<'de> Visitor<'de> for RecipeVisitor {
+    type Value = (RecipeData, Option<serde_json::Value>);
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a recipe object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let injected = Cell::new(false);
+        let mut data: Option<RecipeData> = None;
+        let mut outer_id: Option<u32> = None;
+        let mut meta: Option<serde_json::Value> = None;
+        // Only needed for the flat layout, a wrapped recipe has at most `meta` here
+        let mut rest = serde_json::Map::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "data" if data.is_none() => {
+                    data = Some(map.next_value_seed(DataSeed {
+                        injected: &injected,
+                    })?)
+                }
+                ID if outer_id.is_none() => outer_id = Some(map.next_value()?),
+                "meta" if meta.is_none() => meta = Some(map.next_value()?),
+                _ => {
+                    rest.insert(key, map.next_value()?);
+                }
+            }
+        }
+
+        let data = match (data, outer_id) {
+            (Some(mut data), Some(id)) if injected.get() => {
+                data.id = id;
+                data
+            }
+            (Some(_), None) if injected.get() => return Err(de::Error::missing_field(ID)),
+            (Some(data), _) => data,
+            (None, outer_id) => {
+                if let Some(id) = outer_id {
+                    rest.insert(ID.to_string(), id.into());
+                }
+                serde_json::from_value(serde_json::Value::Object(rest))
+                    .map_err(de::Error::custom)?
+            }
+        };
+
+        Ok((data, meta))
+    }
+}
+
+/// `data` of a recipe, getting an `id` entry appended if it has none
+struct DataSeed<'a> {
+    injected: &'a Cell<bool>,
+}
+
+impl<'de> DeserializeSeed<'de> for DataSeed<'_> {
+    type Value = RecipeData;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<RecipeData, D::Error> {
+        de::Deserialize::deserialize(DataDeserializer {
+            inner: deserializer,
+            injected: self.injected,
+        })
+    }
+}
+
+struct DataDeserializer<'a, D> {
+    inner: D,
+    injected: &'a Cell<bool>,
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for DataDeserializer<'_, D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_any(InjectVisitor {
+            inner: visitor,
+            injected: self.injected,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_struct(
+            name,
+            fields,
+            InjectVisitor {
+                inner: visitor,
+                injected: self.injected,
+            },
+        )
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct InjectVisitor<'a, V> {
+    inner: V,
+    injected: &'a Cell<bool>,
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for InjectVisitor<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_map(InjectMap {
+            inner: map,
+            seen_id: false,
+            pending: false,
+            injected: self.injected,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_seq(seq)
+    }
+}
+
+/// Entries of `data`, followed by `"id": 0` if no id was among them
+struct InjectMap<'a, A> {
+    inner: A,
+    seen_id: bool,
+    /// Injected key was returned, its value is next
+    pending: bool,
+    injected: &'a Cell<bool>,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for InjectMap<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        let mut slot = Some(seed);
+        let key = self.inner.next_key_seed(KeySeed {
+            seed: &mut slot,
+            seen_id: &mut self.seen_id,
+        })?;
+
+        match (key, slot) {
+            (Some(key), _) => Ok(Some(key)),
+            (None, Some(seed)) if !self.seen_id && !self.injected.get() => {
+                self.injected.set(true);
+                self.pending = true;
+                let key: StrDeserializer<'_, A::Error> = ID.into_deserializer();
+                seed.deserialize(key).map(Some)
+            }
+            (None, _) => Ok(None),
+        }
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+        if self.pending {
+            self.pending = false;
+            return seed.deserialize(0i64.into_deserializer());
+        }
+        self.inner.next_value_seed(seed)
+    }
+}
+
+/// Key seed noting whether the key is `id`
+struct KeySeed<'a, K> {
+    seed: &'a mut Option<K>,
+    seen_id: &'a mut bool,
+}
+
+impl<'de, K: DeserializeSeed<'de>> DeserializeSeed<'de> for KeySeed<'_, K> {
+    type Value = K::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<K::Value, D::Error> {
+        let seed = self
+            .seed
+            .take()
+            .ok_or_else(|| de::Error::custom("Key seed used twice"))?;
+        seed.deserialize(KeyDeserializer {
+            inner: deserializer,
+            seen_id: self.seen_id,
+        })
+    }
+}
+
+struct KeyDeserializer<'a, D> {
+    inner: D,
+    seen_id: &'a mut bool,
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for KeyDeserializer<'_, D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_any(KeyVisitor {
+            inner: visitor,
+            seen_id: self.seen_id,
+        })
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_identifier(KeyVisitor {
+            inner: visitor,
+            seen_id: self.seen_id,
+        })
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.inner.deserialize_str(KeyVisitor {
+            inner: visitor,
+            seen_id: self.seen_id,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum ignored_any
+    }
+}
+
+struct KeyVisitor<'a, V> {
+    inner: V,
+    seen_id: &'a mut bool,
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for KeyVisitor<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    fn visit_str<E: de::Error>(self, key: &str) -> Result<V::Value, E> {
+        *self.seen_id |= key == ID;
+        self.inner.visit_str(key)
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, key: &'de str) -> Result<V::Value, E> {
+        *self.seen_id |= key == ID;
+        self.inner.visit_borrowed_str(key)
+    }
+
+    fn visit_string<E: de::Error>(self, key: String) -> Result<V::Value, E> {
+        *self.seen_id |= key == ID;
+        self.inner.visit_string(key)
+    }
+
+    fn visit_bytes<E: de::Error>(self, key: &[u8]) -> Result<V::Value, E> {
+        *self.seen_id |= key == ID.as_bytes();
+        self.inner.visit_bytes(key)
+    }
+
+    fn visit_u64<E: de::Error>(self, key: u64) -> Result<V::Value, E> {
+        self.inner.visit_u64(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    fn load(filename: &str) -> Recipe {
+        serde_json::from_slice(&get_testdata(filename).expect("Failed to get testdata"))
+            .expect("Failed to parse recipe")
+    }
+
+    #[rstest]
+    #[case::id_outside("recipe_single_25011_id_outside.json")]
+    #[case::flat("recipe_single_25011_flat.json")]
+    fn layouts(#[case] filename: &str) {
+        assert_eq!(load(filename), load("recipe_single_25011.json"));
+    }
+
+    #[rstest]
+    #[case::wrapped(|value: serde_json::Value| value)]
+    #[case::id_outside(|mut value: serde_json::Value| {
+        value["id"] = value["data"].as_object_mut().unwrap().remove("id").unwrap();
+        value
+    })]
+    #[case::flat(|value: serde_json::Value| {
+        let mut flat = value["data"].clone();
+        flat["meta"] = value["meta"].clone();
+        flat
+    })]
+    fn envelope_layouts(#[case] layout: fn(serde_json::Value) -> serde_json::Value) {
+        let json = get_testdata("recipe_single_25011_meta.json").expect("Failed to get testdata");
+        let expected: RecipeEnvelope = serde_json::from_slice(&json).expect("Failed to parse");
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+
+        let envelope: RecipeEnvelope =
+            serde_json::from_value(layout(value)).expect("Failed to parse envelope");
+
+        assert_eq!(envelope, expected);
+        assert_eq!(envelope.data.id, 25011);
+        assert_eq!(
+            envelope.meta.and_then(|meta| meta.version).as_deref(),
+            Some("1.4.2")
+        );
+    }
+
+    #[test]
+    fn inner_id_wins() {
+        let mut value: serde_json::Value = serde_json::from_slice(
+            &get_testdata("recipe_single_25011.json").expect("Failed to get testdata"),
+        )
+        .unwrap();
+        value["id"] = 1.into();
+
+        let recipe: Recipe = serde_json::from_value(value).expect("Failed to parse recipe");
+        assert_eq!(recipe.data.id, 25011);
+    }
+
+    #[test]
+    fn missing_id() {
+        let mut value: serde_json::Value = serde_json::from_slice(
+            &get_testdata("recipe_single_25011.json").expect("Failed to get testdata"),
+        )
+        .unwrap();
+        value["data"].as_object_mut().unwrap().remove("id");
+
+        let err = serde_json::from_value::<Recipe>(value).expect_err("Recipe without id accepted");
+        assert!(err.to_string().contains("missing field `id`"), "{}", err);
+    }
+
+    #[test]
+    fn serializes_wrapped() {
+        let recipe = load("recipe_single_25011_id_outside.json");
+        let value = serde_json::to_value(&recipe).expect("Failed to serialize");
+
+        assert_eq!(value["data"]["id"], 25011);
+        assert!(value.get("id").is_none());
+    }
+}
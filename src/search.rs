@@ -0,0 +1,136 @@
+//! Fuzzy lookup of recipes by name, e.g. for "did you mean" suggestions
+use crate::schemas::Recipe;
+
+/// Jaro similarity of two strings, 1.0 means identical
+fn jaro(a: &[char], b: &[char]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+
+    let mut matches = 0usize;
+    for (i, ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        for j in start..end {
+            if !b_matched[j] && b[j] == *ca {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let a_seq = a
+        .iter()
+        .zip(&a_matched)
+        .filter(|(_, m)| **m)
+        .map(|(c, _)| c);
+    let b_seq = b
+        .iter()
+        .zip(&b_matched)
+        .filter(|(_, m)| **m)
+        .map(|(c, _)| c);
+    let transpositions = a_seq.zip(b_seq).filter(|(x, y)| x != y).count() / 2;
+
+    let m = matches as f64;
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0
+}
+
+/// Jaro-Winkler similarity, boosting strings sharing a common prefix
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let sim = jaro(&a, &b);
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+
+    sim + prefix as f64 * 0.1 * (1.0 - sim)
+}
+
+/// Find the recipes whose name is most similar to `query`
+///
+/// Names are compared case-insensitively using Jaro-Winkler similarity.
+/// Returns at most `limit` matches with a score in `(0.0, 1.0]`, best first.
+pub fn find_closest_recipes<'a>(
+    recipes: &'a [Recipe],
+    query: &str,
+    limit: usize,
+) -> Vec<(&'a Recipe, f64)> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let mut scored: Vec<(&Recipe, f64)> = recipes
+        .iter()
+        .map(|r| (r, jaro_winkler(&query, &r.data.name.trim().to_lowercase())))
+        .filter(|(_, score)| *score > 0.0)
+        .collect();
+
+    scored.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.data.name.cmp(&b.0.data.name))
+    });
+    scored.truncate(limit);
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    #[fixture]
+    fn recipes() -> Vec<Recipe> {
+        let json = get_testdata("recipe_all_catalog.json").expect("Failed to get testdata");
+        serde_json::from_slice(&json).expect("Failed to deserialize")
+    }
+
+    #[rstest]
+    #[case::exact("Moscow Mule", 25011)]
+    #[case::case_insensitive("moscow mule", 25011)]
+    #[case::typo("Moskow Mul", 25011)]
+    #[case::missing_letters("kartofelsupe", 25012)]
+    #[case::prefix("Kartof", 25012)]
+    fn near_miss(recipes: Vec<Recipe>, #[case] query: &str, #[case] expected: u32) {
+        let matches = find_closest_recipes(&recipes, query, 1);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0.data.id, expected);
+    }
+
+    #[rstest]
+    fn scores_ordered(recipes: Vec<Recipe>) {
+        let matches = find_closest_recipes(&recipes, "Moscow Mule", 10);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].1, 1.0);
+        assert!(matches[0].1 > matches[1].1);
+    }
+
+    #[rstest]
+    fn empty_query(recipes: Vec<Recipe>) {
+        assert!(find_closest_recipes(&recipes, "  ", 10).is_empty());
+    }
+
+    #[rstest]
+    #[case("martha", "marhta", 0.961)]
+    #[case("dixon", "dicksonx", 0.813)]
+    #[case("abc", "xyz", 0.0)]
+    fn jaro_winkler_reference(#[case] a: &str, #[case] b: &str, #[case] expected: f64) {
+        assert!((jaro_winkler(a, b) - expected).abs() < 0.001);
+    }
+}
@@ -0,0 +1,196 @@
+//! Shopping list aggregation across multiple recipes
+use crate::{schemas::Recipe, units::Unit};
+use std::collections::BTreeMap;
+
+/// Merged, quantifiable entry of a shopping list
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingItem {
+    pub name: String,
+    pub unit: String,
+    pub amount: f64,
+}
+
+/// Entry whose amount could not be summed up (e.g. "etwas Salz")
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnquantifiedItem {
+    pub name: String,
+    pub unit: String,
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShoppingList {
+    /// Summed up ingredients, sorted by name
+    pub items: Vec<ShoppingItem>,
+    /// Ingredients without a parseable amount, in order of appearance
+    pub unquantified: Vec<UnquantifiedItem>,
+}
+
+/// Unit to show for a merged item
+///
+/// Weights and volumes are shown in their base unit. Other units are shown as
+/// the server spelled them first, so "1 Stk" and "2 Stück" end up as "3 Stk".
+fn display_unit(base: &Unit, raw: &str) -> String {
+    match base {
+        Unit::Gram | Unit::Milliliter => base.symbol().to_string(),
+        _ => raw.trim().to_string(),
+    }
+}
+
+/// Merge the ingredients of all recipes into a single shopping list
+///
+/// Ingredients are merged when name (case-insensitive) and [`Unit`] match,
+/// after normalizing weights to gram and volumes to milliliter. Units that
+/// are not convertible (e.g. "Stk", "EL") are not merged with each other.
+/// Amounts are parsed in the language of their recipe, see
+/// [`crate::schemas::Ingredient::quantity_in`].
+pub fn build_shopping_list(recipes: &[Recipe]) -> ShoppingList {
+    let mut items: BTreeMap<(String, String), ShoppingItem> = BTreeMap::new();
+    let mut unquantified: Vec<UnquantifiedItem> = vec![];
+
+    let ingredients = recipes.iter().flat_map(|r| {
+        r.data
+            .ingredients_bases
+            .iter()
+            .flat_map(|base| base.ingredients.iter())
+            .map(move |ingredient| (ingredient, r.data.language.as_str()))
+    });
+
+    for (ingredient, language) in ingredients {
+        let name = ingredient.name.trim();
+
+        match ingredient.quantity_in(language) {
+            Some(quantity) => {
+                let (unit, factor) = ingredient.typed_unit().to_base();
+                let key = (name.to_lowercase(), unit.symbol().to_lowercase());
+
+                items
+                    .entry(key)
+                    .or_insert_with(|| ShoppingItem {
+                        name: name.to_string(),
+                        unit: display_unit(&unit, &ingredient.unit),
+                        amount: 0.0,
+                    })
+                    .amount += quantity * factor;
+            }
+            None => {
+                let item = UnquantifiedItem {
+                    name: name.to_string(),
+                    unit: ingredient.unit.trim().to_string(),
+                    amount: ingredient.amount.trim().to_string(),
+                };
+                if !unquantified.contains(&item) {
+                    unquantified.push(item);
+                }
+            }
+        }
+    }
+
+    ShoppingList {
+        items: items.into_values().collect(),
+        unquantified,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    fn load(filename: &str) -> Recipe {
+        let json = get_testdata(filename).expect("Failed to get testdata");
+        serde_json::from_slice(&json).expect("Failed to deserialize")
+    }
+
+    fn find<'a>(list: &'a ShoppingList, name: &str, unit: &str) -> &'a ShoppingItem {
+        list.items
+            .iter()
+            .find(|i| i.name == name && i.unit == unit)
+            .unwrap_or_else(|| panic!("Missing item {} ({})", name, unit))
+    }
+
+    #[fixture]
+    fn recipes() -> Vec<Recipe> {
+        vec![
+            load("recipe_single_25011.json"),
+            load("recipe_single_25012.json"),
+        ]
+    }
+
+    #[rstest]
+    fn merges_same_name_and_unit(recipes: Vec<Recipe>) {
+        let list = build_shopping_list(&recipes);
+
+        assert_eq!(find(&list, "Limette", "Stk").amount, 2.0);
+        assert_eq!(find(&list, "Vodka", "ml").amount, 120.0);
+        assert_eq!(find(&list, "Ginger beer", "ml").amount, 400.0);
+    }
+
+    #[rstest]
+    fn normalizes_convertible_units(recipes: Vec<Recipe>) {
+        let list = build_shopping_list(&recipes);
+
+        assert_eq!(find(&list, "Kartoffeln", "g").amount, 1250.0);
+        assert_eq!(find(&list, "Gemüsebrühe", "ml").amount, 750.0);
+        assert!(!list.items.iter().any(|i| i.unit == "kg" || i.unit == "l"));
+    }
+
+    #[rstest]
+    fn lists_unquantified_separately(recipes: Vec<Recipe>) {
+        let list = build_shopping_list(&recipes);
+
+        assert_eq!(
+            list.unquantified,
+            vec![UnquantifiedItem {
+                name: "Salz".into(),
+                unit: "".into(),
+                amount: "etwas".into(),
+            }]
+        );
+        assert!(!list.items.iter().any(|i| i.name == "Salz"));
+    }
+
+    #[test]
+    fn scales_with_repeated_recipes() {
+        let recipe = load("recipe_single_25011.json");
+        let list = build_shopping_list(&[recipe.clone(), recipe]);
+
+        assert_eq!(list.items.len(), 3);
+        assert_eq!(find(&list, "Limette", "Stk").amount, 1.0);
+        assert_eq!(find(&list, "Vodka", "ml").amount, 200.0);
+    }
+
+    #[test]
+    fn merges_unit_spellings() {
+        let mut a = load("recipe_single_25011.json");
+        let mut b = a.clone();
+        a.data.ingredients_bases[0].ingredients[0].unit = "Stück".into();
+        b.data.ingredients_bases[0].ingredients[1].unit = "cl".into();
+        b.data.ingredients_bases[0].ingredients[1].amount = "2".into();
+
+        let list = build_shopping_list(&[a, b]);
+
+        assert_eq!(find(&list, "Limette", "Stück").amount, 1.0);
+        assert_eq!(find(&list, "Vodka", "ml").amount, 120.0);
+    }
+
+    #[test]
+    fn parses_amounts_in_recipe_language() {
+        let german = load("recipe_single_25012_grouped_numbers.json");
+        let mut english = load("recipe_single_25011.json");
+        english.data.language = "en".into();
+        english.data.ingredients_bases[0].ingredients[1].amount = "1,000".into();
+
+        let list = build_shopping_list(&[german, english]);
+
+        assert_eq!(find(&list, "Kartoffeln", "g").amount, 1750.0);
+        assert_eq!(find(&list, "Gemüsebrühe", "ml").amount, 750.0);
+        assert_eq!(find(&list, "Vodka", "ml").amount, 1020.0);
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(build_shopping_list(&[]), ShoppingList::default());
+    }
+}
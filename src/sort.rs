@@ -0,0 +1,200 @@
+//! Sorting of recipe lists
+//!
+//! Titles are compared with a lightweight collation instead of plain byte
+//! order: accents and umlauts sort next to their base letter ("Äpfel" near
+//! "Apfel"), case only breaks ties. Polish and Spanish letters that their
+//! alphabets treat as separate letters (e.g. "ł", "ñ") sort right after
+//! their base letter.
+use crate::schemas::Recipe;
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Id,
+    /// Collated title, see the module docs
+    Title,
+    /// Total duration, shortest first
+    TotalTime,
+}
+
+/// Primary, secondary (accent) and tertiary (case) weights of a string
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct CollationKey {
+    primary: Vec<u32>,
+    accents: Vec<u8>,
+    case: Vec<u8>,
+}
+
+/// Base letters and accent weight of accented characters, empty for any other character
+fn decompose(c: char) -> (&'static str, u8) {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ą' => ("a", 1),
+        'ç' | 'ć' | 'č' => ("c", 1),
+        'è' | 'é' | 'ê' | 'ë' | 'ę' => ("e", 1),
+        'ì' | 'í' | 'î' | 'ï' => ("i", 1),
+        'ł' => ("l", 1),
+        'ñ' | 'ń' => ("n", 1),
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => ("o", 1),
+        'ś' | 'š' => ("s", 1),
+        'ù' | 'ú' | 'û' | 'ü' => ("u", 1),
+        'ý' | 'ÿ' => ("y", 1),
+        'ź' | 'ż' | 'ž' => ("z", 1),
+        'ß' => ("ss", 1),
+        'æ' => ("ae", 1),
+        'œ' => ("oe", 1),
+        _ => ("", 0),
+    }
+}
+
+/// Letters sorting as separate letter after their base letter in a language
+fn is_tailored(c: char, language: &str) -> bool {
+    match language {
+        "pl" => matches!(c, 'ą' | 'ć' | 'ę' | 'ł' | 'ń' | 'ó' | 'ś' | 'ź' | 'ż'),
+        "es" => c == 'ñ',
+        _ => false,
+    }
+}
+
+fn collation_key(s: &str, language: &str) -> CollationKey {
+    let mut key = CollationKey {
+        primary: vec![],
+        accents: vec![],
+        case: vec![],
+    };
+
+    for c in s.trim().chars() {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let case = u8::from(lower != c);
+        let (base, accent) = decompose(lower);
+
+        if base.is_empty() {
+            key.primary.push(u32::from(lower) * 2);
+            key.accents.push(0);
+            key.case.push(case);
+            continue;
+        }
+
+        let tailored = u32::from(is_tailored(lower, language));
+        // Weight of the separate letter lies between its base letter and the next
+        for b in base.chars() {
+            key.primary.push(u32::from(b) * 2 + tailored);
+            key.accents.push(accent);
+            key.case.push(case);
+        }
+    }
+
+    key
+}
+
+/// Compare two titles using the collation for `language` (ISO 639-1)
+pub fn compare_titles(a: &str, b: &str, language: &str) -> Ordering {
+    collation_key(a, language)
+        .cmp(&collation_key(b, language))
+        .then_with(|| a.cmp(b))
+}
+
+/// Sort recipes in place, ties are ordered by id
+///
+/// Titles are collated according to the language of each recipe.
+pub fn sort_recipes(recipes: &mut [Recipe], key: SortKey) {
+    match key {
+        SortKey::Id => recipes.sort_by_key(|r| r.data.id),
+        SortKey::TotalTime => recipes.sort_by_key(|r| (r.data.duration_total, r.data.id)),
+        SortKey::Title => recipes.sort_by_cached_key(|r| {
+            (
+                collation_key(&r.data.name, &r.data.language),
+                r.data.name.clone(),
+                r.data.id,
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    fn recipes_named(names: &[&str], language: &str) -> Vec<Recipe> {
+        let json = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+        let recipe: Recipe = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                let mut r = recipe.clone();
+                r.data.id = idx as u32;
+                r.data.name = name.to_string();
+                r.data.language = language.to_string();
+                r
+            })
+            .collect()
+    }
+
+    fn sorted_titles(names: &[&str], language: &str) -> Vec<String> {
+        let mut recipes = recipes_named(names, language);
+        sort_recipes(&mut recipes, SortKey::Title);
+        recipes.into_iter().map(|r| r.data.name).collect()
+    }
+
+    #[test]
+    fn german_umlauts_sort_near_base_letter() {
+        let titles = sorted_titles(
+            &[
+                "Zwiebelkuchen",
+                "Öfen-Kartoffeln",
+                "Apfelstrudel",
+                "Äpfel im Schlafrock",
+                "Ofenkäse",
+            ],
+            "de",
+        );
+
+        assert_eq!(
+            titles,
+            vec![
+                "Äpfel im Schlafrock",
+                "Apfelstrudel",
+                "Öfen-Kartoffeln",
+                "Ofenkäse",
+                "Zwiebelkuchen"
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case::eszett("Masse", "Maße", "de", Ordering::Less)]
+    #[case::umlaut_after_plain("Apfel", "Äpfel", "de", Ordering::Less)]
+    #[case::case_breaks_ties("apfel", "Apfel", "de", Ordering::Less)]
+    #[case::french_accents("crème", "crepe", "fr", Ordering::Less)]
+    #[case::polish_separate_letter("łosoś", "lody", "pl", Ordering::Greater)]
+    #[case::polish_before_next_letter("łosoś", "mak", "pl", Ordering::Less)]
+    #[case::spanish_enye("ñoquis", "nueces", "es", Ordering::Greater)]
+    #[case::enye_elsewhere("ñoquis", "nueces", "de", Ordering::Less)]
+    fn compare(
+        #[case] a: &str,
+        #[case] b: &str,
+        #[case] language: &str,
+        #[case] expected: Ordering,
+    ) {
+        assert_eq!(compare_titles(a, b, language), expected);
+    }
+
+    #[test]
+    fn sort_by_id_and_time() {
+        let mut recipes = recipes_named(&["b", "a", "c"], "de");
+        recipes[0].data.duration_total = 30;
+        recipes[1].data.duration_total = 10;
+        recipes[2].data.duration_total = 20;
+
+        sort_recipes(&mut recipes, SortKey::TotalTime);
+        let ids: Vec<u32> = recipes.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![1, 2, 0]);
+
+        sort_recipes(&mut recipes, SortKey::Id);
+        let ids: Vec<u32> = recipes.iter().map(|r| r.data.id).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,195 @@
+//! Aggregated statistics over a recipe catalog
+use crate::{schemas::Recipe, sort::compare_titles};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CatalogStats {
+    /// Number of recipes
+    pub total: usize,
+    /// Mean of the total duration in minutes, 0 for an empty catalog
+    pub average_total_time: f64,
+    /// Number of recipes per complexity as provided by the server (e.g. "Einfach")
+    pub difficulty_distribution: BTreeMap<String, usize>,
+    /// Mean number of ingredients per recipe, summed over all ingredient bases
+    pub average_ingredient_count: f64,
+    /// Top of [`ingredient_frequency`], most common first
+    pub most_common_ingredients: Vec<(String, usize)>,
+}
+
+impl CatalogStats {
+    /// Number of entries kept in [`CatalogStats::most_common_ingredients`]
+    pub const MOST_COMMON_LIMIT: usize = 10;
+}
+
+/// Compute statistics over already fetched recipes
+///
+/// Ingredients are matched by name case-insensitively and counted once per
+/// recipe, even if listed in multiple ingredient bases.
+pub fn catalog_stats(recipes: &[Recipe]) -> CatalogStats {
+    if recipes.is_empty() {
+        return CatalogStats::default();
+    }
+    let total = recipes.len();
+
+    let mut difficulty_distribution = BTreeMap::new();
+    let mut total_time = 0i64;
+    let mut ingredient_count = 0usize;
+
+    for recipe in recipes {
+        let data = &recipe.data;
+        *difficulty_distribution
+            .entry(data.complexity.clone())
+            .or_insert(0) += 1;
+        total_time += data.duration_total;
+        ingredient_count += data
+            .ingredients_bases
+            .iter()
+            .map(|b| b.ingredients.len())
+            .sum::<usize>();
+    }
+
+    let mut most_common_ingredients = ingredient_frequency(recipes);
+    most_common_ingredients.truncate(CatalogStats::MOST_COMMON_LIMIT);
+
+    CatalogStats {
+        total,
+        average_total_time: total_time as f64 / total as f64,
+        difficulty_distribution,
+        average_ingredient_count: ingredient_count as f64 / total as f64,
+        most_common_ingredients,
+    }
+}
+
+/// Ingredient names and the number of recipes using them, most common first
+///
+/// Names are trimmed, runs of whitespace collapsed and matched
+/// case-insensitively, the spelling seen first is returned. An ingredient
+/// counts once per recipe, even if listed in multiple ingredient bases.
+/// Equally common ingredients are sorted by name.
+pub fn ingredient_frequency(recipes: &[Recipe]) -> Vec<(String, usize)> {
+    // Lowercase name -> (name as first seen, recipes using it)
+    let mut usage: HashMap<String, (String, usize)> = HashMap::new();
+
+    for recipe in recipes {
+        let mut seen = HashSet::new();
+        for ingredient in recipe
+            .data
+            .ingredients_bases
+            .iter()
+            .flat_map(|b| &b.ingredients)
+        {
+            let name = ingredient
+                .name
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let key = name.to_lowercase();
+            if !name.is_empty() && seen.insert(key.clone()) {
+                usage.entry(key).or_insert_with(|| (name, 0)).1 += 1;
+            }
+        }
+    }
+
+    let mut ranking: Vec<(String, usize)> = usage.into_values().collect();
+    ranking.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranking
+}
+
+/// Distinct ingredient names over all recipes, collated for `language`
+///
+/// Names are trimmed and matched case-insensitively, the spelling seen
+/// first is kept. See [`compare_titles`] for the order.
+pub fn distinct_ingredients(recipes: &[Recipe], language: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut names: Vec<String> = recipes
+        .iter()
+        .flat_map(|r| r.data.ingredients_bases.iter())
+        .flat_map(|base| base.ingredients.iter())
+        .map(|ingredient| ingredient.name.trim())
+        .filter(|name| !name.is_empty() && seen.insert(name.to_lowercase()))
+        .map(String::from)
+        .collect();
+
+    names.sort_by(|a, b| compare_titles(a, b, language));
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+
+    #[test]
+    fn catalog_fixture() {
+        let json = get_testdata("recipe_all_catalog.json").expect("Failed to get testdata");
+        let recipes: Vec<Recipe> = serde_json::from_slice(&json).expect("Failed to deserialize");
+
+        let stats = catalog_stats(&recipes);
+
+        assert_eq!(stats.total, 2);
+        assert_eq!(stats.average_total_time, 54.0);
+        assert_eq!(stats.difficulty_distribution["Einfach"], 1);
+        assert_eq!(stats.difficulty_distribution["Mittel"], 1);
+        assert_eq!(stats.average_ingredient_count, 5.0);
+        assert_eq!(
+            stats.most_common_ingredients[..3],
+            [
+                ("Limette".to_string(), 2),
+                ("Vodka".to_string(), 2),
+                ("Gemüsebrühe".to_string(), 1)
+            ]
+        );
+        // Listed twice in the soup, still a single recipe
+        assert!(stats
+            .most_common_ingredients
+            .contains(&("Kartoffeln".to_string(), 1)));
+    }
+
+    #[test]
+    fn empty_catalog() {
+        assert_eq!(catalog_stats(&[]), CatalogStats::default());
+    }
+
+    #[test]
+    fn ingredient_ranking() {
+        let json = get_testdata("recipe_all_catalog.json").expect("Failed to get testdata");
+        let mut recipes: Vec<Recipe> =
+            serde_json::from_slice(&json).expect("Failed to deserialize");
+        recipes[1].data.ingredients_bases[0].ingredients[2].name = "  zwiebel ".into();
+
+        let ranking = ingredient_frequency(&recipes);
+
+        assert_eq!(
+            ranking[..4],
+            [
+                ("Limette".to_string(), 2),
+                ("Vodka".to_string(), 2),
+                ("Zwiebel".to_string(), 2),
+                ("Gemüsebrühe".to_string(), 1),
+            ]
+        );
+        assert_eq!(ranking.iter().map(|(_, n)| n).sum::<usize>(), 9);
+        assert!(ingredient_frequency(&[]).is_empty());
+    }
+
+    #[test]
+    fn distinct_ingredient_names() {
+        let json = get_testdata("recipe_all_catalog.json").expect("Failed to get testdata");
+        let mut recipes: Vec<Recipe> =
+            serde_json::from_slice(&json).expect("Failed to deserialize");
+        recipes[0].data.ingredients_bases[0].ingredients[0].name = " kartoffeln ".into();
+
+        assert_eq!(
+            distinct_ingredients(&recipes, "de"),
+            vec![
+                "Gemüsebrühe",
+                "Ginger beer",
+                "kartoffeln",
+                "Limette",
+                "Salz",
+                "Vodka",
+                "Zwiebel"
+            ]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Cooking temperatures in Celsius or Fahrenheit
+//!
+//! The server sends temperatures as whole degrees Celsius, with 0 for steps
+//! that don't heat. [`Recipe::convert_temperatures`] changes the unit of the
+//! fields, the step remembers it so the accessors below stay correct.
+use crate::schemas::{Recipe, Step};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TempUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TempUnit {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TempUnit::Celsius => "°C",
+            TempUnit::Fahrenheit => "°F",
+        }
+    }
+
+    /// Convert `celsius` into this unit
+    pub fn from_celsius(&self, celsius: f64) -> f64 {
+        match self {
+            TempUnit::Celsius => celsius,
+            TempUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    /// Convert `value` in this unit into Celsius
+    pub fn to_celsius(&self, value: f64) -> f64 {
+        match self {
+            TempUnit::Celsius => value,
+            TempUnit::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+        }
+    }
+}
+
+impl fmt::Display for TempUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Temperature as stored, `None` for 0 which means heating is off
+fn heating(temp: Option<i64>) -> Option<i64> {
+    temp.filter(|t| *t != 0)
+}
+
+/// Convert a field in place, rounded to whole degrees
+fn convert_field(temp: &mut Option<i64>, from: TempUnit, to: TempUnit) {
+    if let Some(value) = heating(*temp) {
+        *temp = Some(to.from_celsius(from.to_celsius(value as f64)).round() as i64);
+    }
+}
+
+impl Step {
+    /// Unit of the `temp` fields of machine values and measurement
+    ///
+    /// Celsius as sent by the server, unless converted with
+    /// [`Recipe::convert_temperatures`]. The unit is not serialized.
+    pub fn temperature_unit(&self) -> TempUnit {
+        self.temp_unit
+    }
+
+    /// Temperature the machine heats to in Celsius, `None` if it doesn't heat
+    ///
+    /// Taken from the machine values, falling back to the measurement.
+    pub fn temperature_celsius(&self) -> Option<f64> {
+        heating(self.machine_values.temp)
+            .or_else(|| heating(self.measurement.temp))
+            .map(|t| self.temp_unit.to_celsius(t as f64))
+    }
+
+    /// [`Step::temperature_celsius`] in Fahrenheit, not rounded
+    pub fn temperature_fahrenheit(&self) -> Option<f64> {
+        self.temperature_celsius()
+            .map(|t| TempUnit::Fahrenheit.from_celsius(t))
+    }
+}
+
+impl Recipe {
+    /// Copy of the recipe with the step temperatures converted into `to`
+    ///
+    /// Temperatures are rounded to whole degrees, as the fields only hold
+    /// integers. Steps without heating keep their 0. Steps already in `to`
+    /// are left as they are, see [`Step::temperature_unit`]. Temperatures
+    /// mentioned in the step text are not touched.
+    pub fn convert_temperatures(&self, to: TempUnit) -> Recipe {
+        let mut recipe = self.clone();
+        for step in recipe.data.guided_cooking.steps.iter_mut() {
+            convert_field(&mut step.machine_values.temp, step.temp_unit, to);
+            convert_field(&mut step.measurement.temp, step.temp_unit, to);
+            step.temp_unit = to;
+        }
+        recipe
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    #[fixture]
+    fn recipe() -> Recipe {
+        serde_json::from_slice(
+            &get_testdata("recipe_single_25012.json").expect("Failed to get testdata"),
+        )
+        .expect("Failed to parse recipe")
+    }
+
+    #[rstest]
+    #[case::freezing(0.0, 32.0)]
+    #[case::boiling(100.0, 212.0)]
+    #[case::body(37.0, 98.6)]
+    #[case::negative(-40.0, -40.0)]
+    fn from_celsius(#[case] celsius: f64, #[case] fahrenheit: f64) {
+        assert!((TempUnit::Fahrenheit.from_celsius(celsius) - fahrenheit).abs() < 1e-9);
+        assert_eq!(TempUnit::Celsius.from_celsius(celsius), celsius);
+    }
+
+    #[rstest]
+    fn step_temperature(recipe: Recipe) {
+        let temps: Vec<Option<f64>> = recipe
+            .data
+            .guided_cooking
+            .steps
+            .iter()
+            .map(Step::temperature_fahrenheit)
+            .collect();
+
+        assert_eq!(temps, vec![None, None, Some(212.0), None]);
+    }
+
+    #[rstest]
+    fn step_temperature_measurement(recipe: Recipe) {
+        let mut step = recipe.data.guided_cooking.steps[0].clone();
+        step.machine_values.temp = None;
+        step.measurement.temp = Some(37);
+
+        assert_eq!(step.temperature_celsius(), Some(37.0));
+        assert!((step.temperature_fahrenheit().unwrap() - 98.6).abs() < 1e-9);
+    }
+
+    #[rstest]
+    fn convert_temperatures(mut recipe: Recipe) {
+        recipe.data.guided_cooking.steps[1].machine_values.temp = Some(37);
+        recipe.data.guided_cooking.steps[1].measurement.temp = None;
+
+        let converted = recipe.convert_temperatures(TempUnit::Fahrenheit);
+
+        let temps: Vec<Option<i64>> = converted
+            .data
+            .guided_cooking
+            .steps
+            .iter()
+            .map(|s| s.machine_values.temp)
+            .collect();
+        assert_eq!(temps, vec![Some(0), Some(99), Some(212), Some(0)]);
+        assert_eq!(
+            converted.data.guided_cooking.steps[1].measurement.temp,
+            None
+        );
+        assert_eq!(recipe.convert_temperatures(TempUnit::Celsius), recipe);
+    }
+
+    #[rstest]
+    fn converted_accessors(recipe: Recipe) {
+        let converted = recipe.convert_temperatures(TempUnit::Fahrenheit);
+        let step = &converted.data.guided_cooking.steps[2];
+
+        assert_eq!(step.temperature_unit(), TempUnit::Fahrenheit);
+        assert_eq!(step.machine_values.temp, Some(212));
+        assert_eq!(step.temperature_celsius(), Some(100.0));
+        assert_eq!(step.temperature_fahrenheit(), Some(212.0));
+        assert_eq!(
+            converted.convert_temperatures(TempUnit::Fahrenheit),
+            converted
+        );
+        assert_eq!(converted.convert_temperatures(TempUnit::Celsius), recipe);
+    }
+}
@@ -0,0 +1,156 @@
+//! Plain text from the HTML markup found in recipe text fields
+//!
+//! Only tags and the common entities are handled, this is no HTML parser.
+//! The raw markup stays available in the recipe fields themselves.
+use crate::schemas::Recipe;
+
+/// Named entities decoded by [`strip_html`], others are kept verbatim
+const ENTITIES: &[(&str, &str)] = &[
+    ("amp", "&"),
+    ("lt", "<"),
+    ("gt", ">"),
+    ("quot", "\""),
+    ("apos", "'"),
+    ("nbsp", " "),
+    ("ndash", "–"),
+    ("mdash", "—"),
+    ("deg", "°"),
+];
+
+/// Tags separating text, replaced by a space instead of just being removed
+const BREAKING_TAGS: &[&str] = &[
+    "br", "p", "div", "li", "ul", "ol", "tr", "td", "h1", "h2", "h3",
+];
+
+/// Longest entity name looked for, `&` further away from `;` is literal
+const MAX_ENTITY_LEN: usize = 10;
+
+fn decode_entity(entity: &str) -> Option<String> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return std::char::from_u32(code).map(String::from);
+    }
+
+    ENTITIES
+        .iter()
+        .find(|(name, _)| *name == entity)
+        .map(|(_, decoded)| decoded.to_string())
+}
+
+/// Remove tags, decode entities and collapse whitespace
+///
+/// Line and block level tags like `<br>` or `<p>` are replaced by a space,
+/// so `a<br>b` doesn't become `ab`. Inline tags are just removed.
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(idx) = rest.find(['<', '&']) {
+        text.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+
+        if rest.starts_with('<') {
+            match rest.find('>') {
+                Some(end) => {
+                    let name = rest[1..end]
+                        .trim_start_matches('/')
+                        .split(|c: char| c.is_whitespace() || c == '/')
+                        .next()
+                        .unwrap_or_default()
+                        .to_lowercase();
+                    if BREAKING_TAGS.contains(&name.as_str()) {
+                        text.push(' ');
+                    }
+                    rest = &rest[end + 1..];
+                }
+                // Unterminated tag, not markup
+                None => {
+                    text.push_str(rest);
+                    rest = "";
+                }
+            }
+            continue;
+        }
+
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|end| *end <= MAX_ENTITY_LEN)
+            .and_then(|end| Some((end, decode_entity(&rest[1..end + 1])?)));
+        match decoded {
+            Some((end, decoded)) => {
+                text.push_str(&decoded);
+                rest = &rest[end + 2..];
+            }
+            None => {
+                text.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl Recipe {
+    /// Text of the guided cooking steps without HTML markup
+    ///
+    /// Steps without text, e.g. pure machine steps, are skipped. The raw
+    /// text is still available as [`crate::schemas::Step::text`].
+    pub fn plain_text_steps(&self) -> Vec<String> {
+        self.data
+            .guided_cooking
+            .steps
+            .iter()
+            .map(|step| strip_html(&step.text))
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    #[rstest]
+    #[case::plain("Vodka hinzugeben.", "Vodka hinzugeben.")]
+    #[case::tags("<p>Limette <b>pressen</b></p>", "Limette pressen")]
+    #[case::line_break("Vodka<br/>Eis", "Vodka Eis")]
+    #[case::attributes(r#"<span class="x">Eis</span>"#, "Eis")]
+    #[case::inline("Ko<b>ch</b>en", "Kochen")]
+    #[case::block("<li>Eis</li><li>Vodka</li>", "Eis Vodka")]
+    #[case::named("Salz &amp; Pfeffer", "Salz & Pfeffer")]
+    #[case::numeric("&#72;i &#x2713;", "Hi ✓")]
+    #[case::escaped_tag("&lt;b&gt;", "<b>")]
+    #[case::unknown_entity("&foo; bar", "&foo; bar")]
+    #[case::bare_ampersand("Salz & Pfeffer", "Salz & Pfeffer")]
+    #[case::unterminated("1 < 2", "1 < 2")]
+    #[case::whitespace("  a \n\t b  ", "a b")]
+    fn strip_html(#[case] html: &str, #[case] expected: &str) {
+        assert_eq!(super::strip_html(html), expected);
+    }
+
+    #[test]
+    fn plain_text_steps() {
+        let recipe: Recipe = serde_json::from_slice(
+            &get_testdata("recipe_single_25011_html.json").expect("Failed to get testdata"),
+        )
+        .expect("Failed to parse recipe");
+
+        assert_eq!(
+            recipe.plain_text_steps(),
+            vec![
+                "Limettensaft auspressen und in den Topf geben.",
+                "Vodka & Eis hinzugeben.",
+                "Ginger Beer hinzugeben – <kalt>.",
+                "\"Ginger Beer\" hinzugeben! ✓",
+            ]
+        );
+        assert!(recipe.data.guided_cooking.steps[0].text.contains("<b>"));
+    }
+}
@@ -0,0 +1,141 @@
+//! HTTP backend requests are sent through
+//!
+//! [`ReqwestTransport`] is used unless another transport is passed to
+//! [`crate::api::ApiBuilder::transport`]. Requests are still assembled with
+//! reqwest types, a custom transport only has to turn a [`reqwest::Request`]
+//! into a [`reqwest::Response`], e.g. from an in-memory fixture via
+//! `reqwest::Response::from(http::Response<_>)`.
+use crate::{error::MccError, Result};
+use std::{fmt, future::Future, pin::Pin};
+
+#[cfg(all(unix, feature = "uds"))]
+pub mod unix;
+#[cfg(feature = "vcr")]
+pub mod vcr;
+
+/// Future returned by [`HttpTransport::send`]
+pub type Sending = Pin<Box<dyn Future<Output = Result<reqwest::Response>> + Send>>;
+
+pub trait HttpTransport: fmt::Debug + Send + Sync {
+    /// Send `request` and resolve once the response headers arrived
+    ///
+    /// Non-success statuses are not an error at this level.
+    fn send(&self, request: reqwest::Request) -> Sending;
+}
+
+/// Network transport backed by a [`reqwest::Client`]
+///
+/// Transport errors are classified into [`MccError`] variants.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: reqwest::Request) -> Sending {
+        let response = self.client.execute(request);
+        Box::pin(async move { response.await.map_err(MccError::from_transport) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Api;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers every request with the same body, remembering the requests
+    #[derive(Debug, Default)]
+    struct MockTransport {
+        body: &'static str,
+        requests: Mutex<Vec<(reqwest::Method, String, Option<String>)>>,
+    }
+
+    impl HttpTransport for MockTransport {
+        fn send(&self, request: reqwest::Request) -> Sending {
+            let recipe_type = request
+                .headers()
+                .get("X-Recipe-Type")
+                .and_then(|v| v.to_str().ok())
+                .map(String::from);
+            self.requests.lock().unwrap().push((
+                request.method().clone(),
+                request.url().path().to_string(),
+                recipe_type,
+            ));
+
+            let response = http::Response::builder()
+                .status(200)
+                .header("content-type", "application/json")
+                .body(self.body)
+                .expect("Failed to build response");
+            Box::pin(futures::future::ready(Ok(response.into())))
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_transport() {
+        let transport = Arc::new(MockTransport {
+            body: r#"{"ids":[25011,25012]}"#,
+            ..Default::default()
+        });
+        let client = Api::builder()
+            .transport(transport.clone())
+            .build()
+            .expect("Failed to build Api");
+
+        let ids = client
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to get ids");
+
+        assert_eq!(ids, vec![25011, 25012]);
+        assert_eq!(
+            *transport.requests.lock().unwrap(),
+            vec![(
+                reqwest::Method::GET,
+                "/mcc/api/v1/recipe/ids".to_string(),
+                Some("default".to_string())
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn mock_transport_status() {
+        #[derive(Debug)]
+        struct Unavailable;
+
+        impl HttpTransport for Unavailable {
+            fn send(&self, _: reqwest::Request) -> Sending {
+                let response = http::Response::builder()
+                    .status(503)
+                    .body("")
+                    .expect("Failed to build response");
+                Box::pin(futures::future::ready(Ok(response.into())))
+            }
+        }
+
+        let client = Api::builder()
+            .transport(Arc::new(Unavailable))
+            .build()
+            .expect("Failed to build Api");
+
+        let err = client
+            .get_recipe(25011, "de", None)
+            .await
+            .expect_err("503 should fail");
+
+        assert_eq!(
+            MccError::without_context(err.as_ref())
+                .downcast_ref::<reqwest::Error>()
+                .and_then(|e| e.status()),
+            Some(reqwest::StatusCode::SERVICE_UNAVAILABLE)
+        );
+    }
+}
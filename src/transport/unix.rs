@@ -0,0 +1,209 @@
+//! Transport sending every request over a Unix domain socket
+//!
+//! Used by [`crate::api::ApiBuilder::unix_socket`]. The URL of a request
+//! still decides path, query and `Host` header, only the connection goes to
+//! the socket instead of the network.
+use super::{HttpTransport, Sending};
+use hyper::{
+    client::connect::{Connected, Connection},
+    service::Service,
+};
+use std::{
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::UnixStream,
+};
+
+/// Opens a new connection to the socket for every connection hyper asks for
+#[derive(Debug, Clone)]
+struct UnixConnector {
+    path: Arc<PathBuf>,
+}
+
+struct UnixConnection(UnixStream);
+
+impl Connection for UnixConnection {
+    fn connected(&self) -> Connected {
+        Connected::new()
+    }
+}
+
+impl AsyncRead for UnixConnection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for UnixConnection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_shutdown(cx)
+    }
+}
+
+impl Service<hyper::Uri> for UnixConnector {
+    type Response = UnixConnection;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<UnixConnection>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: hyper::Uri) -> Self::Future {
+        let path = self.path.clone();
+        Box::pin(async move {
+            match UnixStream::connect(path.as_path()).await {
+                Ok(stream) => Ok(UnixConnection(stream)),
+                Err(err) => Err(io::Error::new(
+                    err.kind(),
+                    format!("Failed to connect to {}: {}", path.display(), err),
+                )),
+            }
+        })
+    }
+}
+
+/// HTTP/1 over the Unix domain socket at a path, only available on Unix
+///
+/// Request bodies have to be in memory, streaming bodies are rejected. The
+/// response body is streamed. Per-request timeouts are honored, client-wide
+/// reqwest settings like proxies or DNS overrides don't apply.
+#[derive(Debug, Clone)]
+pub struct UnixSocketTransport {
+    client: hyper::Client<UnixConnector, hyper::Body>,
+}
+
+impl UnixSocketTransport {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let connector = UnixConnector {
+            path: Arc::new(path.as_ref().to_path_buf()),
+        };
+        Self {
+            client: hyper::Client::builder().build(connector),
+        }
+    }
+}
+
+impl HttpTransport for UnixSocketTransport {
+    fn send(&self, request: reqwest::Request) -> Sending {
+        let client = self.client.clone();
+
+        Box::pin(async move {
+            let body = match request.body() {
+                Some(body) => body
+                    .as_bytes()
+                    .ok_or("Streaming request bodies are not supported over unix sockets")?
+                    .to_vec(),
+                None => vec![],
+            };
+            let mut outgoing = hyper::Request::builder()
+                .method(request.method().clone())
+                .uri(request.url().as_str())
+                .body(hyper::Body::from(body))?;
+            *outgoing.headers_mut() = request.headers().clone();
+
+            let response = client.request(outgoing);
+            let response = match request.timeout() {
+                Some(timeout) => tokio::time::timeout(*timeout, response)
+                    .await
+                    .map_err(|_| format!("Request timed out after {:?}", timeout))??,
+                None => response.await?,
+            };
+
+            let (parts, body) = response.into_parts();
+            Ok(hyper::Response::from_parts(parts, reqwest::Body::wrap_stream(body)).into())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{api::Api, tests::get_testdata};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixListener,
+    };
+
+    #[tokio::test]
+    async fn get_recipe() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("mcc.sock");
+        let listener = UnixListener::bind(&path).expect("Failed to bind socket");
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("Failed to accept");
+            let mut request = vec![];
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.expect("Failed to read");
+                request.extend_from_slice(&buf[..n]);
+            }
+
+            let body = get_testdata("recipe_single_25011.json").expect("Failed to get testdata");
+            let head = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                body.len()
+            );
+            stream
+                .write_all(head.as_bytes())
+                .await
+                .expect("Failed to write");
+            stream.write_all(&body).await.expect("Failed to write");
+            String::from_utf8(request).expect("Request is no UTF-8")
+        });
+
+        let client = Api::builder()
+            .unix_socket(&path)
+            .build()
+            .expect("Failed to build Api");
+        let recipe = client
+            .get_recipe(25011, "de", None)
+            .await
+            .expect("Failed to get recipe");
+
+        assert_eq!(recipe.data.id, 25011);
+        let request = server.await.expect("Server failed");
+        assert!(request.starts_with("GET /mcc/api/v1/recipe/25011 HTTP/1.1\r\n"));
+        assert!(request.to_lowercase().contains("x-recipe-type: default"));
+    }
+
+    #[tokio::test]
+    async fn missing_socket() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("missing.sock");
+        let client = Api::builder()
+            .unix_socket(&path)
+            .build()
+            .expect("Failed to build Api");
+
+        let err = client
+            .get_recipe(25011, "de", None)
+            .await
+            .expect_err("Missing socket should fail");
+
+        let message = format!("{:?}", err);
+        assert!(message.contains("missing.sock"), "{}", message);
+    }
+}
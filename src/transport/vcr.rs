@@ -0,0 +1,524 @@
+//! Record HTTP sessions to a cassette file and replay them without network
+//!
+//! Wrap a transport in [`RecordingTransport`] to capture a live session
+//! once, then pass a [`ReplayTransport`] loaded from the same cassette to
+//! [`crate::api::ApiBuilder::transport`] for deterministic tests.
+//!
+//! Request headers are not recorded, so credentials don't end up in the
+//! cassette. Query parameters of the request, response headers and body
+//! fields are masked as configured with [`RecordingTransport::redact`].
+//! Response bodies are read completely before they are handed on.
+use super::{HttpTransport, Sending};
+use crate::{files, redact::Redaction, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        files::write_atomic(path, &serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// Request and the response the server sent for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub url: String,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Body,
+}
+
+/// Response body, text if it is valid UTF-8 to keep cassettes readable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Body {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Body {
+    fn new(bytes: Vec<u8>) -> Self {
+        match String::from_utf8(bytes) {
+            Ok(text) => Body::Text(text),
+            Err(err) => Body::Bytes(err.into_bytes()),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Body::Text(text) => text.clone().into_bytes(),
+            Body::Bytes(bytes) => bytes.clone(),
+        }
+    }
+}
+
+/// Path and query of `url`, for messages
+fn request_target(url: &reqwest::Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+/// Path and query of a request, what replayed requests are matched on
+#[derive(Debug)]
+struct Target {
+    path: String,
+    query: Vec<(String, String)>,
+}
+
+impl Target {
+    fn of(url: &reqwest::Url) -> Self {
+        Self {
+            path: url.path().to_string(),
+            query: url.query_pairs().into_owned().collect(),
+        }
+    }
+
+    /// Whether `request` is what was recorded, a redacted value matches any value
+    fn matches(&self, request: &Target) -> bool {
+        self.path == request.path
+            && self.query.len() == request.query.len()
+            && self.query.iter().zip(&request.query).all(
+                |((name, value), (request_name, request_value))| {
+                    name == request_name
+                        && (value == request_value || value == Redaction::PLACEHOLDER)
+                },
+            )
+    }
+}
+
+impl RecordedResponse {
+    async fn record(response: reqwest::Response) -> Result<Self> {
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        let body = Body::new(response.bytes().await?.to_vec());
+
+        Ok(Self {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// Copy with sensitive headers and body fields masked, for the cassette
+    ///
+    /// `Content-Length` is set to the length of the masked body.
+    fn redacted(&self, redaction: &Redaction) -> Self {
+        let body = match &self.body {
+            Body::Text(text) => Body::new(redaction.redact_body(text.as_bytes()).into_owned()),
+            Body::Bytes(bytes) => Body::Bytes(bytes.clone()),
+        };
+        let length = match &body {
+            Body::Text(text) => text.len(),
+            Body::Bytes(bytes) => bytes.len(),
+        };
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if redaction.is_redacted_header(name) {
+                    Redaction::PLACEHOLDER.to_string()
+                } else if name.eq_ignore_ascii_case("content-length") {
+                    length.to_string()
+                } else {
+                    value.clone()
+                };
+                (name.clone(), value)
+            })
+            .collect();
+
+        Self {
+            status: self.status,
+            headers,
+            body,
+        }
+    }
+
+    fn to_response(&self) -> Result<reqwest::Response> {
+        let mut response = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            response = response.header(name, value);
+        }
+        Ok(response.body(self.body.to_bytes())?.into())
+    }
+}
+
+/// Sends through another transport, appending every exchange to a cassette
+///
+/// The cassette file is rewritten after each response, so a session that
+/// is interrupted keeps what was recorded so far. Writing happens off the
+/// async executor and without blocking concurrent requests. Failed
+/// requests, e.g. timeouts, are not recorded.
+#[derive(Debug)]
+pub struct RecordingTransport {
+    inner: Arc<dyn HttpTransport>,
+    path: PathBuf,
+    cassette: Arc<Mutex<Cassette>>,
+    /// Taken while writing the file, so an older state never replaces a newer one
+    writing: Arc<tokio::sync::Mutex<()>>,
+    redaction: Arc<Redaction>,
+}
+
+impl RecordingTransport {
+    /// Record into a new cassette at `path`, replacing an existing file
+    pub fn new(inner: Arc<dyn HttpTransport>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            cassette: Default::default(),
+            writing: Default::default(),
+            redaction: Default::default(),
+        }
+    }
+
+    /// Mask query parameters, headers and JSON fields in the cassette
+    ///
+    /// Defaults to [`Redaction::new`], which masks cookies and credentials.
+    /// Responses are handed on unmasked. Redacted query parameters match any
+    /// value when replayed.
+    pub fn redact(mut self, redaction: Redaction) -> Self {
+        self.redaction = Arc::new(redaction);
+        self
+    }
+
+    /// Everything recorded so far
+    pub fn cassette(&self) -> Cassette {
+        self.cassette.lock().unwrap().clone()
+    }
+}
+
+impl HttpTransport for RecordingTransport {
+    fn send(&self, request: reqwest::Request) -> Sending {
+        let method = request.method().to_string();
+        let url = self.redaction.redact_url(request.url()).to_string();
+        let sending = self.inner.send(request);
+        let path = self.path.clone();
+        let cassette = self.cassette.clone();
+        let writing = self.writing.clone();
+        let redaction = self.redaction.clone();
+
+        Box::pin(async move {
+            let response = RecordedResponse::record(sending.await?).await?;
+            let replayed = response.to_response()?;
+
+            cassette.lock().unwrap().interactions.push(Interaction {
+                method,
+                url,
+                response: response.redacted(&redaction),
+            });
+
+            // Serialized once the file is ours, to include everything recorded until then
+            let _writing = writing.lock().await;
+            let json = serde_json::to_vec_pretty(&*cassette.lock().unwrap())?;
+            files::write_atomic_async(path, json).await?;
+
+            Ok(replayed)
+        })
+    }
+}
+
+/// Answers requests from a cassette, never touching the network
+///
+/// Requests are matched on method, path and query, the host is ignored.
+/// Redacted query parameters match any value. Repeated requests get the
+/// recorded responses in order, once those are used up the last one is
+/// served again. Unmatched requests fail.
+#[derive(Debug)]
+pub struct ReplayTransport {
+    /// Recorded interactions with the request target, and whether they were served
+    interactions: Mutex<Vec<(Target, Interaction, bool)>>,
+}
+
+impl ReplayTransport {
+    pub fn new(cassette: Cassette) -> Self {
+        let interactions = cassette
+            .interactions
+            .into_iter()
+            .map(|interaction| {
+                let target = reqwest::Url::parse(&interaction.url)
+                    .map(|url| Target::of(&url))
+                    .unwrap_or_else(|_| Target {
+                        path: interaction.url.clone(),
+                        query: vec![],
+                    });
+                (target, interaction, false)
+            })
+            .collect();
+
+        Self {
+            interactions: Mutex::new(interactions),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+
+    fn replay(&self, request: &reqwest::Request) -> Result<reqwest::Response> {
+        let method = request.method().as_str();
+        let target = Target::of(request.url());
+        let mut interactions = self.interactions.lock().unwrap();
+
+        let matches = |(t, interaction, _): &(Target, Interaction, bool)| {
+            t.matches(&target) && interaction.method == method
+        };
+        let next = interactions
+            .iter()
+            .position(|i| matches(i) && !i.2)
+            .or_else(|| interactions.iter().rposition(matches));
+
+        match next {
+            Some(idx) => {
+                interactions[idx].2 = true;
+                interactions[idx].1.response.to_response()
+            }
+            None => Err(format!(
+                "No recorded response for {} {}",
+                method,
+                request_target(request.url())
+            )
+            .into()),
+        }
+    }
+}
+
+impl HttpTransport for ReplayTransport {
+    fn send(&self, request: reqwest::Request) -> Sending {
+        Box::pin(futures::future::ready(self.replay(&request)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::Api, tests::get_testdata, transport::ReqwestTransport};
+    use mockito::mock;
+
+    #[tokio::test]
+    async fn record_replay() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("session.json");
+        let live = mock("GET", "/mcc/api/v1/recipe/25011")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(get_testdata("recipe_single_25011.json").expect("Failed to get testdata"))
+            .expect(1)
+            .create();
+
+        let recording = Api::builder()
+            .transport(Arc::new(RecordingTransport::new(
+                Arc::new(ReqwestTransport::default()),
+                &path,
+            )))
+            .build()
+            .expect("Failed to build Api");
+        let recorded = recording
+            .get_recipe(25011, "de", None)
+            .await
+            .expect("Failed to record recipe");
+
+        let replaying = Api::builder()
+            .transport(Arc::new(
+                ReplayTransport::load(&path).expect("Failed to load cassette"),
+            ))
+            .build()
+            .expect("Failed to build Api");
+        let replayed = replaying
+            .get_recipe(25011, "de", None)
+            .await
+            .expect("Failed to replay recipe");
+
+        assert_eq!(replayed, recorded);
+        live.assert();
+        replaying
+            .get_recipe(25012, "de", None)
+            .await
+            .expect_err("Unrecorded request should fail");
+    }
+
+    #[tokio::test]
+    async fn record_redacted() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("session.json");
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("x-session-user", "u-4711")
+            .with_body(r#"{"ids":[25011],"uid":"u-4711"}"#)
+            .create();
+
+        let client = Api::builder()
+            .transport(Arc::new(
+                RecordingTransport::new(Arc::new(ReqwestTransport::default()), &path)
+                    .redact(Redaction::new().header("X-Session-User").json_field("uid")),
+            ))
+            .build()
+            .expect("Failed to build Api");
+        let ids = client
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to record ids");
+
+        assert_eq!(ids, vec![25011]);
+        let cassette = std::fs::read_to_string(&path).expect("Cassette not written");
+        assert!(!cassette.contains("u-4711"));
+        let response = &Cassette::load(&path)
+            .expect("Failed to load cassette")
+            .interactions[0]
+            .response;
+        assert!(response
+            .headers
+            .contains(&("x-session-user".to_string(), "[redacted]".to_string())));
+        assert_eq!(
+            response.body,
+            Body::Text(r#"{"ids":[25011],"uid":"[redacted]"}"#.to_string())
+        );
+        let length = r#"{"ids":[25011],"uid":"[redacted]"}"#.len().to_string();
+        assert!(response
+            .headers
+            .contains(&("content-length".to_string(), length)));
+    }
+
+    #[tokio::test]
+    async fn record_redacted_query() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("session.json");
+        let _m = mock("GET", "/mcc/api/v1/recipe/ids")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "token".to_string(),
+                "t-4711".to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"ids":[25011]}"#)
+            .create();
+
+        let recording = Api::builder()
+            .query_param("token", "t-4711")
+            .transport(Arc::new(RecordingTransport::new(
+                Arc::new(ReqwestTransport::default()),
+                &path,
+            )))
+            .build()
+            .expect("Failed to build Api");
+        recording
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to record ids");
+
+        let cassette = std::fs::read_to_string(&path).expect("Cassette not written");
+        assert!(!cassette.contains("t-4711"));
+
+        // Redacted values match whatever is sent on replay
+        let replaying = Api::builder()
+            .query_param("token", "t-0815")
+            .transport(Arc::new(
+                ReplayTransport::load(&path).expect("Failed to load cassette"),
+            ))
+            .build()
+            .expect("Failed to build Api");
+        let ids = replaying
+            .get_recipe_ids("de", None)
+            .await
+            .expect("Failed to replay ids");
+        assert_eq!(ids, vec![25011]);
+        replaying
+            .with_query_param("lang", "de")
+            .get_recipe_ids("de", None)
+            .await
+            .expect_err("Request with other parameters should not match");
+    }
+
+    #[tokio::test]
+    async fn record_concurrently() {
+        let dir = tempfile::tempdir().expect("Failed to create tempdir");
+        let path = dir.path().join("session.json");
+        let _m = mock(
+            "GET",
+            mockito::Matcher::Regex(r"^/mcc/api/v1/recipe/\d+$".to_string()),
+        )
+        .with_status(404)
+        .create();
+
+        let client = Api::builder()
+            .transport(Arc::new(RecordingTransport::new(
+                Arc::new(ReqwestTransport::default()),
+                &path,
+            )))
+            .build()
+            .expect("Failed to build Api");
+        let ids: Vec<u32> = (1..=16).collect();
+        let results = client.get_recipes_by_ids_settled(&ids, "de", None).await;
+        assert!(results.iter().all(|res| res.is_err()));
+
+        let mut urls: Vec<String> = Cassette::load(&path)
+            .expect("Failed to load cassette")
+            .interactions
+            .into_iter()
+            .map(|interaction| interaction.url)
+            .collect();
+        urls.sort();
+        urls.dedup();
+        assert_eq!(urls.len(), ids.len());
+        assert_eq!(
+            fs::read_dir(dir.path()).expect("Failed to list").count(),
+            1,
+            "Temporary files left behind"
+        );
+    }
+
+    #[test]
+    fn replay_order() {
+        let interaction = |status| Interaction {
+            method: "GET".to_string(),
+            url: "https://example.com/versions.txt".to_string(),
+            response: RecordedResponse {
+                status,
+                headers: vec![],
+                body: Body::Bytes(vec![0xff]),
+            },
+        };
+        let transport = ReplayTransport::new(Cassette {
+            interactions: vec![interaction(503), interaction(200)],
+        });
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            "http://localhost/versions.txt".parse().unwrap(),
+        );
+
+        let statuses: Vec<u16> = (0..3)
+            .map(|_| transport.replay(&request).unwrap().status().as_u16())
+            .collect();
+
+        assert_eq!(statuses, vec![503, 200, 200]);
+    }
+}
@@ -0,0 +1,195 @@
+//! Typed ingredient units, parsed from the localized free text the server sends
+use crate::schemas::Recipe;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Milligram,
+    Gram,
+    Kilogram,
+    Milliliter,
+    Centiliter,
+    Liter,
+    Teaspoon,
+    Tablespoon,
+    Pinch,
+    Piece,
+    Package,
+    /// Unit not known to this crate, trimmed raw string as sent by the server
+    Unknown(String),
+}
+
+impl Unit {
+    /// Parse German or English unit names and abbreviations, case-insensitive
+    pub fn parse(s: &str) -> Unit {
+        let raw = s.trim();
+        let normalized = raw.trim_end_matches('.').to_lowercase();
+
+        match normalized.as_str() {
+            "mg" | "milligramm" | "milligram" | "milligrams" => Unit::Milligram,
+            "g" | "gr" | "gramm" | "gram" | "grams" | "gramme" => Unit::Gram,
+            "kg" | "kilogramm" | "kilogram" | "kilograms" => Unit::Kilogram,
+            "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => Unit::Milliliter,
+            "cl" | "zentiliter" | "centiliter" | "centiliters" | "centilitre" => Unit::Centiliter,
+            "l" | "liter" | "liters" | "litre" | "litres" => Unit::Liter,
+            "tl" | "teelöffel" | "tsp" | "teaspoon" | "teaspoons" => Unit::Teaspoon,
+            "el" | "esslöffel" | "tbsp" | "tablespoon" | "tablespoons" => Unit::Tablespoon,
+            "prise" | "prisen" | "pinch" | "pinches" => Unit::Pinch,
+            "stk" | "stück" | "pc" | "pcs" | "piece" | "pieces" => Unit::Piece,
+            "pck" | "päckchen" | "packung" | "pack" | "package" | "packet" => Unit::Package,
+            _ => Unit::Unknown(raw.to_string()),
+        }
+    }
+
+    /// Convert into the base unit of its dimension, returning base unit and factor
+    ///
+    /// Weights map onto gram, volumes onto milliliter. Other units are
+    /// their own base unit.
+    pub fn to_base(&self) -> (Unit, f64) {
+        match self {
+            Unit::Milligram => (Unit::Gram, 0.001),
+            Unit::Kilogram => (Unit::Gram, 1000.0),
+            Unit::Centiliter => (Unit::Milliliter, 10.0),
+            Unit::Liter => (Unit::Milliliter, 1000.0),
+            other => (other.clone(), 1.0),
+        }
+    }
+
+    /// Short, language independent symbol, the raw string for unknown units
+    pub fn symbol(&self) -> &str {
+        match self {
+            Unit::Milligram => "mg",
+            Unit::Gram => "g",
+            Unit::Kilogram => "kg",
+            Unit::Milliliter => "ml",
+            Unit::Centiliter => "cl",
+            Unit::Liter => "l",
+            Unit::Teaspoon => "tsp",
+            Unit::Tablespoon => "tbsp",
+            Unit::Pinch => "pinch",
+            Unit::Piece => "pcs",
+            Unit::Package => "pkg",
+            Unit::Unknown(raw) => raw,
+        }
+    }
+}
+
+impl From<&str> for Unit {
+    fn from(s: &str) -> Self {
+        Unit::parse(s)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Raw unit strings that parse to [`Unit::Unknown`], per recipe id
+///
+/// Empty units, as used for countable ingredients like eggs, are not
+/// reported. Each unit is listed once per recipe, in order of appearance.
+/// Recipes without unknown units are left out.
+pub fn find_unparseable_units(recipes: &[Recipe]) -> Vec<(u32, Vec<String>)> {
+    recipes
+        .iter()
+        .filter_map(|recipe| {
+            let mut units: Vec<String> = vec![];
+            for ingredient in recipe
+                .data
+                .ingredients_bases
+                .iter()
+                .flat_map(|base| &base.ingredients)
+            {
+                if let Unit::Unknown(raw) = ingredient.typed_unit() {
+                    if !raw.is_empty() && !units.contains(&raw) {
+                        units.push(raw);
+                    }
+                }
+            }
+            if units.is_empty() {
+                None
+            } else {
+                Some((recipe.data.id, units))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_testdata;
+    use rstest::*;
+
+    #[rstest]
+    #[case::g("g", Unit::Gram)]
+    #[case::gramm("Gramm", Unit::Gram)]
+    #[case::kg("kg", Unit::Kilogram)]
+    #[case::ml("ml", Unit::Milliliter)]
+    #[case::liter("Liter", Unit::Liter)]
+    #[case::el("EL", Unit::Tablespoon)]
+    #[case::essloeffel("Esslöffel", Unit::Tablespoon)]
+    #[case::tl("TL", Unit::Teaspoon)]
+    #[case::prise("Prise", Unit::Pinch)]
+    #[case::stk("Stk", Unit::Piece)]
+    #[case::stk_dot("Stk.", Unit::Piece)]
+    #[case::pck("Pck.", Unit::Package)]
+    fn parse_german(#[case] s: &str, #[case] expected: Unit) {
+        assert_eq!(Unit::parse(s), expected);
+    }
+
+    #[rstest]
+    #[case::grams("grams", Unit::Gram)]
+    #[case::kilogram("Kilogram", Unit::Kilogram)]
+    #[case::millilitre("millilitre", Unit::Milliliter)]
+    #[case::litres("litres", Unit::Liter)]
+    #[case::tbsp("tbsp", Unit::Tablespoon)]
+    #[case::teaspoons("teaspoons", Unit::Teaspoon)]
+    #[case::pinch("pinch", Unit::Pinch)]
+    #[case::pcs("pcs", Unit::Piece)]
+    #[case::packet("packet", Unit::Package)]
+    fn parse_english(#[case] s: &str, #[case] expected: Unit) {
+        assert_eq!(Unit::parse(s), expected);
+    }
+
+    #[rstest]
+    #[case::bund(" Bund ", "Bund")]
+    #[case::empty("", "")]
+    fn parse_unknown_keeps_raw(#[case] s: &str, #[case] raw: &str) {
+        assert_eq!(Unit::parse(s), Unit::Unknown(raw.to_string()));
+    }
+
+    #[rstest]
+    #[case::mg(Unit::Milligram, Unit::Gram, 0.001)]
+    #[case::kg(Unit::Kilogram, Unit::Gram, 1000.0)]
+    #[case::cl(Unit::Centiliter, Unit::Milliliter, 10.0)]
+    #[case::l(Unit::Liter, Unit::Milliliter, 1000.0)]
+    #[case::el(Unit::Tablespoon, Unit::Tablespoon, 1.0)]
+    fn to_base(#[case] unit: Unit, #[case] base: Unit, #[case] factor: f64) {
+        assert_eq!(unit.to_base(), (base, factor));
+    }
+
+    #[test]
+    fn unparseable_units() {
+        let recipes: Vec<Recipe> = [
+            "recipe_single_25011.json",
+            "recipe_single_25012_exotic_units.json",
+        ]
+        .iter()
+        .map(|name| {
+            let json = get_testdata(name).expect("Failed to get testdata");
+            serde_json::from_slice(&json).expect("Failed to deserialize")
+        })
+        .collect();
+
+        assert_eq!(
+            find_unparseable_units(&recipes),
+            vec![(
+                25012,
+                vec!["Bund".to_string(), "Zehe".to_string(), "Tasse".to_string()]
+            )]
+        );
+    }
+}